@@ -1,5 +1,12 @@
+use crate::core::export::timetable_csv::CsvLayout;
+use crate::core::lib::generated_fahrplan::ReportFormat;
+use crate::core::lib::time_rounding::RoundingGranularity;
+use crate::core::schedules::combine::CombineMethod;
+use crate::input::variables::VariableConfig;
 use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
+use time::macros::format_description;
+use time::Date;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -11,9 +18,17 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum CliCommand {
     GenerateFahrplan(GenerateFahrplanArgs),
+    Validate(ValidateArgs),
+    ConvertConfig(ConvertConfigArgs),
 
     #[command(subcommand)]
     Schedule(CliScheduleCommand),
+
+    #[command(subcommand)]
+    Export(CliExportCommand),
+
+    #[command(subcommand)]
+    Import(CliImportCommand),
 }
 
 /// Actions related to schedules
@@ -21,14 +36,85 @@ pub enum CliCommand {
 pub enum CliScheduleCommand {
     Apply(ApplyScheduleArgs),
     Generate(GenerateScheduleArgs),
+    Transform(TransformScheduleArgs),
+}
+
+/// Exports of generated Fahrplaene
+#[derive(Subcommand)]
+pub enum CliExportCommand {
+    Bildfahrplan(ExportBildfahrplanArgs),
+    Csv(ExportCsvArgs),
+}
+
+/// Imports of external timetables
+#[derive(Subcommand)]
+pub enum CliImportCommand {
+    Csv(ImportCsvArgs),
+    Gtfs(ImportGtfsArgs),
 }
 
 /// Copy trains and delay them by given time
 #[derive(Args, Debug)]
 pub struct GenerateFahrplanArgs {
-    /// Path to config file
+    /// Path to config file in XML, TOML or YAML format
+    #[arg(short, long)]
+    pub config: PathBuf,
+
+    /// Sets a variable of the config, the value replaces the one defined in the config
+    #[arg(long = "set", value_name = "NAME=VALUE")]
+    pub variables: Vec<VariableConfig>,
+
+    /// Run the generation without writing any files and print which files would be written
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Generate and write all valid Zuege and report the invalid ones instead of stopping at the first error
+    #[arg(long)]
+    pub collect_errors: bool,
+
+    /// Write the conflicts found between the generated Zuege to this file as JSON
+    #[arg(long)]
+    pub conflict_report: Option<PathBuf>,
+
+    /// Render the generated Zuege as Bildfahrplan to this SVG file
+    #[arg(long)]
+    pub bildfahrplan: Option<PathBuf>,
+
+    /// Write the timetables of the generated Zuege to this CSV file
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+
+    /// Layout of the CSV file
+    #[arg(long, value_enum, default_value_t = CsvLayout::PerZug, requires = "csv")]
+    pub csv_layout: CsvLayout,
+
+    /// Print a summary of the generated Zuege in this format instead of the usual output
+    #[arg(long, value_enum, conflicts_with = "dry_run")]
+    pub report: Option<ReportFormat>,
+}
+
+/// Checks a config file and all files referenced by it without generating anything
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    /// Path to config file in XML, TOML or YAML format
     #[arg(short, long)]
     pub config: PathBuf,
+
+    /// Sets a variable of the config, the value replaces the one defined in the config
+    #[arg(long = "set", value_name = "NAME=VALUE")]
+    pub variables: Vec<VariableConfig>,
+}
+
+/// Converts a config file between XML, TOML and YAML, the formats are chosen by the file extensions
+#[derive(Args, Debug)]
+pub struct ConvertConfigArgs {
+    /// Path to config file
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Path to converted config file, relative paths in the config are kept unchanged
+    #[arg(short, long)]
+    pub output: PathBuf,
 }
 
 /// Updates times in specified .trn files according to provided schedule file
@@ -41,16 +127,192 @@ pub struct ApplyScheduleArgs {
     /// .trn files to modify
     #[arg(short, long, num_args = 1..)]
     pub trn_files: Vec<PathBuf>,
+
+    /// Zusi data dir, if set the Buchfahrplan files linked by the .trn files are updated as well
+    #[arg(short, long)]
+    pub data_dir: Option<PathBuf>,
 }
 
-/// Generates a schedule file based on the given .trn file
+/// Generates a schedule file based on the given .trn files or Buchfahrplan file
 #[derive(Args, Debug)]
 pub struct GenerateScheduleArgs {
-    /// Path to trn file
-    #[arg(short, long)]
-    pub trn: String,
+    /// Paths to trn files of the same line, the schedules of multiple files are combined
+    #[arg(short, long, num_args = 1.., required_unless_present = "timetable", conflicts_with = "timetable")]
+    pub trn: Vec<String>,
+
+    /// How the driving and stop times of multiple trn files are combined
+    #[arg(short, long, value_enum, default_value_t = CombineMethod::Median)]
+    pub combine: CombineMethod,
+
+    /// Path to Buchfahrplan file (.timetable.xml)
+    #[arg(short = 'b', long)]
+    pub timetable: Option<String>,
+
+    /// Add km and FplLaufweg to the schedule entries (Buchfahrplan files only)
+    #[arg(long, requires = "timetable")]
+    pub extra_data: bool,
 
     /// Path where to create the schedule file
     #[arg(short, long)]
     pub schedule: String,
+}
+
+/// Creates a new schedule file with scaled driving times, recovery margins and minimum stop times
+#[derive(Args, Debug)]
+pub struct TransformScheduleArgs {
+    /// Path to the schedule file to transform
+    #[arg(short, long)]
+    pub schedule: PathBuf,
+
+    /// Path where to create the transformed schedule file
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Factor all driving times are multiplied with, must be positive
+    #[arg(long, default_value_t = 1., value_parser = parse_scale)]
+    pub scale: f64,
+
+    /// Recovery margin in seconds added to every driving time
+    #[arg(long, conflicts_with = "recovery_percent")]
+    pub recovery_seconds: Option<i64>,
+
+    /// Recovery margin in percent of every driving time, must be at least -100
+    #[arg(long, value_parser = parse_recovery_percent)]
+    pub recovery_percent: Option<f64>,
+
+    /// Minimum stop time in seconds for all stops
+    #[arg(long)]
+    pub min_stop_time: Option<i64>,
+
+    /// Round driving times to the nearest and stop times up to whole or half minutes
+    #[arg(long, value_enum)]
+    pub round: Option<RoundingGranularity>,
+}
+
+/// Renders a time-distance diagram (Bildfahrplan) of a generated Fahrplan as SVG
+#[derive(Args, Debug)]
+pub struct ExportBildfahrplanArgs {
+    /// Path to the .fpn file
+    #[arg(short, long)]
+    pub fahrplan: PathBuf,
+
+    /// Zusi data dir the paths of the Zuege in the .fpn file are relative to
+    #[arg(short, long)]
+    pub data_dir: PathBuf,
+
+    /// Path where to create the SVG file
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+/// Writes the timetables of a generated Fahrplan as CSV
+#[derive(Args, Debug)]
+pub struct ExportCsvArgs {
+    /// Path to the .fpn file
+    #[arg(short, long)]
+    pub fahrplan: PathBuf,
+
+    /// Zusi data dir the paths of the Zuege in the .fpn file are relative to
+    #[arg(short, long)]
+    pub data_dir: PathBuf,
+
+    /// Path where to create the CSV file
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// One row per FahrplanEintrag or one row per Betriebsstelle and one column per Zug
+    #[arg(short, long, value_enum, default_value_t = CsvLayout::PerZug)]
+    pub layout: CsvLayout,
+
+    /// Character separating the fields
+    #[arg(short, long, default_value_t = ',')]
+    pub separator: char,
+}
+
+/// Creates schedules and a Fahrplan config from a CSV timetable with one set of Betriebsstelle, Ankunft and Abfahrt columns per Zug
+#[derive(Args, Debug)]
+pub struct ImportCsvArgs {
+    /// Path to the CSV file
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Character separating the fields
+    #[arg(short, long, default_value_t = ',')]
+    pub separator: char,
+
+    /// Date the times in the CSV file belong to (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date)]
+    pub date: Date,
+
+    #[command(flatten)]
+    pub config: ImportConfigArgs,
+}
+
+/// Creates schedules and a Fahrplan config from the trips of a GTFS feed running at the given date
+#[derive(Args, Debug)]
+pub struct ImportGtfsArgs {
+    /// Path to the GTFS zip file
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Path to the file mapping GTFS stops to Betriebsstellen
+    #[arg(short, long)]
+    pub mapping: PathBuf,
+
+    /// Date of the trips to import (YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date)]
+    pub date: Date,
+
+    #[command(flatten)]
+    pub config: ImportConfigArgs,
+}
+
+/// Arguments of the Fahrplan config created by imports
+#[derive(Args, Debug)]
+pub struct ImportConfigArgs {
+    /// .trn files used as route templates, each Zug uses the one sharing the most consecutive Betriebsstellen (relative to the config file)
+    #[arg(short, long, num_args = 1.., required = true)]
+    pub templates: Vec<PathBuf>,
+
+    /// .trn file providing the rolling stock of all Zuege (relative to the config file)
+    #[arg(short, long)]
+    pub rolling_stock: PathBuf,
+
+    /// Path where to create the config file, the schedules are placed next to it
+    #[arg(short, long)]
+    pub config: PathBuf,
+
+    /// Zusi data dir (relative to the config file)
+    #[arg(short, long)]
+    pub data_dir: PathBuf,
+
+    /// Path to .fpn file to use for Streckenmodule and UTM data (relative to the config file)
+    #[arg(long)]
+    pub generate_from: PathBuf,
+
+    /// Path where to place the generated .fpn file (relative to the config file)
+    #[arg(long)]
+    pub generate_at: PathBuf,
+}
+
+fn parse_date(value: &str) -> Result<Date, String> {
+    Date::parse(value, format_description!("[year]-[month]-[day]")).map_err(|error| format!("{error}"))
+}
+
+fn parse_scale(value: &str) -> Result<f64, String> {
+    let scale: f64 = value.parse().map_err(|error| format!("{error}"))?;
+    if scale.is_finite() && scale > 0. {
+        Ok(scale)
+    } else {
+        Err("the scale must be a positive number".into())
+    }
+}
+
+fn parse_recovery_percent(value: &str) -> Result<f64, String> {
+    let percentage: f64 = value.parse().map_err(|error| format!("{error}"))?;
+    if percentage.is_finite() && percentage >= -100. {
+        Ok(percentage)
+    } else {
+        Err("the recovery margin must be a number of at least -100 percent".into())
+    }
 }
\ No newline at end of file
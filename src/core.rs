@@ -1,4 +1,6 @@
 pub mod generate_fahrplan;
 pub mod schedules;
 pub mod replace_rolling_stock;
-pub mod lib;
\ No newline at end of file
+pub mod lib;
+pub mod export;
+pub mod import;
\ No newline at end of file
@@ -0,0 +1,3 @@
+pub mod bildfahrplan;
+pub mod read_fahrplan_zuege;
+pub mod timetable_csv;
\ No newline at end of file
@@ -0,0 +1,286 @@
+use crate::core::lib::generated_zug::RawGeneratedZug;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use thiserror::Error;
+use time::{Duration, PrimitiveDateTime};
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_name::FahrplanName;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::FahrplanZeile;
+use zusi_xml_lib::xml::zusi::zug::Zug;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BildfahrplanError {
+    #[error("None of the Zuege has a Buchfahrplan with at least two Betriebsstellen to create the distance axis from.")]
+    NoDistanceAxis,
+
+    #[error("None of the Zuege has times at the Betriebsstellen of the distance axis.")]
+    NoTimes,
+}
+
+const COLORS: [&str; 8] = ["#d62728", "#1f77b4", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b", "#e377c2", "#17becf"];
+const MARGIN_LEFT: f64 = 60.;
+const MARGIN_TOP: f64 = 150.;
+const MARGIN_BOTTOM: f64 = 60.;
+const PLOT_WIDTH: f64 = 1000.;
+const PIXELS_PER_MINUTE: f64 = 4.;
+const GRID_MINUTES: i64 = 10;
+
+/// Betriebsstelle on the distance axis
+#[derive(Debug, Clone, PartialEq)]
+struct AxisStation {
+    name: String,
+
+    /// Distance to the first Betriebsstelle in meters
+    position: f64,
+
+    km: Option<f32>,
+}
+
+/// Renders a time-distance diagram of all Zuege as SVG.
+/// The distance axis is taken from the Buchfahrplan with the most Betriebsstellen, positioned by FplLaufweg and labelled with Fplkm.
+/// Zuege are drawn at all Betriebsstellen of the axis they have times for, colored by Gattung.
+pub fn render_bildfahrplan(zuege: &[RawGeneratedZug]) -> Result<String, BildfahrplanError> {
+    let axis = zuege
+        .iter()
+        .filter_map(|zug| zug.buchfahrplan.as_ref())
+        .map(|buchfahrplan| distance_axis(&buchfahrplan.fahrplan_zeilen))
+        .fold(vec![], |longest: Vec<AxisStation>, axis| if axis.len() > longest.len() { axis } else { longest });
+    if axis.len() < 2 {
+        return Err(BildfahrplanError::NoDistanceAxis);
+    }
+
+    let zug_lines: Vec<(&Zug, Vec<Vec<(f64, PrimitiveDateTime)>>)> = zuege
+        .iter()
+        .map(|zug| (&zug.zug, train_lines(&zug.zug, &axis)))
+        .filter(|(_, lines)| !lines.is_empty())
+        .collect();
+    let times = zug_lines.iter().flat_map(|(_, lines)| lines.iter().flatten().map(|(_, time)| *time));
+    let (Some(first), Some(last)) = (times.clone().min(), times.max()) else {
+        return Err(BildfahrplanError::NoTimes);
+    };
+    let start = round_to_grid(first, false);
+    let end = round_to_grid(last, true);
+
+    // unwrap: the axis has at least two entries
+    let length = axis.last().unwrap().position.max(1.);
+    let x = |position: f64| MARGIN_LEFT + position / length * PLOT_WIDTH;
+    let y = |time: PrimitiveDateTime| MARGIN_TOP + (time - start).whole_seconds() as f64 / 60. * PIXELS_PER_MINUTE;
+    let plot_bottom = y(end);
+    let width = MARGIN_LEFT * 2. + PLOT_WIDTH;
+    let height = plot_bottom + MARGIN_BOTTOM;
+
+    let gattungen: BTreeMap<&str, &str> = zug_lines
+        .iter()
+        .map(|(zug, _)| zug.gattung.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .zip(COLORS.iter().cycle().copied())
+        .collect();
+
+    let mut svg = String::new();
+    // writing to a String can't fail
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.0} {height:.0}" font-family="sans-serif" font-size="11">"#).unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+
+    let mut time = start;
+    while time <= end {
+        let stroke = if time.minute() == 0 { "#999999" } else { "#dddddd" };
+        writeln!(svg, r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{stroke}"/>"#, x(0.), y(time), x(length), y(time)).unwrap();
+        writeln!(svg, r#"<text x="{:.1}" y="{:.1}" text-anchor="end" dominant-baseline="middle">{:02}:{:02}</text>"#, MARGIN_LEFT - 6., y(time), time.hour(), time.minute()).unwrap();
+        time += Duration::minutes(GRID_MINUTES);
+    }
+
+    for station in &axis {
+        let label = match station.km {
+            Some(km) => format!("{} ({km:.1})", station.name),
+            None => station.name.clone(),
+        };
+        writeln!(svg, r#"<line x1="{0:.1}" y1="{1:.1}" x2="{0:.1}" y2="{2:.1}" stroke="#999999"/>"#, x(station.position), MARGIN_TOP, plot_bottom).unwrap();
+        writeln!(svg, r#"<text transform="translate({:.1},{:.1}) rotate(-60)">{}</text>"#, x(station.position), MARGIN_TOP - 6., escape(&label)).unwrap();
+    }
+
+    for (zug, lines) in &zug_lines {
+        let color = gattungen[zug.gattung.as_str()];
+        let name = escape(&format!("{} {}", zug.gattung, zug.nummer));
+        for line in lines {
+            let points: Vec<String> = line.iter().map(|(position, time)| format!("{:.1},{:.1}", x(*position), y(*time))).collect();
+            writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="1.5"><title>{name}</title></polyline>"#, points.join(" ")).unwrap();
+        }
+        if let Some((position, time)) = lines.first().and_then(|line| line.first()) {
+            writeln!(svg, r#"<text x="{:.1}" y="{:.1}" fill="{color}" font-size="9">{}</text>"#, x(*position) + 3., y(*time) - 3., escape(&zug.nummer)).unwrap();
+        }
+    }
+
+    for (index, (gattung, color)) in gattungen.iter().enumerate() {
+        let legend_x = MARGIN_LEFT + index as f64 * 100.;
+        let legend_y = plot_bottom + MARGIN_BOTTOM / 2.;
+        writeln!(svg, r#"<line x1="{:.1}" y1="{legend_y:.1}" x2="{:.1}" y2="{legend_y:.1}" stroke="{color}" stroke-width="3"/>"#, legend_x, legend_x + 20.).unwrap();
+        writeln!(svg, r#"<text x="{:.1}" y="{legend_y:.1}" dominant-baseline="middle">{}</text>"#, legend_x + 26., escape(gattung)).unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// All named FahrplanZeilen, duplicate FahrplanZeilen for Regelgleis and Gegengleis are only considered once.
+fn distance_axis(fahrplan_zeilen: &[FahrplanZeile]) -> Vec<AxisStation> {
+    let mut stations: Vec<AxisStation> = vec![];
+    for zeile in fahrplan_zeilen {
+        if let Some(FahrplanName { fahrplan_name_text, .. }) = &zeile.fahrplan_name {
+            if stations.last().is_some_and(|last| &last.name == fahrplan_name_text) {
+                continue;
+            }
+            stations.push(AxisStation {
+                name: fahrplan_name_text.clone(),
+                position: zeile.fahrplan_laufweg as f64,
+                km: zeile.fahrplan_km.first().map(|fahrplan_km| fahrplan_km.km),
+            });
+        }
+    }
+    match stations.first().map(|first| first.position) {
+        Some(offset) => stations.into_iter().map(|station| AxisStation { position: station.position - offset, ..station }).collect(),
+        None => stations,
+    }
+}
+
+/// Points of the Zug at the Betriebsstellen of the axis, split wherever the Zug leaves the axis.
+fn train_lines(zug: &Zug, axis: &[AxisStation]) -> Vec<Vec<(f64, PrimitiveDateTime)>> {
+    let mut lines = vec![];
+    let mut line = vec![];
+    for eintrag in &zug.fahrplan_eintraege {
+        if eintrag.ankunft.is_none() && eintrag.abfahrt.is_none() {
+            continue;
+        }
+        match axis.iter().find(|station| station.name == eintrag.betriebsstelle) {
+            Some(station) => {
+                line.extend(eintrag.ankunft.into_iter().chain(eintrag.abfahrt).map(|time| (station.position, time)));
+            },
+            None => lines.push(std::mem::take(&mut line)),
+        }
+    }
+    lines.push(line);
+    lines.into_iter().filter(|line| line.len() > 1).collect()
+}
+
+fn round_to_grid(time: PrimitiveDateTime, up: bool) -> PrimitiveDateTime {
+    let grid = GRID_MINUTES * 60;
+    let seconds = (time - time.date().midnight()).whole_seconds();
+    let mut rounded = seconds - seconds.rem_euclid(grid);
+    if up && rounded < seconds {
+        rounded += grid;
+    }
+    time.date().midnight() + Duration::seconds(rounded)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+    use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_km::FahrplanKm;
+    use zusi_xml_lib::xml::zusi::buchfahrplan::Buchfahrplan;
+    use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+    use zusi_xml_lib::xml::zusi::lib::utm::UTM;
+    use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
+    use zusi_xml_lib::xml::zusi::zug::fahrzeug_varianten::FahrzeugVarianten;
+
+    fn zeile(name: Option<&str>, laufweg: f32, km: f32, rgl_ggl: i32) -> FahrplanZeile {
+        FahrplanZeile::builder()
+            .fahrplan_regelgleis_gegengleis(rgl_ggl)
+            .fahrplan_laufweg(laufweg)
+            .fahrplan_km(vec![FahrplanKm::builder().km(km).build()])
+            .fahrplan_name(name.map(|name| FahrplanName::builder().fahrplan_name_text(name.into()).build()))
+            .build()
+    }
+
+    fn fahrplan_zeilen() -> Vec<FahrplanZeile> {
+        vec![
+            zeile(None, 1000., 0.5, 0),
+            zeile(Some("A"), 1500., 1., 0),
+            zeile(Some("B"), 3500., 3., 1),
+            zeile(Some("B"), 3500., 3., 2),
+            zeile(None, 4000., 3.5, 1),
+            zeile(Some("C"), 6500., 6., 1),
+        ]
+    }
+
+    fn zug(gattung: &str, nummer: &str, fahrplan_eintraege: Vec<FahrplanEintrag>, with_buchfahrplan: bool) -> RawGeneratedZug {
+        RawGeneratedZug {
+            zug: Zug::builder()
+                .gattung(gattung.into())
+                .nummer(nummer.into())
+                .fahrplan_datei(Datei::builder().build())
+                .fahrzeug_varianten(FahrzeugVarianten::builder().build())
+                .fahrplan_eintraege(fahrplan_eintraege)
+                .build(),
+            buchfahrplan: with_buchfahrplan.then(|| Buchfahrplan::builder()
+                .datei_fpn(Datei::builder().build())
+                .datei_trn(Datei::builder().build())
+                .utm(UTM::builder().build())
+                .fahrplan_zeilen(fahrplan_zeilen())
+                .build()
+            ),
+        }
+    }
+
+    fn eintrag(betriebsstelle: &str, ankunft: Option<PrimitiveDateTime>, abfahrt: Option<PrimitiveDateTime>) -> FahrplanEintrag {
+        FahrplanEintrag::builder().betriebsstelle(betriebsstelle.into()).ankunft(ankunft).abfahrt(abfahrt).build()
+    }
+
+    #[test]
+    fn test_distance_axis() {
+        assert_eq!(
+            distance_axis(&fahrplan_zeilen()),
+            vec![
+                AxisStation { name: "A".into(), position: 0., km: Some(1.) },
+                AxisStation { name: "B".into(), position: 2000., km: Some(3.) },
+                AxisStation { name: "C".into(), position: 5000., km: Some(6.) },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_train_lines() {
+        let axis = distance_axis(&fahrplan_zeilen());
+        let zug = zug("RB", "20001", vec![
+            eintrag("A", None, Some(datetime!(2024-06-20 08:00:00))),
+            eintrag("B", Some(datetime!(2024-06-20 08:03:00)), Some(datetime!(2024-06-20 08:04:00))),
+            eintrag("B", None, None),
+            eintrag("X", Some(datetime!(2024-06-20 08:06:00)), Some(datetime!(2024-06-20 08:07:00))),
+            eintrag("C", Some(datetime!(2024-06-20 08:10:00)), None),
+        ], false);
+
+        assert_eq!(
+            train_lines(&zug.zug, &axis),
+            vec![vec![
+                (0., datetime!(2024-06-20 08:00:00)),
+                (2000., datetime!(2024-06-20 08:03:00)),
+                (2000., datetime!(2024-06-20 08:04:00)),
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_render_bildfahrplan() {
+        let zuege = vec![
+            zug("RB", "20001", vec![
+                eintrag("A", None, Some(datetime!(2024-06-20 08:00:00))),
+                eintrag("C", Some(datetime!(2024-06-20 08:10:00)), None),
+            ], true),
+            zug("RE", "30001", vec![
+                eintrag("C", None, Some(datetime!(2024-06-20 08:05:00))),
+                eintrag("A", Some(datetime!(2024-06-20 08:12:00)), None),
+            ], false),
+        ];
+
+        let svg = render_bildfahrplan(&zuege).unwrap();
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="1120" height="290""#));
+        assert!(svg.contains(r#"<polyline points="60.0,150.0 1060.0,190.0" fill="none" stroke="#d62728" stroke-width="1.5"><title>RB 20001</title></polyline>"#));
+        assert!(svg.contains(r#"<polyline points="1060.0,170.0 60.0,198.0" fill="none" stroke="#1f77b4" stroke-width="1.5"><title>RE 30001</title></polyline>"#));
+        assert!(svg.contains(r#"rotate(-60)">B (3.0)</text>"#));
+        assert!(svg.contains(">08:20</text>"));
+        assert_eq!(render_bildfahrplan(&zuege[1..]), Err(BildfahrplanError::NoDistanceAxis));
+    }
+}
\ No newline at end of file
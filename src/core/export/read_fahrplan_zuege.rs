@@ -0,0 +1,27 @@
+use crate::core::lib::file_error::FileError;
+use crate::core::lib::generated_zug::RawGeneratedZug;
+use crate::core::lib::helpers::{read_buchfahrplan, read_fahrplan, read_zug};
+use std::path::{Path, PathBuf};
+use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+use zusi_xml_lib::xml::zusi::lib::path::prejoined_zusi_path::PrejoinedZusiPath;
+
+/// Reads all Zuege of a Fahrplan together with their Buchfahrplaene.
+/// The paths of the Zuege and Buchfahrplaene are resolved relative to the Zusi data dir.
+pub fn read_fahrplan_zuege<P: AsRef<Path> + Into<PathBuf>>(fahrplan_path: P, data_dir: &Path) -> Result<Vec<RawGeneratedZug>, FileError> {
+    let fahrplan = read_fahrplan(fahrplan_path)?;
+    fahrplan.value.zug_dateien
+        .iter()
+        .map(|eintrag| {
+            let zug_path = PrejoinedZusiPath::new(data_dir, eintrag.datei.dateiname.clone());
+            let zug = read_zug(zug_path.full_path())?.value;
+            let buchfahrplan = match &zug.buchfahrplan_roh_datei {
+                Some(Datei { dateiname, .. }) => {
+                    let buchfahrplan_path = PrejoinedZusiPath::new(data_dir, dateiname.clone());
+                    Some(read_buchfahrplan(buchfahrplan_path.full_path())?.value)
+                },
+                None => None,
+            };
+            Ok(RawGeneratedZug { zug, buchfahrplan })
+        })
+        .collect()
+}
\ No newline at end of file
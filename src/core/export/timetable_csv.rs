@@ -0,0 +1,201 @@
+use crate::core::lib::generated_zug::RawGeneratedZug;
+use clap::ValueEnum;
+use time::PrimitiveDateTime;
+use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::fahrzeug_verband_aktion::FahrzeugVerbandAktion;
+use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvLayout {
+    /// One row per FahrplanEintrag, grouped by Zug
+    #[default]
+    PerZug,
+
+    /// One row per Betriebsstelle and one column per Zug, like a Kursbuch table
+    Matrix,
+}
+
+/// Writes the FahrplanEintraege of all Zuege as CSV in the given layout.
+pub fn zuege_to_csv(zuege: &[RawGeneratedZug], layout: CsvLayout, separator: char) -> String {
+    let rows = match layout {
+        CsvLayout::PerZug => per_zug_rows(zuege),
+        CsvLayout::Matrix => matrix_rows(zuege),
+    };
+    rows
+        .into_iter()
+        .map(|row| {
+            let fields: Vec<String> = row.iter().map(|field| csv_field(field, separator)).collect();
+            fields.join(&separator.to_string()) + "\n"
+        })
+        .collect()
+}
+
+fn per_zug_rows(zuege: &[RawGeneratedZug]) -> Vec<Vec<String>> {
+    let header = ["Gattung", "Nummer", "Betriebsstelle", "Ankunft", "Abfahrt", "Signal", "FahrzeugVerbandAktion"];
+    let mut rows = vec![header.map(String::from).to_vec()];
+    for zug in zuege {
+        rows.extend(zug.zug.fahrplan_eintraege.iter().map(|eintrag| vec![
+            zug.zug.gattung.clone(),
+            zug.zug.nummer.clone(),
+            eintrag.betriebsstelle.clone(),
+            eintrag.ankunft.map(format_date_time).unwrap_or_default(),
+            eintrag.abfahrt.map(format_date_time).unwrap_or_default(),
+            eintrag.fahrplan_signal_eintraege
+                .iter()
+                .map(|signal_eintrag| signal_eintrag.fahrplan_signal.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            if eintrag.fahrzeug_verband_aktion == FahrzeugVerbandAktion::default() {
+                String::new()
+            } else {
+                format!("{:?}", eintrag.fahrzeug_verband_aktion)
+            },
+        ]));
+    }
+    rows
+}
+
+/// Betriebsstellen are ordered by their first appearance, Betriebsstellen only passed by later Zuege are inserted
+/// after the previous Betriebsstelle of that Zug. Only the first FahrplanEintrag with times per Betriebsstelle and Zug is used.
+fn matrix_rows(zuege: &[RawGeneratedZug]) -> Vec<Vec<String>> {
+    let mut betriebsstellen: Vec<&str> = vec![];
+    for zug in zuege {
+        let mut previous: Option<usize> = None;
+        for eintrag in zug.zug.fahrplan_eintraege.iter().filter(|eintrag| has_times(eintrag)) {
+            let index = match betriebsstellen.iter().position(|betriebsstelle| *betriebsstelle == eintrag.betriebsstelle) {
+                Some(index) => index,
+                None => {
+                    let index = previous.map(|previous| previous + 1).unwrap_or(0);
+                    betriebsstellen.insert(index, &eintrag.betriebsstelle);
+                    index
+                },
+            };
+            previous = Some(index);
+        }
+    }
+
+    let eintrag_at = |zug: &RawGeneratedZug, betriebsstelle: &str| -> Option<&FahrplanEintrag> {
+        zug.zug.fahrplan_eintraege
+            .iter()
+            .find(|eintrag| eintrag.betriebsstelle == betriebsstelle && has_times(eintrag))
+    };
+
+    let mut header = vec!["Betriebsstelle".to_string(), String::new()];
+    header.extend(zuege.iter().map(|zug| format!("{} {}", zug.zug.gattung, zug.zug.nummer)));
+    let mut rows = vec![header];
+    for betriebsstelle in betriebsstellen {
+        let eintraege: Vec<Option<&FahrplanEintrag>> = zuege.iter().map(|zug| eintrag_at(zug, betriebsstelle)).collect();
+        let has_stops = eintraege.iter().flatten().any(|eintrag| eintrag.ankunft.is_some() && eintrag.abfahrt.is_some());
+        if has_stops {
+            rows.push(matrix_row(betriebsstelle, "an", &eintraege, |eintrag| eintrag.ankunft));
+            rows.push(matrix_row(betriebsstelle, "ab", &eintraege, |eintrag| eintrag.abfahrt.or(eintrag.ankunft)));
+        } else {
+            rows.push(matrix_row(betriebsstelle, "", &eintraege, |eintrag| eintrag.abfahrt.or(eintrag.ankunft)));
+        }
+    }
+    rows
+}
+
+fn matrix_row(betriebsstelle: &str, kind: &str, eintraege: &[Option<&FahrplanEintrag>], time: impl Fn(&FahrplanEintrag) -> Option<PrimitiveDateTime>) -> Vec<String> {
+    let mut row = vec![betriebsstelle.to_string(), kind.to_string()];
+    row.extend(eintraege.iter().map(|eintrag| eintrag.and_then(&time).map(format_time).unwrap_or_default()));
+    row
+}
+
+fn has_times(eintrag: &FahrplanEintrag) -> bool {
+    eintrag.ankunft.is_some() || eintrag.abfahrt.is_some()
+}
+
+fn format_date_time(date_time: PrimitiveDateTime) -> String {
+    format!("{} {}", date_time.date(), format_time(date_time))
+}
+
+fn format_time(date_time: PrimitiveDateTime) -> String {
+    format!("{:02}:{:02}:{:02}", date_time.hour(), date_time.minute(), date_time.second())
+}
+
+/// Quotes the field if it contains the separator, quotes or line breaks.
+fn csv_field(field: &str, separator: char) -> String {
+    if field.contains(separator) || field.contains(['"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+    use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+    use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::fahrplan_signal_eintrag::FahrplanSignalEintrag;
+    use zusi_xml_lib::xml::zusi::zug::fahrzeug_varianten::FahrzeugVarianten;
+    use zusi_xml_lib::xml::zusi::zug::Zug;
+
+    fn zug(nummer: &str, fahrplan_eintraege: Vec<FahrplanEintrag>) -> RawGeneratedZug {
+        RawGeneratedZug {
+            zug: Zug::builder()
+                .gattung("RB".into())
+                .nummer(nummer.into())
+                .fahrplan_datei(Datei::builder().build())
+                .fahrzeug_varianten(FahrzeugVarianten::builder().build())
+                .fahrplan_eintraege(fahrplan_eintraege)
+                .build(),
+            buchfahrplan: None,
+        }
+    }
+
+    fn zuege() -> Vec<RawGeneratedZug> {
+        vec![
+            zug("20001", vec![
+                FahrplanEintrag::builder()
+                    .betriebsstelle("B".into())
+                    .abfahrt(Some(datetime!(2024-06-20 08:00:00)))
+                    .fahrplan_signal_eintraege(vec![FahrplanSignalEintrag::builder().fahrplan_signal("N1".into()).build()])
+                    .build(),
+                FahrplanEintrag::builder().betriebsstelle("C, Hbf".into()).build(),
+                FahrplanEintrag::builder()
+                    .betriebsstelle("C, Hbf".into())
+                    .ankunft(Some(datetime!(2024-06-20 08:10:00)))
+                    .fahrzeug_verband_aktion(FahrzeugVerbandAktion::ZugDrehen)
+                    .build(),
+            ]),
+            zug("20003", vec![
+                FahrplanEintrag::builder().betriebsstelle("A".into()).abfahrt(Some(datetime!(2024-06-20 08:55:00))).build(),
+                FahrplanEintrag::builder()
+                    .betriebsstelle("B".into())
+                    .ankunft(Some(datetime!(2024-06-20 09:00:00)))
+                    .abfahrt(Some(datetime!(2024-06-20 09:00:30)))
+                    .build(),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn test_zuege_to_csv_per_zug() {
+        assert_eq!(
+            zuege_to_csv(&zuege(), CsvLayout::PerZug, ','),
+            concat!(
+                "Gattung,Nummer,Betriebsstelle,Ankunft,Abfahrt,Signal,FahrzeugVerbandAktion\n",
+                "RB,20001,B,,2024-06-20 08:00:00,N1,\n",
+                "RB,20001,\"C, Hbf\",,,,\n",
+                "RB,20001,\"C, Hbf\",2024-06-20 08:10:00,,,ZugDrehen\n",
+                "RB,20003,A,,2024-06-20 08:55:00,,\n",
+                "RB,20003,B,2024-06-20 09:00:00,2024-06-20 09:00:30,,\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_zuege_to_csv_matrix() {
+        assert_eq!(
+            zuege_to_csv(&zuege(), CsvLayout::Matrix, ';'),
+            concat!(
+                "Betriebsstelle;;RB 20001;RB 20003\n",
+                "A;;;08:55:00\n",
+                "B;an;;09:00:00\n",
+                "B;ab;08:00:00;09:00:30\n",
+                "C, Hbf;;08:10:00;\n",
+            ),
+        );
+    }
+}
\ No newline at end of file
@@ -1,9 +1,28 @@
-mod generate_zug;
+pub mod conflicts;
+pub mod fahrplan_header;
+pub mod generate_zug;
+pub mod includes;
+mod route_dependencies;
+pub mod str_module;
+pub mod templates;
+pub mod umlauf;
+pub mod validate_fahrplan;
+pub mod zug_collisions;
 
 use crate::core::lib::generated_zug::GeneratedZug;
+use crate::core::generate_fahrplan::conflicts::{find_conflicts, Conflict};
+use crate::core::generate_fahrplan::fahrplan_header::{apply_fahrplan_header, FahrplanHeaderError};
+use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::ResolvedRoutes;
 use crate::core::generate_fahrplan::generate_zug::{generate_zug, GenerateZugError};
+use crate::core::generate_fahrplan::includes::{resolve_includes, IncludeError};
+use crate::core::generate_fahrplan::route_dependencies::{sort_by_route_dependencies, RouteDependencyError};
+use crate::core::generate_fahrplan::str_module::{add_str_module, StrModuleError};
+use crate::core::generate_fahrplan::templates::{resolve_templates, TemplateError};
+use crate::core::generate_fahrplan::umlauf::{carry_rolling_stock, check_umlaeufe, UmlaufError};
+use crate::core::generate_fahrplan::zug_collisions::{check_zug_collisions, ZugCollisionError};
 use crate::core::generate_fahrplan::GenerateFahrplanError::ReadFahrplanTemplateError;
 use crate::core::lib::file_error::FileError;
+use crate::core::lib::generated_fahrplan::{AttachedBuchfahrplan, AttachedZug, GeneratedFahrplan};
 use crate::core::lib::helpers::{datei_from_prejoined_zusi_path, generate_buchfahrplan_path, generate_zug_path, read_fahrplan};
 use crate::core::lib::zug_nummer::ZugNummer;
 use crate::input::environment::zusi_environment::ZusiEnvironment;
@@ -27,15 +46,60 @@ pub enum GenerateFahrplanError {
         error: FileError,
     },
 
+    #[error("The included configs couldn't be resolved: {error}")]
+    IncludeError {
+        error: IncludeError,
+    },
+
+    #[error("The ZugTemplates couldn't be applied: {error}")]
+    TemplateError {
+        error: TemplateError,
+    },
+
+    #[error("The Streckenmodule couldn't be added: {error}")]
+    StrModuleError {
+        error: StrModuleError,
+    },
+
     #[error("A Zug couldn't be generated: {error}")]
     GenerateZugError {
         error: GenerateZugError,
     },
 
+    #[error("The Fahrplan header couldn't be applied: {error}")]
+    FahrplanHeaderError {
+        error: FahrplanHeaderError,
+    },
+
     #[error("A Zug couldn't be attached: {error}")]
     AttachZugError {
         error: FileError,
     },
+
+    #[error("The route dependencies between the Zuege couldn't be resolved: {error}")]
+    RouteDependencyError {
+        error: RouteDependencyError,
+    },
+
+    #[error("{} Zuege couldn't be generated, all other Zuege were written:{}", errors.len(), errors.iter().map(|error| format!("\n- {error}")).collect::<String>())]
+    GenerateZuegeErrors {
+        errors: Vec<GenerateZugError>,
+    },
+
+    #[error("The generated Zuege collide: {error}")]
+    ZugCollisionError {
+        error: ZugCollisionError,
+    },
+
+    #[error("{} connections of the Umlaeufe are invalid:{}", errors.len(), errors.iter().map(|error| format!("\n- {error}")).collect::<String>())]
+    UmlaufErrors {
+        errors: Vec<UmlaufError>,
+    },
+
+    #[error("{} conflicts were found between the generated Zuege:{}", conflicts.len(), conflicts.iter().map(|conflict| format!("\n- {conflict}")).collect::<String>())]
+    Conflicts {
+        conflicts: Vec<Conflict>,
+    },
 }
 
 impl From<GenerateZugError> for GenerateFahrplanError {
@@ -45,6 +109,19 @@ impl From<GenerateZugError> for GenerateFahrplanError {
 }
 
 pub fn generate_fahrplan(env: &ZusiEnvironment, config: FahrplanConfig) -> Result<(), GenerateFahrplanError> {
+    let generated_fahrplan = prepare_fahrplan(env, config)?;
+    write_fahrplan(generated_fahrplan)
+}
+
+/// Runs the whole generation without writing any files.
+/// If `collect_errors` is set in the config, Zuege which couldn't be generated are skipped and listed in the result.
+pub fn prepare_fahrplan(env: &ZusiEnvironment, mut config: FahrplanConfig) -> Result<GeneratedFahrplan, GenerateFahrplanError> {
+    resolve_includes(env, &mut config)
+        .map_err(|error| GenerateFahrplanError::IncludeError { error })?;
+    resolve_templates(&mut config)
+        .map_err(|error| GenerateFahrplanError::TemplateError { error })?;
+    let collect_errors = config.collect_errors;
+    let zug_collision_policy = config.zug_collisions;
     let generate_from = env.path_to_prejoined_zusi_path(&config.generate_from)
         .map_err(|error| GenerateFahrplanError::ReadFahrplanTemplateError { error })?;
     let generate_at = env.path_to_prejoined_zusi_path(&config.generate_at)
@@ -58,29 +135,96 @@ pub fn generate_fahrplan(env: &ZusiEnvironment, config: FahrplanConfig) -> Resul
     fahrplan.value.zug_dateien = vec![];
     fahrplan.value.zug_eintraege = vec![];
 
-    let zuege = config.zuege
+    if let Some(str_module) = &config.str_module {
+        add_str_module(env, str_module, &config.zuege, &mut fahrplan.value)
+            .map_err(|error| GenerateFahrplanError::StrModuleError { error })?;
+    }
+
+    let mut zug_configs = sort_by_route_dependencies(config.zuege)
+        .map_err(|error| GenerateFahrplanError::RouteDependencyError { error })?
         .into_iter()
-        .map(|train| generate_zug(env, &generate_at, train))
+        .map(|mut zug_config| {
+            zug_config.time_rounding = zug_config.time_rounding.or_else(|| config.time_rounding.clone());
+            zug_config
+        })
+        .collect::<Vec<_>>();
+    let umlauf_errors = carry_rolling_stock(&mut zug_configs, &config.umlaeufe);
+    if !umlauf_errors.is_empty() {
+        return Err(GenerateFahrplanError::UmlaufErrors { errors: umlauf_errors });
+    }
+    let mut resolved_routes = ResolvedRoutes::new();
+    let mut failed_zuege = vec![];
+
+    let mut zuege = zug_configs
+        .into_iter()
+        .map(|train| generate_zug(env, &generate_at, train, &mut resolved_routes))
+        .filter_map(|result| match result {
+            Err(error) if collect_errors => {
+                failed_zuege.push(error);
+                None
+            },
+            result => Some(result),
+        })
         .collect::<Result<Vec<_>, _>>()?
         .into_iter()
         .flatten()
         .collect::<Vec<_>>();
-    let zuege = sort_zuege(zuege);
+    let zug_collisions = check_zug_collisions(&mut zuege, zug_collision_policy)
+        .map_err(|error| GenerateFahrplanError::ZugCollisionError { error })?;
+    let umlauf_errors = check_umlaeufe(&mut zuege, &config.umlaeufe);
+    if !umlauf_errors.is_empty() {
+        return Err(GenerateFahrplanError::UmlaufErrors { errors: umlauf_errors });
+    }
+    let conflicts = match &config.conflict_detection {
+        Some(conflict_detection) => find_conflicts(&zuege, conflict_detection.headway),
+        None => vec![],
+    };
+    if config.conflict_detection.is_some_and(|conflict_detection| conflict_detection.fail) && !conflicts.is_empty() {
+        return Err(GenerateFahrplanError::Conflicts { conflicts });
+    }
+    if let Some(header) = &config.header {
+        apply_fahrplan_header(env, header, &mut fahrplan.value, &zuege)
+            .map_err(|error| GenerateFahrplanError::FahrplanHeaderError { error })?;
+    }
+    let zuege = sort_zuege(zuege)
+        .into_iter()
+        .map(|zug| attach_zug(&mut fahrplan, zug, &generate_at))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(GeneratedFahrplan {
+        path: generate_at,
+        fahrplan,
+        zuege,
+        failed_zuege,
+        zug_collisions,
+        conflicts,
+    })
+}
+
+/// Writes the generated Fahrplan and all its Zuege.
+/// Fails after writing if any Zuege were skipped because they couldn't be generated.
+pub fn write_fahrplan(generated_fahrplan: GeneratedFahrplan) -> Result<(), GenerateFahrplanError> {
+    let GeneratedFahrplan { path, fahrplan, zuege, failed_zuege, .. } = generated_fahrplan;
+
     zuege
         .into_iter()
-        .try_for_each(|zug| attach_zug(&mut fahrplan, zug, &generate_at))?;
+        .try_for_each(write_zug)?;
 
     let fahrplan: Zusi = fahrplan.into();
-    fahrplan.to_xml_file_by_path(generate_at.full_path(), true)
-        .map_err(|error| GenerateFahrplanError::WriteGeneratedFahrplanError { error: (generate_at.full_path(), error).into() })?;
+    fahrplan.to_xml_file_by_path(path.full_path(), true)
+        .map_err(|error| GenerateFahrplanError::WriteGeneratedFahrplanError { error: (path.full_path(), error).into() })?;
 
-    Ok(())
+    if failed_zuege.is_empty() {
+        Ok(())
+    } else {
+        Err(GenerateFahrplanError::GenerateZuegeErrors { errors: failed_zuege })
+    }
 }
 
-fn attach_zug(fahrplan: &mut TypedZusi<Fahrplan>, mut zug: GeneratedZug, fahrplan_path: &PrejoinedZusiPath) -> Result<(), GenerateFahrplanError> {
+fn attach_zug(fahrplan: &mut TypedZusi<Fahrplan>, mut zug: GeneratedZug, fahrplan_path: &PrejoinedZusiPath) -> Result<AttachedZug, GenerateFahrplanError> {
     let zug_path = generate_zug_path(&zug.zug, fahrplan_path);
 
-    if let Some(mut buchfahrplan) = zug.buchfahrplan {
+    let buchfahrplan = if let Some(mut buchfahrplan) = zug.buchfahrplan {
         let buchfahrplan_path = generate_buchfahrplan_path(&buchfahrplan, fahrplan_path);
 
         buchfahrplan.value.datei_fpn = datei_from_prejoined_zusi_path(fahrplan_path, true)
@@ -92,12 +236,14 @@ fn attach_zug(fahrplan: &mut TypedZusi<Fahrplan>, mut zug: GeneratedZug, fahrpla
         zug.zug.value.buchfahrplan_roh_datei = Some(datei_from_prejoined_zusi_path(&buchfahrplan_path, false)
             .map_err(|error| GenerateFahrplanError::AttachZugError { error })?);
 
-        let buchfahrplan: Zusi = buchfahrplan.into();
-        buchfahrplan.to_xml_file_by_path(buchfahrplan_path.full_path(), true)
-            .map_err(|error| GenerateFahrplanError::AttachZugError { error: (buchfahrplan_path.full_path(), error).into() })?;
-    }
+        Some(AttachedBuchfahrplan {
+            path: buchfahrplan_path,
+            buchfahrplan,
+        })
+    } else {
+        None
+    };
 
-    let zug: Zusi = zug.zug.into();
     fahrplan.value.zug_dateien.push(
         ZugDateiEintrag::builder()
             .datei(
@@ -106,6 +252,23 @@ fn attach_zug(fahrplan: &mut TypedZusi<Fahrplan>, mut zug: GeneratedZug, fahrpla
             )
             .build()
     );
+    Ok(AttachedZug {
+        path: zug_path,
+        zug: zug.zug,
+        buchfahrplan,
+        origin: zug.origin,
+    })
+}
+
+fn write_zug(zug: AttachedZug) -> Result<(), GenerateFahrplanError> {
+    if let Some(AttachedBuchfahrplan { path, buchfahrplan }) = zug.buchfahrplan {
+        let buchfahrplan: Zusi = buchfahrplan.into();
+        buchfahrplan.to_xml_file_by_path(path.full_path(), true)
+            .map_err(|error| GenerateFahrplanError::AttachZugError { error: (path.full_path(), error).into() })?;
+    }
+
+    let zug_path = zug.path;
+    let zug: Zusi = zug.zug.into();
     zug.to_xml_file_by_path(zug_path.full_path(), true)
         .map_err(|error| GenerateFahrplanError::AttachZugError { error: (zug_path.full_path(), error).into() })?;
     Ok(())
@@ -123,13 +286,16 @@ fn sort_zuege(zuege: Vec<GeneratedZug>) -> Vec<GeneratedZug> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::input::fahrplan_config::{RouteConfig, RoutePart, RoutePartSource, ZugConfig};
+    use crate::core::lib::generated_fahrplan::PlannedFile;
+    use crate::input::fahrplan_config::{AnfangsZeitConfig, AnfangsZeitSource, FahrplanHeaderConfig, HeaderFileConfig, RouteConfig, RoutePart, RoutePartSource, ZugCollisionPolicy, ZugConfig};
     use crate::input::rolling_stock_config::RollingStockConfig;
     use glob::glob;
     use serde_helpers::xml::test_utils::{cleanup_xml, read_xml_file};
     use std::fs;
     use std::path::PathBuf;
     use tempfile::tempdir;
+    use time::macros::datetime;
+    use time::Duration;
 
     const FROM_FPN: &str = r#"
         <?xml version="1.0" encoding="UTF-8"?>
@@ -455,10 +621,21 @@ mod tests {
         let config = FahrplanConfig {
             generate_at: at_fpn_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
             generate_from: from_fpn_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+            collect_errors: false,
+            zug_collisions: ZugCollisionPolicy::Warn,
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![],
+            includes: vec![],
             zuege: vec![
                 ZugConfig {
                     nummer: "20001".into(),
                     gattung: "RB".into(),
+                    extends: None,
                     zuglauf: "".into(),
                     fahrplan_gruppe: "".into(),
                     meta_data: None,
@@ -471,15 +648,20 @@ mod tests {
                                 apply_schedule: None,
                             },
                         ],
+                        includes: vec![],
                     },
                     rolling_stock: RollingStockConfig {
                         path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
                     },
                     copy_delay_config: None,
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
                 },
                 ZugConfig {
                     nummer: "10001".into(),
                     gattung: "RB".into(),
+                    extends: None,
                     zuglauf: "".into(),
                     fahrplan_gruppe: "".into(),
                     meta_data: None,
@@ -492,11 +674,15 @@ mod tests {
                                 apply_schedule: None,
                             },
                         ],
+                        includes: vec![],
                     },
                     rolling_stock: RollingStockConfig {
                         path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
                     },
                     copy_delay_config: None,
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
                 },
             ],
         };
@@ -574,10 +760,21 @@ mod tests {
         let config = FahrplanConfig {
             generate_at: at_fpn_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
             generate_from: from_fpn_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+            collect_errors: false,
+            zug_collisions: ZugCollisionPolicy::Warn,
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![],
+            includes: vec![],
             zuege: vec![
                 ZugConfig {
                     nummer: "20001".into(),
                     gattung: "RB".into(),
+                    extends: None,
                     zuglauf: "".into(),
                     fahrplan_gruppe: "".into(),
                     meta_data: None,
@@ -590,15 +787,20 @@ mod tests {
                                 apply_schedule: None,
                             },
                         ],
+                        includes: vec![],
                     },
                     rolling_stock: RollingStockConfig {
                         path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
                     },
                     copy_delay_config: None,
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
                 },
                 ZugConfig {
                     nummer: "10001".into(),
                     gattung: "RB".into(),
+                    extends: None,
                     zuglauf: "".into(),
                     fahrplan_gruppe: "".into(),
                     meta_data: None,
@@ -611,11 +813,15 @@ mod tests {
                                 apply_schedule: None,
                             },
                         ],
+                        includes: vec![],
                     },
                     rolling_stock: RollingStockConfig {
                         path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
                     },
                     copy_delay_config: None,
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
                 },
             ],
         };
@@ -657,4 +863,260 @@ mod tests {
             at_fpn_path,
         ]);
     }
+
+    #[test]
+    fn test_prepare_fahrplan_writes_no_files() {
+        let tmp_dir = tempdir().unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().to_owned(),
+        };
+
+        let from_fpn_path = tmp_dir.path().join("test/dev/test.fpn");
+        fs::create_dir_all(from_fpn_path.parent().unwrap()).unwrap();
+        fs::write(&from_fpn_path, FROM_FPN).unwrap();
+
+        let at_fpn_path = tmp_dir.path().join("test/out/test.fpn");
+
+        let route1_path = tmp_dir.path().join("test/out/test/RB10001.trn");
+        fs::create_dir_all(route1_path.parent().unwrap()).unwrap();
+        fs::write(&route1_path, ROUTE1_TEMPLATE_TRN).unwrap();
+
+        let route1_template_path = tmp_dir.path().join("test/dev/test/RB10001.trn");
+        fs::create_dir_all(route1_template_path.parent().unwrap()).unwrap();
+        fs::write(&route1_template_path, ROUTE1_TEMPLATE_TRN).unwrap();
+
+        let rolling_stock_path = tmp_dir.path().join("test/dev/test/rolling-stock/Triebwagen-A.trn");
+        fs::create_dir_all(rolling_stock_path.parent().unwrap()).unwrap();
+        fs::write(&rolling_stock_path, ROLLING_STOCK_TEMPLATE_TRN).unwrap();
+
+        let config = FahrplanConfig {
+            generate_at: at_fpn_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+            generate_from: from_fpn_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+            collect_errors: false,
+            zug_collisions: ZugCollisionPolicy::Warn,
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![],
+            includes: vec![],
+            zuege: vec![
+                ZugConfig {
+                    nummer: "10001".into(),
+                    gattung: "RB".into(),
+                    extends: None,
+                    zuglauf: "".into(),
+                    fahrplan_gruppe: "".into(),
+                    meta_data: None,
+                    route: RouteConfig {
+                        parts: vec![
+                            RoutePart {
+                                source: RoutePartSource::TrainFileByPath { path: route1_template_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned() },
+                                start_fahrzeug_verband_aktion: None,
+                                time_fix: None,
+                                apply_schedule: None,
+                            },
+                        ],
+                        includes: vec![],
+                    },
+                    rolling_stock: RollingStockConfig {
+                        path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+                    },
+                    copy_delay_config: None,
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
+                },
+            ],
+        };
+
+        let report = prepare_fahrplan(&env, config).unwrap().dry_run_report();
+
+        assert_eq!(report.files, vec![
+            PlannedFile { path: route1_path.clone(), exists: true },
+            PlannedFile { path: at_fpn_path.clone(), exists: false },
+        ]);
+        assert_eq!(report.zug_dateien, vec![PathBuf::from("test/out/test/RB10001.trn")]);
+
+        assert_eq!(fs::read_to_string(&route1_path).unwrap(), ROUTE1_TEMPLATE_TRN);
+        assert!(!at_fpn_path.exists());
+    }
+
+    #[test]
+    fn test_generate_fahrplan_collect_errors() {
+        let tmp_dir = tempdir().unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().to_owned(),
+        };
+
+        let from_fpn_path = tmp_dir.path().join("test/dev/test.fpn");
+        fs::create_dir_all(from_fpn_path.parent().unwrap()).unwrap();
+        fs::write(&from_fpn_path, FROM_FPN).unwrap();
+
+        let at_fpn_path = tmp_dir.path().join("test/out/test.fpn");
+
+        let route1_path = tmp_dir.path().join("test/out/test/RB10001.trn");
+        let route1_template_path = tmp_dir.path().join("test/dev/test/RB10001.trn");
+        fs::create_dir_all(route1_template_path.parent().unwrap()).unwrap();
+        fs::write(&route1_template_path, ROUTE1_TEMPLATE_TRN).unwrap();
+
+        let route2_path = tmp_dir.path().join("test/out/test/RB20001.trn");
+
+        let rolling_stock_path = tmp_dir.path().join("test/dev/test/rolling-stock/Triebwagen-A.trn");
+        fs::create_dir_all(rolling_stock_path.parent().unwrap()).unwrap();
+        fs::write(&rolling_stock_path, ROLLING_STOCK_TEMPLATE_TRN).unwrap();
+
+        let zug_config = |nummer: &str, route_path: PathBuf| ZugConfig {
+            nummer: nummer.into(),
+            gattung: "RB".into(),
+            extends: None,
+            zuglauf: "".into(),
+            fahrplan_gruppe: "".into(),
+            meta_data: None,
+            route: RouteConfig {
+                parts: vec![
+                    RoutePart {
+                        source: RoutePartSource::TrainFileByPath { path: route_path },
+                        start_fahrzeug_verband_aktion: None,
+                        time_fix: None,
+                        apply_schedule: None,
+                    },
+                ],
+                includes: vec![],
+            },
+            rolling_stock: RollingStockConfig {
+                path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+            },
+            copy_delay_config: None,
+            takt: None,
+            return_config: None,
+            time_rounding: None,
+        };
+
+        let config = FahrplanConfig {
+            generate_at: at_fpn_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+            generate_from: from_fpn_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+            collect_errors: true,
+            zug_collisions: ZugCollisionPolicy::Warn,
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![],
+            includes: vec![],
+            zuege: vec![
+                zug_config("20001", "test/dev/test/non-existent.trn".into()),
+                zug_config("10001", route1_template_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned()),
+            ],
+        };
+
+        let error = generate_fahrplan(&env, config).unwrap_err();
+
+        assert!(matches!(
+            &error,
+            GenerateFahrplanError::GenerateZuegeErrors { errors } if errors.len() == 1,
+        ));
+        assert!(error.to_string().contains("Couldn't generate Zug with Zugnummer '20001'"));
+
+        assert_eq!(read_xml_file(&route1_path), cleanup_xml(EXPECTED_ROUTE1_TRN.into()));
+        assert!(!route2_path.exists());
+
+        let fpn = read_xml_file(&at_fpn_path);
+        assert!(fpn.contains("test/out/test/RB10001.trn"));
+        assert!(!fpn.contains("RB20001"));
+    }
+
+    #[test]
+    fn test_prepare_fahrplan_with_header() {
+        let tmp_dir = tempdir().unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().join("test/dev"),
+        };
+
+        let from_fpn_path = tmp_dir.path().join("test/dev/test.fpn");
+        fs::create_dir_all(from_fpn_path.parent().unwrap()).unwrap();
+        fs::write(&from_fpn_path, FROM_FPN).unwrap();
+
+        let route1_template_path = tmp_dir.path().join("test/dev/test/RB10001.trn");
+        fs::create_dir_all(route1_template_path.parent().unwrap()).unwrap();
+        fs::write(&route1_template_path, ROUTE1_TEMPLATE_TRN).unwrap();
+
+        let rolling_stock_path = tmp_dir.path().join("test/dev/test/rolling-stock/Triebwagen-A.trn");
+        fs::create_dir_all(rolling_stock_path.parent().unwrap()).unwrap();
+        fs::write(&rolling_stock_path, ROLLING_STOCK_TEMPLATE_TRN).unwrap();
+
+        let config = FahrplanConfig {
+            generate_at: "../out/test.fpn".into(),
+            generate_from: "./test.fpn".into(),
+            collect_errors: false,
+            zug_collisions: ZugCollisionPolicy::Warn,
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: Some(FahrplanHeaderConfig {
+                chaos_vorschlagen: Some(false),
+                anfangs_zeit: Some(AnfangsZeitConfig {
+                    source: AnfangsZeitSource::BeforeFirstAbfahrt { lead_time: Duration::minutes(15) },
+                }),
+                befehls_konfiguration: None,
+                begruessungsdatei: None,
+                la_pdf: Some(HeaderFileConfig { path: "./la.pdf".into() }),
+                strebu_pdf: None,
+                ersatzfahrplaene_pdf: None,
+            }),
+            str_module: None,
+            templates: vec![],
+            includes: vec![],
+            zuege: vec![
+                ZugConfig {
+                    nummer: "10001".into(),
+                    gattung: "RB".into(),
+                    extends: None,
+                    zuglauf: "".into(),
+                    fahrplan_gruppe: "".into(),
+                    meta_data: None,
+                    route: RouteConfig {
+                        parts: vec![
+                            RoutePart {
+                                source: RoutePartSource::TrainFileByPath { path: "./test/RB10001.trn".into() },
+                                start_fahrzeug_verband_aktion: None,
+                                time_fix: None,
+                                apply_schedule: None,
+                            },
+                        ],
+                        includes: vec![],
+                    },
+                    rolling_stock: RollingStockConfig { path: "./test/rolling-stock/Triebwagen-A.trn".into() },
+                    copy_delay_config: None,
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
+                },
+            ],
+        };
+
+        let fahrplan = prepare_fahrplan(&env, config).unwrap().fahrplan.value;
+
+        assert_eq!(fahrplan.anfangs_zeit, datetime!(2024-06-20 08:26:40));
+        assert!(!fahrplan.chaos_vorschlagen);
+        assert_eq!(
+            fahrplan.la_pdf,
+            datei_from_prejoined_zusi_path(env.path_to_prejoined_zusi_path("./la.pdf").unwrap(), false).unwrap(),
+        );
+        assert_eq!(
+            fahrplan.befehls_konfiguration,
+            read_fahrplan(&from_fpn_path).unwrap().value.befehls_konfiguration,
+        );
+    }
 }
\ No newline at end of file
@@ -0,0 +1,325 @@
+use crate::core::lib::generated_zug::GeneratedZug;
+use serde::Serialize;
+use serde_helpers::with::date_time::date_time_format;
+use std::fmt::{Display, Formatter};
+use time::{Duration, PrimitiveDateTime};
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_abfahrt::FahrplanAbfahrt;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_ankunft::FahrplanAnkunft;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_name::FahrplanName;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::FahrplanZeile;
+use zusi_xml_lib::xml::zusi::zug::Zug;
+
+/// Value of FplRglGgl for FahrplanZeilen on single-track sections, double-track sections use 1 (Regelgleis) and 2 (Gegengleis)
+const SINGLE_TRACK: i32 = 0;
+
+/// Two Zuege which occupy the same place within the headway
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub location: ConflictLocation,
+    pub first: ConflictingZug,
+    pub second: ConflictingZug,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum ConflictLocation {
+    Betriebsstelle {
+        betriebsstelle: String,
+    },
+
+    /// Both Zuege have the same FahrplanSignalEintrag at the Betriebsstelle
+    Signal {
+        betriebsstelle: String,
+        signal: String,
+    },
+
+    /// The Zuege run in opposite directions on the single-track section between both Betriebsstellen
+    SingleTrack {
+        from: String,
+        to: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConflictingZug {
+    pub gattung: String,
+    pub nummer: String,
+
+    #[serde(with = "date_time_format")]
+    pub from: PrimitiveDateTime,
+
+    #[serde(with = "date_time_format")]
+    pub to: PrimitiveDateTime,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} and {} ", self.first, self.second)?;
+        match &self.location {
+            ConflictLocation::Betriebsstelle { betriebsstelle } => write!(f, "at '{betriebsstelle}'"),
+            ConflictLocation::Signal { betriebsstelle, signal } => write!(f, "at signal '{signal}' of '{betriebsstelle}'"),
+            ConflictLocation::SingleTrack { from, to } => write!(f, "on the single-track section '{from}' - '{to}'"),
+        }
+    }
+}
+
+impl Display for ConflictingZug {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{} ({} - {})", self.gattung, self.nummer, self.from.time(), self.to.time())
+    }
+}
+
+/// Time a Zug spends at a Betriebsstelle
+struct Occupation<'a> {
+    betriebsstelle: &'a str,
+    signals: Vec<&'a str>,
+    from: PrimitiveDateTime,
+    to: PrimitiveDateTime,
+}
+
+/// Time a Zug needs from one Betriebsstelle to the next one on a single-track section
+struct SingleTrackRun {
+    from: String,
+    to: String,
+    start: PrimitiveDateTime,
+    end: PrimitiveDateTime,
+}
+
+/// Finds all pairs of Zuege which occupy the same Betriebsstelle within the headway.
+/// If both Zuege have FahrplanSignalEintraege at the Betriebsstelle, they only conflict if they share a signal.
+/// Opposing runs on single-track sections are detected by the FplRglGgl of the Buchfahrplan, if there is one.
+pub fn find_conflicts(zuege: &[GeneratedZug], headway: Duration) -> Vec<Conflict> {
+    let occupations: Vec<Vec<Occupation>> = zuege.iter().map(|zug| occupations(&zug.zug.value)).collect();
+    let single_track_runs: Vec<Vec<SingleTrackRun>> = zuege
+        .iter()
+        .map(|zug| zug.buchfahrplan
+            .as_ref()
+            .map(|buchfahrplan| single_track_runs(&buchfahrplan.value.fahrplan_zeilen))
+            .unwrap_or_default()
+        )
+        .collect();
+
+    let mut conflicts = vec![];
+    for first in 0..zuege.len() {
+        for second in first + 1..zuege.len() {
+            let zug = |index: usize, from: PrimitiveDateTime, to: PrimitiveDateTime| ConflictingZug {
+                gattung: zuege[index].zug.value.gattung.clone(),
+                nummer: zuege[index].zug.value.nummer.clone(),
+                from,
+                to,
+            };
+
+            for a in &occupations[first] {
+                for b in occupations[second].iter().filter(|b| b.betriebsstelle == a.betriebsstelle) {
+                    if !overlap(a.from, a.to, b.from, b.to, headway) {
+                        continue;
+                    }
+                    let location = if a.signals.is_empty() || b.signals.is_empty() {
+                        Some(ConflictLocation::Betriebsstelle { betriebsstelle: a.betriebsstelle.into() })
+                    } else {
+                        a.signals
+                            .iter()
+                            .find(|signal| b.signals.contains(signal))
+                            .map(|signal| ConflictLocation::Signal { betriebsstelle: a.betriebsstelle.into(), signal: (*signal).into() })
+                    };
+                    if let Some(location) = location {
+                        conflicts.push(Conflict { location, first: zug(first, a.from, a.to), second: zug(second, b.from, b.to) });
+                    }
+                }
+            }
+
+            for a in &single_track_runs[first] {
+                for b in single_track_runs[second].iter().filter(|b| b.from == a.to && b.to == a.from) {
+                    if overlap(a.start, a.end, b.start, b.end, headway) {
+                        conflicts.push(Conflict {
+                            location: ConflictLocation::SingleTrack { from: a.from.clone(), to: a.to.clone() },
+                            first: zug(first, a.start, a.end),
+                            second: zug(second, b.start, b.end),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+fn overlap(from1: PrimitiveDateTime, to1: PrimitiveDateTime, from2: PrimitiveDateTime, to2: PrimitiveDateTime, headway: Duration) -> bool {
+    from1 < to2 + headway && from2 < to1 + headway
+}
+
+fn occupations(zug: &Zug) -> Vec<Occupation<'_>> {
+    zug.fahrplan_eintraege
+        .iter()
+        .filter_map(|eintrag| {
+            let from = eintrag.ankunft.or(eintrag.abfahrt)?;
+            Some(Occupation {
+                betriebsstelle: &eintrag.betriebsstelle,
+                signals: eintrag.fahrplan_signal_eintraege
+                    .iter()
+                    .map(|signal_eintrag| signal_eintrag.fahrplan_signal.as_str())
+                    .collect(),
+                from,
+                to: eintrag.abfahrt.unwrap_or(from),
+            })
+        })
+        .collect()
+}
+
+/// Sections between two FahrplanZeilen with FplName and times where all FahrplanZeilen are on a single-track section.
+fn single_track_runs(fahrplan_zeilen: &[FahrplanZeile]) -> Vec<SingleTrackRun> {
+    let mut runs = vec![];
+    let mut previous: Option<(&str, PrimitiveDateTime)> = None;
+    let mut single_track = true;
+    for zeile in fahrplan_zeilen {
+        let ankunft = zeile.fahrplan_ankunft.as_ref().map(|FahrplanAnkunft { ankunft, .. }| *ankunft);
+        let abfahrt = zeile.fahrplan_abfahrt.as_ref().map(|FahrplanAbfahrt { abfahrt, .. }| *abfahrt);
+        let betriebsstelle = match (&zeile.fahrplan_name, ankunft.or(abfahrt)) {
+            (Some(FahrplanName { fahrplan_name_text, .. }), Some(arrival)) => Some((fahrplan_name_text.as_str(), arrival)),
+            _ => None,
+        };
+
+        // duplicate FahrplanZeilen for Regelgleis and Gegengleis don't belong to the next section
+        if matches!((previous, betriebsstelle), (Some((previous_name, _)), Some((name, _))) if previous_name == name) {
+            continue;
+        }
+
+        single_track &= zeile.fahrplan_regelgleis_gegengleis == SINGLE_TRACK;
+        if let Some((name, arrival)) = betriebsstelle {
+            if let Some((previous_name, departure)) = previous {
+                if single_track {
+                    runs.push(SingleTrackRun { from: previous_name.into(), to: name.into(), start: departure, end: arrival });
+                }
+            }
+            previous = Some((name, abfahrt.unwrap_or(arrival)));
+            single_track = true;
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::lib::generated_zug::{RawGeneratedZug, ZugOrigin};
+    use time::macros::datetime;
+    use zusi_xml_lib::xml::zusi::buchfahrplan::Buchfahrplan;
+    use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+    use zusi_xml_lib::xml::zusi::lib::utm::UTM;
+    use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::fahrplan_signal_eintrag::FahrplanSignalEintrag;
+    use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
+    use zusi_xml_lib::xml::zusi::zug::fahrzeug_varianten::FahrzeugVarianten;
+
+    fn eintrag(betriebsstelle: &str, ankunft: Option<PrimitiveDateTime>, abfahrt: Option<PrimitiveDateTime>, signal: Option<&str>) -> FahrplanEintrag {
+        FahrplanEintrag::builder()
+            .betriebsstelle(betriebsstelle.into())
+            .ankunft(ankunft)
+            .abfahrt(abfahrt)
+            .fahrplan_signal_eintraege(
+                signal.into_iter().map(|signal| FahrplanSignalEintrag::builder().fahrplan_signal(signal.into()).build()).collect()
+            )
+            .build()
+    }
+
+    fn zeile(name: &str, ankunft: Option<PrimitiveDateTime>, abfahrt: Option<PrimitiveDateTime>, rgl_ggl: i32) -> FahrplanZeile {
+        FahrplanZeile::builder()
+            .fahrplan_regelgleis_gegengleis(rgl_ggl)
+            .fahrplan_name((!name.is_empty()).then(|| FahrplanName::builder().fahrplan_name_text(name.into()).build()))
+            .fahrplan_ankunft(ankunft.map(|ankunft| FahrplanAnkunft::builder().ankunft(ankunft).build()))
+            .fahrplan_abfahrt(abfahrt.map(|abfahrt| FahrplanAbfahrt::builder().abfahrt(abfahrt).build()))
+            .build()
+    }
+
+    fn generated_zug(nummer: &str, fahrplan_eintraege: Vec<FahrplanEintrag>, fahrplan_zeilen: Option<Vec<FahrplanZeile>>) -> GeneratedZug {
+        let raw = RawGeneratedZug {
+            zug: Zug::builder()
+                .gattung("RB".into())
+                .nummer(nummer.into())
+                .fahrplan_datei(Datei::builder().build())
+                .fahrzeug_varianten(FahrzeugVarianten::builder().build())
+                .fahrplan_eintraege(fahrplan_eintraege)
+                .build(),
+            buchfahrplan: fahrplan_zeilen.map(|fahrplan_zeilen| Buchfahrplan::builder()
+                .datei_fpn(Datei::builder().build())
+                .datei_trn(Datei::builder().build())
+                .utm(UTM::builder().build())
+                .fahrplan_zeilen(fahrplan_zeilen)
+                .build()
+            ),
+        };
+        (raw, ZugOrigin { config_nummer: nummer.into(), copy_delay: None, rolling_stock: "./rolling-stock.trn".into() }).into()
+    }
+
+    fn conflicting_zug(nummer: &str, from: PrimitiveDateTime, to: PrimitiveDateTime) -> ConflictingZug {
+        ConflictingZug { gattung: "RB".into(), nummer: nummer.into(), from, to }
+    }
+
+    #[test]
+    fn test_find_conflicts_at_betriebsstelle() {
+        let zuege = vec![
+            generated_zug("10001", vec![
+                eintrag("A", None, Some(datetime!(2024-06-20 08:00:00)), Some("N1")),
+                eintrag("B", Some(datetime!(2024-06-20 08:10:00)), Some(datetime!(2024-06-20 08:11:00)), None),
+            ], None),
+            generated_zug("20001", vec![
+                eintrag("A", None, Some(datetime!(2024-06-20 08:01:00)), Some("N2")),
+                eintrag("B", Some(datetime!(2024-06-20 08:12:30)), Some(datetime!(2024-06-20 08:13:00)), None),
+            ], None),
+            generated_zug("30001", vec![
+                eintrag("A", Some(datetime!(2024-06-20 07:58:00)), Some(datetime!(2024-06-20 07:59:00)), Some("N1")),
+                eintrag("C", Some(datetime!(2024-06-20 08:20:00)), None, None),
+            ], None),
+        ];
+
+        assert_eq!(
+            find_conflicts(&zuege, Duration::minutes(2)),
+            vec![
+                Conflict {
+                    location: ConflictLocation::Betriebsstelle { betriebsstelle: "B".into() },
+                    first: conflicting_zug("10001", datetime!(2024-06-20 08:10:00), datetime!(2024-06-20 08:11:00)),
+                    second: conflicting_zug("20001", datetime!(2024-06-20 08:12:30), datetime!(2024-06-20 08:13:00)),
+                },
+                Conflict {
+                    location: ConflictLocation::Signal { betriebsstelle: "A".into(), signal: "N1".into() },
+                    first: conflicting_zug("10001", datetime!(2024-06-20 08:00:00), datetime!(2024-06-20 08:00:00)),
+                    second: conflicting_zug("30001", datetime!(2024-06-20 07:58:00), datetime!(2024-06-20 07:59:00)),
+                },
+            ],
+        );
+        assert_eq!(find_conflicts(&zuege, Duration::ZERO), vec![]);
+    }
+
+    #[test]
+    fn test_find_conflicts_on_single_track() {
+        let zuege = vec![
+            generated_zug("10001", vec![], Some(vec![
+                zeile("A", None, Some(datetime!(2024-06-20 08:00:00)), 1),
+                zeile("", None, None, SINGLE_TRACK),
+                zeile("B", Some(datetime!(2024-06-20 08:10:00)), Some(datetime!(2024-06-20 08:11:00)), SINGLE_TRACK),
+                zeile("", None, None, 1),
+                zeile("C", Some(datetime!(2024-06-20 08:20:00)), None, 1),
+                zeile("C", Some(datetime!(2024-06-20 08:20:00)), None, 2),
+            ])),
+            generated_zug("20001", vec![], Some(vec![
+                zeile("C", None, Some(datetime!(2024-06-20 08:00:00)), 1),
+                zeile("C", None, Some(datetime!(2024-06-20 08:00:00)), 2),
+                zeile("", None, None, 1),
+                zeile("", None, None, 2),
+                zeile("B", None, Some(datetime!(2024-06-20 08:09:00)), 1),
+                zeile("", None, None, SINGLE_TRACK),
+                zeile("A", Some(datetime!(2024-06-20 08:15:00)), None, SINGLE_TRACK),
+            ])),
+        ];
+
+        assert_eq!(
+            find_conflicts(&zuege, Duration::minutes(1)),
+            vec![
+                Conflict {
+                    location: ConflictLocation::SingleTrack { from: "A".into(), to: "B".into() },
+                    first: conflicting_zug("10001", datetime!(2024-06-20 08:00:00), datetime!(2024-06-20 08:10:00)),
+                    second: conflicting_zug("20001", datetime!(2024-06-20 08:09:00), datetime!(2024-06-20 08:15:00)),
+                },
+            ],
+        );
+    }
+}
\ No newline at end of file
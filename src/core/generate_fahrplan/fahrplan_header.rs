@@ -0,0 +1,75 @@
+use crate::core::lib::file_error::FileError;
+use crate::core::lib::generated_zug::GeneratedZug;
+use crate::core::lib::helpers::datei_from_prejoined_zusi_path;
+use crate::input::environment::zusi_environment::ZusiEnvironment;
+use crate::input::fahrplan_config::{AnfangsZeitSource, FahrplanHeaderConfig, HeaderFileConfig};
+use thiserror::Error;
+use time::PrimitiveDateTime;
+use zusi_xml_lib::xml::zusi::fahrplan::Fahrplan;
+use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum FahrplanHeaderError {
+    #[error("The path of the {element} is invalid: {error}")]
+    InvalidPath {
+        element: String,
+        error: FileError,
+    },
+
+    #[error("The AnfangsZeit can't be derived from the first Abfahrt as none of the generated Zuege has an Abfahrt.")]
+    NoAbfahrt,
+}
+
+/// Replaces the values of the Fahrplan which are set by the config, all others are kept from the template.
+pub fn apply_fahrplan_header(env: &ZusiEnvironment, config: &FahrplanHeaderConfig, fahrplan: &mut Fahrplan, zuege: &[GeneratedZug]) -> Result<(), FahrplanHeaderError> {
+    if let Some(chaos_vorschlagen) = config.chaos_vorschlagen {
+        fahrplan.chaos_vorschlagen = chaos_vorschlagen;
+    }
+    if let Some(anfangs_zeit) = &config.anfangs_zeit {
+        fahrplan.anfangs_zeit = match anfangs_zeit.source {
+            AnfangsZeitSource::Fixed { value } => value,
+            AnfangsZeitSource::BeforeFirstAbfahrt { lead_time } => first_abfahrt(zuege).ok_or(FahrplanHeaderError::NoAbfahrt)? - lead_time,
+        };
+    }
+    for (element, file_config, datei) in [
+        ("BefehlsKonfiguration", &config.befehls_konfiguration, &mut fahrplan.befehls_konfiguration),
+        ("Begruessungsdatei", &config.begruessungsdatei, &mut fahrplan.begruessungsdatei),
+        ("LaPDF", &config.la_pdf, &mut fahrplan.la_pdf),
+        ("StrebuPDF", &config.strebu_pdf, &mut fahrplan.strebu_pdf),
+        ("ErsatzfahrplaenePDF", &config.ersatzfahrplaene_pdf, &mut fahrplan.ersatzfahrplaene_pdf),
+    ] {
+        if let Some(file_config) = file_config {
+            *datei = header_datei(env, element, file_config)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks the paths of all files set by the config.
+pub fn validate_fahrplan_header(env: &ZusiEnvironment, config: &FahrplanHeaderConfig) -> Vec<FahrplanHeaderError> {
+    [
+        ("BefehlsKonfiguration", &config.befehls_konfiguration),
+        ("Begruessungsdatei", &config.begruessungsdatei),
+        ("LaPDF", &config.la_pdf),
+        ("StrebuPDF", &config.strebu_pdf),
+        ("ErsatzfahrplaenePDF", &config.ersatzfahrplaene_pdf),
+    ]
+        .into_iter()
+        .filter_map(|(element, file_config)| file_config.as_ref().and_then(|file_config| header_datei(env, element, file_config).err()))
+        .collect()
+}
+
+fn header_datei(env: &ZusiEnvironment, element: &str, config: &HeaderFileConfig) -> Result<Datei, FahrplanHeaderError> {
+    env.path_to_prejoined_zusi_path(&config.path)
+        .and_then(|path| datei_from_prejoined_zusi_path(path, false))
+        .map_err(|error| FahrplanHeaderError::InvalidPath { element: element.into(), error })
+}
+
+/// Earliest Abfahrt of all Zuege
+fn first_abfahrt(zuege: &[GeneratedZug]) -> Option<PrimitiveDateTime> {
+    zuege
+        .iter()
+        .flat_map(|zug| zug.zug.value.fahrplan_eintraege.iter())
+        .filter_map(|eintrag| eintrag.abfahrt)
+        .min()
+}
\ No newline at end of file
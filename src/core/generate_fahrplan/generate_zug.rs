@@ -1,13 +1,18 @@
-mod generate_route;
+pub mod generate_route;
 mod add_meta_data;
+pub mod return_zug;
+mod round_times;
 
 use crate::core::generate_fahrplan::generate_zug::add_meta_data::{add_meta_data, AddMetaDataError};
-use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::apply_resolved_route_to_zug;
+use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::{apply_resolved_route_to_zug, ResolvedRoutes};
 use crate::core::generate_fahrplan::generate_zug::generate_route::{generate_route, GenerateRouteError};
-use crate::core::lib::copy_delay::{copy_delay, CopyDelayError};
+use crate::core::generate_fahrplan::generate_zug::return_zug::{apply_vehicle_turn, return_zug_config, ReturnZugError};
+use crate::core::generate_fahrplan::generate_zug::round_times::{round_route, round_times};
+use crate::core::lib::copy_delay::{copy_delay, copy_delay_origins, CopyDelayError};
 use crate::core::lib::file_error::FileError;
-use crate::core::lib::generated_zug::{GeneratedZug, RawGeneratedZug};
+use crate::core::lib::generated_zug::{GeneratedZug, RawGeneratedZug, ZugOrigin};
 use crate::core::lib::helpers::{datei_from_prejoined_zusi_path, empty_buchfahrplan_with_gattung_and_nummer, override_with_non_default};
+use crate::core::lib::takt::{takt_to_copy_delay_config, TaktError};
 use crate::core::replace_rolling_stock::{replace_rolling_stock, ReplaceRollingStockError};
 use crate::input::environment::zusi_environment::ZusiEnvironment;
 use crate::input::fahrplan_config::ZugConfig;
@@ -53,6 +58,18 @@ pub enum GenerateZugErrorKind {
         #[from]
         error: CopyDelayError,
     },
+
+    #[error("Couldn't create the Takt: {error}")]
+    TaktError {
+        #[from]
+        error: TaktError,
+    },
+
+    #[error("Couldn't create the return Zug: {error}")]
+    ReturnZugError {
+        #[from]
+        error: ReturnZugError,
+    },
 }
 
 impl From<(&String, GenerateZugErrorKind)> for GenerateZugError {
@@ -64,7 +81,9 @@ impl From<(&String, GenerateZugErrorKind)> for GenerateZugError {
     }
 }
 
-pub fn generate_zug(env: &ZusiEnvironment, fahrplan_path: &PrejoinedZusiPath, zug_config: ZugConfig) -> Result<Vec<GeneratedZug>, GenerateZugError> {
+pub fn generate_zug(env: &ZusiEnvironment, fahrplan_path: &PrejoinedZusiPath, zug_config: ZugConfig, resolved_routes: &mut ResolvedRoutes) -> Result<Vec<GeneratedZug>, GenerateZugError> {
+    let return_template = zug_config.return_config.clone().map(|return_config| (zug_config.clone(), return_config));
+
     let fahrplan_datei = datei_from_prejoined_zusi_path(fahrplan_path, true)
         .map_err(|error| GenerateZugError::from((&zug_config.nummer, GenerateZugErrorKind::AttachFahrplanFileError { error })))?;
 
@@ -77,8 +96,12 @@ pub fn generate_zug(env: &ZusiEnvironment, fahrplan_path: &PrejoinedZusiPath, zu
         .fahrzeug_varianten(FahrzeugVarianten::builder().build())
         .build();
 
-    let route = generate_route(env, zug_config.route)
+    let mut route = generate_route(env, zug_config.route, resolved_routes)
         .map_err(|error| GenerateZugError::from((&zug.nummer, error.into())))?;
+    if let Some(time_rounding) = &zug_config.time_rounding {
+        round_route(&mut route, time_rounding);
+    }
+    resolved_routes.insert(zug.nummer.clone(), route.clone());
 
     override_with_non_default(&mut zug.mindest_bremshundertstel, route.mindest_bremshundertstel);
 
@@ -100,6 +123,7 @@ pub fn generate_zug(env: &ZusiEnvironment, fahrplan_path: &PrejoinedZusiPath, zu
 
     apply_resolved_route_to_zug(route, &mut zug);
 
+    let rolling_stock = zug_config.rolling_stock.path.clone();
     replace_rolling_stock(env, zug_config.rolling_stock, &mut zug)
         .map_err(|error| GenerateZugError::from((&zug.zug.nummer, error.into())))?;
 
@@ -108,23 +132,63 @@ pub fn generate_zug(env: &ZusiEnvironment, fahrplan_path: &PrejoinedZusiPath, zu
             .map_err(|error| GenerateZugError::from((&zug.zug.nummer, error.into())))?;
     }
 
-    let mut zuege = vec![zug];
+    if let Some(aktion) = return_template.as_ref().and_then(|(_, return_config)| return_config.start_fahrzeug_verband_aktion.as_ref()) {
+        apply_vehicle_turn(&mut zug.zug, aktion);
+    }
+
+    let config_nummer = zug.zug.nummer.clone();
+    let mut zuege = vec![(zug, ZugOrigin { config_nummer: config_nummer.clone(), copy_delay: None, rolling_stock: rolling_stock.clone() })];
+
+    let copy_delay_config = match (zug_config.copy_delay_config, zug_config.takt) {
+        (Some(_), Some(_)) => Err(TaktError::CombinedWithCopyDelay),
+        (None, Some(takt)) => takt_to_copy_delay_config(&takt, &zuege[0].0).map(Some),
+        (copy_delay_config, None) => Ok(copy_delay_config),
+    }.map_err(|error| GenerateZugError::from((&config_nummer, error.into())))?;
 
-    if let Some(copy_delay_config) = zug_config.copy_delay_config {
-        let raw_generated_zug = zuege.first().unwrap();
-        let mut additional = copy_delay(env, copy_delay_config, raw_generated_zug)
+    if let Some(copy_delay_config) = copy_delay_config.clone() {
+        let origins: Vec<_> = copy_delay_origins(&copy_delay_config)
+            .into_iter()
+            .map(|origin| {
+                let rolling_stock = copy_delay_config.tasks[origin.task - 1].custom_rolling_stock
+                    .as_ref()
+                    .map_or_else(|| rolling_stock.clone(), |custom_rolling_stock| custom_rolling_stock.path.clone());
+                (origin, rolling_stock)
+            })
+            .collect();
+        let (raw_generated_zug, _) = zuege.first().unwrap();
+        let additional = copy_delay(env, copy_delay_config, raw_generated_zug)
             .map_err(|error| GenerateZugError::from((&raw_generated_zug.zug.nummer, error.into())))?;
-        zuege.append(&mut additional);
+        zuege.extend(additional.into_iter().zip(origins).map(|(zug, (origin, rolling_stock))| (
+            zug,
+            ZugOrigin { config_nummer: config_nummer.clone(), copy_delay: Some(origin), rolling_stock },
+        )));
+    }
+
+    if let Some(time_rounding) = zug_config.time_rounding {
+        zuege.iter_mut().for_each(|(zug, _)| round_times(zug, &time_rounding));
+    }
+
+    let return_zug_config = return_template
+        .map(|(zug_config, return_config)| return_zug_config(zug_config, return_config, &zuege[0].0, copy_delay_config))
+        .transpose()
+        .map_err(|error| GenerateZugError::from((&config_nummer, error.into())))?;
+
+    let mut generated_zuege: Vec<GeneratedZug> = zuege.into_iter().map(|zug| zug.into()).collect();
+
+    if let Some(return_zug_config) = return_zug_config {
+        generated_zuege.extend(generate_zug(env, fahrplan_path, return_zug_config, resolved_routes)?);
     }
 
-    Ok(zuege.into_iter().map(|raw| raw.into()).collect())
+    Ok(generated_zuege)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::lib::generated_zug::CopyDelayOrigin;
+    use crate::core::lib::time_rounding::{RoundingDirection, RoundingGranularity};
     use crate::input::copy_delay_config::{CopyDelayConfig, CopyDelayTask};
-    use crate::input::fahrplan_config::{MetaDataConfig, RouteConfig, RoutePart, RoutePartSource};
+    use crate::input::fahrplan_config::{MetaDataConfig, RouteConfig, RoutePart, RoutePartSource, TimeRounding};
     use crate::input::rolling_stock_config::RollingStockConfig;
     use std::fs;
     use tempfile::tempdir;
@@ -294,6 +358,7 @@ mod tests {
         let config = ZugConfig {
             nummer: "10001".into(),
             gattung: "RB".into(),
+            extends: None,
             zuglauf: "".into(),
             fahrplan_gruppe: "".into(),
             meta_data: Some(MetaDataConfig {
@@ -308,6 +373,7 @@ mod tests {
                         apply_schedule: None,
                     },
                 ],
+                includes: vec![],
             },
             rolling_stock: RollingStockConfig {
                 path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
@@ -324,6 +390,9 @@ mod tests {
                     },
                 ],
             }),
+            takt: None,
+            return_config: None,
+            time_rounding: None,
         };
 
         let expected = vec![
@@ -373,6 +442,11 @@ mod tests {
                         .build())
                     .build(),
                 buchfahrplan: None,
+                origin: ZugOrigin {
+                    config_nummer: "10001".into(),
+                    copy_delay: None,
+                    rolling_stock: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+                },
             },
             GeneratedZug {
                 zug: TypedZusi::builder()
@@ -420,11 +494,16 @@ mod tests {
                         .build())
                     .build(),
                 buchfahrplan: None,
+                origin: ZugOrigin {
+                    config_nummer: "10001".into(),
+                    copy_delay: Some(CopyDelayOrigin { task: 1, copy: 1, delay: Duration::hours(1), increment: 2 }),
+                    rolling_stock: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+                },
             },
         ];
 
         assert_eq!(
-            generate_zug(&env, &prejoined_fpn_path, config).unwrap(),
+            generate_zug(&env, &prejoined_fpn_path, config, &mut ResolvedRoutes::new()).unwrap(),
             expected,
         );
 
@@ -433,6 +512,79 @@ mod tests {
         assert_eq!(fs::read_to_string(rolling_stock_path).unwrap(), ROLLING_STOCK_TRN);
     }
 
+    #[test]
+    fn test_generate_zug_referencing_rounded_route() {
+        let tmp_dir = tempdir().unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().to_owned(),
+        };
+
+        let fpn_path = tmp_dir.path().join("test.fpn");
+        let prejoined_fpn_path = PrejoinedZusiPath::new(&env.data_dir, ZusiPath::new_using_data_dir(fpn_path, &env.data_dir).unwrap());
+
+        let route_path = tmp_dir.path().join("test/10001.trn");
+        fs::create_dir_all(route_path.parent().unwrap()).unwrap();
+        fs::write(&route_path, ROUTE_TRN).unwrap();
+
+        let rolling_stock_path = tmp_dir.path().join("test/dev/rolling-stock/Triebwagen-A.trn");
+        fs::create_dir_all(rolling_stock_path.parent().unwrap()).unwrap();
+        fs::write(&rolling_stock_path, ROLLING_STOCK_TRN).unwrap();
+
+        let zug_config = |nummer: &str, source: RoutePartSource, time_rounding: Option<TimeRounding>| ZugConfig {
+            nummer: nummer.into(),
+            gattung: "RB".into(),
+            extends: None,
+            zuglauf: "".into(),
+            fahrplan_gruppe: "".into(),
+            meta_data: None,
+            route: RouteConfig {
+                parts: vec![
+                    RoutePart {
+                        source,
+                        start_fahrzeug_verband_aktion: None,
+                        time_fix: None,
+                        apply_schedule: None,
+                    },
+                ],
+                includes: vec![],
+            },
+            rolling_stock: RollingStockConfig {
+                path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+            },
+            copy_delay_config: None,
+            takt: None,
+            return_config: None,
+            time_rounding,
+        };
+
+        let mut resolved_routes = ResolvedRoutes::new();
+        let rounded = generate_zug(
+            &env,
+            &prejoined_fpn_path,
+            zug_config(
+                "10001",
+                RoutePartSource::TrainFileByPath { path: route_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned() },
+                Some(TimeRounding { granularity: RoundingGranularity::Minute, direction: RoundingDirection::Nearest }),
+            ),
+            &mut resolved_routes,
+        ).unwrap();
+        let referencing = generate_zug(
+            &env,
+            &prejoined_fpn_path,
+            zug_config("10003", RoutePartSource::TrainConfigByNummer { nummer: "10001".into() }, None),
+            &mut resolved_routes,
+        ).unwrap();
+
+        let times = |zug: &GeneratedZug| zug.zug.value.fahrplan_eintraege
+            .iter()
+            .map(|eintrag| (eintrag.ankunft, eintrag.abfahrt))
+            .collect::<Vec<_>>();
+        assert_eq!(times(&referencing[0]), times(&rounded[0]));
+        assert!(times(&referencing[0]).into_iter().flat_map(|(ankunft, abfahrt)| [ankunft, abfahrt]).flatten().all(|time| time.second() == 0));
+    }
+
     #[test]
     fn test_generate_zug_with_buchfahrplan() {
         let tmp_dir = tempdir().unwrap();
@@ -468,6 +620,7 @@ mod tests {
         let config = ZugConfig {
             nummer: "10001".into(),
             gattung: "RB".into(),
+            extends: None,
             zuglauf: "".into(),
             fahrplan_gruppe: "".into(),
             meta_data: Some(MetaDataConfig {
@@ -482,6 +635,7 @@ mod tests {
                         apply_schedule: None,
                     },
                 ],
+                includes: vec![],
             },
             rolling_stock: RollingStockConfig {
                 path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
@@ -498,6 +652,9 @@ mod tests {
                     },
                 ],
             }),
+            takt: None,
+            return_config: None,
+            time_rounding: None,
         };
 
         let expected = vec![
@@ -592,6 +749,11 @@ mod tests {
                         ])
                         .build())
                     .build()),
+                origin: ZugOrigin {
+                    config_nummer: "10001".into(),
+                    copy_delay: None,
+                    rolling_stock: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+                },
             },
             GeneratedZug {
                 zug: TypedZusi::builder()
@@ -684,11 +846,16 @@ mod tests {
                         ])
                         .build())
                     .build()),
+                origin: ZugOrigin {
+                    config_nummer: "10001".into(),
+                    copy_delay: Some(CopyDelayOrigin { task: 1, copy: 1, delay: Duration::hours(1), increment: 2 }),
+                    rolling_stock: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+                },
             },
         ];
 
         assert_eq!(
-            generate_zug(&env, &prejoined_fpn_path, config).unwrap(),
+            generate_zug(&env, &prejoined_fpn_path, config, &mut ResolvedRoutes::new()).unwrap(),
             expected,
         );
 
@@ -730,6 +897,7 @@ mod tests {
         let config = ZugConfig {
             nummer: "10001".into(),
             gattung: "RB".into(),
+            extends: None,
             zuglauf: "".into(),
             fahrplan_gruppe: "A - B".into(),
             meta_data: Some(MetaDataConfig {
@@ -744,6 +912,7 @@ mod tests {
                         apply_schedule: None,
                     },
                 ],
+                includes: vec![],
             },
             rolling_stock: RollingStockConfig {
                 path: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
@@ -760,6 +929,9 @@ mod tests {
                     },
                 ],
             }),
+            takt: None,
+            return_config: None,
+            time_rounding: None,
         };
 
         let expected = vec![
@@ -811,6 +983,11 @@ mod tests {
                         .build())
                     .build(),
                 buchfahrplan: None,
+                origin: ZugOrigin {
+                    config_nummer: "10001".into(),
+                    copy_delay: None,
+                    rolling_stock: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+                },
             },
             GeneratedZug {
                 zug: TypedZusi::builder()
@@ -860,11 +1037,16 @@ mod tests {
                         .build())
                     .build(),
                 buchfahrplan: None,
+                origin: ZugOrigin {
+                    config_nummer: "10001".into(),
+                    copy_delay: Some(CopyDelayOrigin { task: 1, copy: 1, delay: Duration::hours(1), increment: 2 }),
+                    rolling_stock: rolling_stock_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned(),
+                },
             },
         ];
 
         assert_eq!(
-            generate_zug(&env, &prejoined_fpn_path, config).unwrap(),
+            generate_zug(&env, &prejoined_fpn_path, config, &mut ResolvedRoutes::new()).unwrap(),
             expected,
         );
 
@@ -4,7 +4,7 @@ pub mod resolved_route;
 
 use crate::core::generate_fahrplan::generate_zug::generate_route::generate_route_part::{generate_route_part, GenerateRoutePartError};
 use crate::core::generate_fahrplan::generate_zug::generate_route::merge_routes::{merge_routes, MergeRoutePartsError};
-use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::ResolvedRoute;
+use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::{ResolvedRoute, ResolvedRoutePart, ResolvedRoutes};
 use crate::input::environment::zusi_environment::ZusiEnvironment;
 use crate::input::fahrplan_config::{RouteConfig, RoutePartSource};
 use std::collections::VecDeque;
@@ -33,10 +33,28 @@ pub enum GenerateRouteError {
     }
 }
 
-pub fn generate_route(env: &ZusiEnvironment, config: RouteConfig) -> Result<ResolvedRoute, GenerateRouteError> {
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValidateRouteError {
+    #[error("{error}")]
+    GenerateRouteError {
+        #[from]
+        error: GenerateRouteError,
+    },
+
+    #[error("Route part {part}: {error}")]
+    RoutePartError {
+        /// Position of the affected route part, starting at 1
+        part: usize,
+
+        #[source]
+        error: GenerateRouteError,
+    },
+}
+
+pub fn generate_route(env: &ZusiEnvironment, config: RouteConfig, resolved_routes: &ResolvedRoutes) -> Result<ResolvedRoute, GenerateRouteError> {
     let mut resolved_route_parts = config.parts
         .into_iter()
-        .map(|part| generate_route_part(env, part.clone()) // TODO: do not clone
+        .map(|part| generate_route_part(env, part.clone(), resolved_routes) // TODO: do not clone
             .map_err(|error| GenerateRouteError::GenerateRoutePartError {
                 source: part.source,
                 error,
@@ -55,6 +73,53 @@ pub fn generate_route(env: &ZusiEnvironment, config: RouteConfig) -> Result<Reso
         ).map(|route| route.into())
 }
 
+/// Resolves and merges all route parts like [generate_route] but collects all errors instead of stopping at the first one.
+/// Route parts which couldn't be resolved are skipped, so only directly consecutive parts are merged.
+pub fn validate_route(env: &ZusiEnvironment, config: RouteConfig, resolved_routes: &ResolvedRoutes) -> Result<ResolvedRoute, Vec<ValidateRouteError>> {
+    if config.parts.is_empty() {
+        return Err(vec![GenerateRouteError::NoRouteParts.into()]);
+    }
+
+    let mut errors = vec![];
+    let mut resolved_route_parts = vec![];
+    for (index, part) in config.parts.into_iter().enumerate() {
+        let source = part.source.clone();
+        match generate_route_part(env, part, resolved_routes) {
+            Ok(resolved_route_part) => resolved_route_parts.push((index, resolved_route_part)),
+            Err(error) => errors.push(ValidateRouteError::RoutePartError {
+                part: index + 1,
+                error: GenerateRouteError::GenerateRoutePartError { source, error },
+            }),
+        }
+    }
+
+    let mut generated_route: Option<(usize, ResolvedRoutePart)> = None;
+    for (index, resolved_route_part) in resolved_route_parts {
+        generated_route = Some(match generated_route {
+            Some((previous_index, previous)) if previous_index + 1 == index => {
+                match merge_routes(previous, resolved_route_part.clone()) {
+                    Ok(merged) => (index, merged),
+                    Err(error) => {
+                        errors.push(ValidateRouteError::RoutePartError { part: index + 1, error: error.into() });
+                        (index, resolved_route_part)
+                    },
+                }
+            },
+            _ => {
+                if index == 0 && resolved_route_part.start_data.fahrzeug_verband_aktion.is_some() {
+                    errors.push(ValidateRouteError::RoutePartError { part: 1, error: GenerateRouteError::IllegalFahrzeugVerbandAktion });
+                }
+                (index, resolved_route_part)
+            },
+        });
+    }
+
+    match generated_route {
+        Some((_, route)) if errors.is_empty() => Ok(route.into()),
+        _ => Err(errors),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,6 +336,7 @@ mod tests {
                     apply_schedule: None,
                 },
             ],
+            includes: vec![],
         };
 
         let expected = ResolvedRoute {
@@ -324,7 +390,7 @@ mod tests {
             mindest_bremshundertstel: 0.,
         };
 
-        let generated_route = generate_route(&env, route_config).unwrap();
+        let generated_route = generate_route(&env, route_config, &ResolvedRoutes::new()).unwrap();
 
         assert_eq!(generated_route, expected);
 
@@ -363,10 +429,11 @@ mod tests {
                     apply_schedule: None,
                 },
             ],
+            includes: vec![],
         };
 
         assert!(matches!(
-            generate_route(&env, route_config).unwrap_err(),
+            generate_route(&env, route_config, &ResolvedRoutes::new()).unwrap_err(),
             GenerateRouteError::MergeRoutePartsError { error: MergeRoutePartsError::NonConsecutiveRouteParts, .. },
         ));
 
@@ -404,10 +471,11 @@ mod tests {
                     apply_schedule: None,
                 },
             ],
+            includes: vec![],
         };
 
         assert!(matches!(
-            generate_route(&env, route_config).unwrap_err(),
+            generate_route(&env, route_config, &ResolvedRoutes::new()).unwrap_err(),
             GenerateRouteError::GenerateRoutePartError { error: GenerateRoutePartError::EmptyRoutePart, .. },
         ));
 
@@ -426,9 +494,10 @@ mod tests {
 
         let route_config = RouteConfig {
             parts: vec![],
+            includes: vec![],
         };
 
-        assert_eq!(generate_route(&env, route_config).unwrap_err(), GenerateRouteError::NoRouteParts);
+        assert_eq!(generate_route(&env, route_config, &ResolvedRoutes::new()).unwrap_err(), GenerateRouteError::NoRouteParts);
     }
 
     #[test]
@@ -456,13 +525,69 @@ mod tests {
                     apply_schedule: None,
                 },
             ],
+            includes: vec![],
         };
 
-        assert_eq!(generate_route(&env, route_config).unwrap_err(), GenerateRouteError::IllegalFahrzeugVerbandAktion);
+        assert_eq!(generate_route(&env, route_config, &ResolvedRoutes::new()).unwrap_err(), GenerateRouteError::IllegalFahrzeugVerbandAktion);
 
         assert_eq!(fs::read_to_string(trn_path).unwrap(), TRN1);
     }
 
+    #[test]
+    fn test_validate_route_collects_all_errors() {
+        let tmp_dir = tempdir().unwrap();
+
+        let trn1_path = tmp_dir.path().join("00001.trn");
+        fs::write(&trn1_path, TRN1).unwrap();
+
+        let trn2_path = tmp_dir.path().join("00002.trn");
+        fs::write(&trn2_path, TRN2).unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().to_owned(),
+        };
+
+        let route_config = RouteConfig {
+            parts: vec![
+                RoutePart {
+                    source: RoutePartSource::TrainFileByPath { path: "does-not-exist.trn".into() },
+                    start_fahrzeug_verband_aktion: None,
+                    time_fix: None,
+                    apply_schedule: None,
+                },
+                RoutePart {
+                    source: RoutePartSource::TrainFileByPath { path: trn2_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned() },
+                    start_fahrzeug_verband_aktion: None,
+                    time_fix: None,
+                    apply_schedule: None,
+                },
+                RoutePart {
+                    source: RoutePartSource::TrainFileByPath { path: trn1_path.clone().strip_prefix(tmp_dir.path()).unwrap().to_owned() },
+                    start_fahrzeug_verband_aktion: None,
+                    time_fix: None,
+                    apply_schedule: None,
+                },
+            ],
+            includes: vec![],
+        };
+
+        let errors = validate_route(&env, route_config, &ResolvedRoutes::new()).unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            errors[0],
+            ValidateRouteError::RoutePartError { part: 1, error: GenerateRouteError::GenerateRoutePartError { error: GenerateRoutePartError::ReadRouteError { .. }, .. } },
+        ));
+        assert!(matches!(
+            errors[1],
+            ValidateRouteError::RoutePartError { part: 3, error: GenerateRouteError::MergeRoutePartsError { error: MergeRoutePartsError::NonConsecutiveRouteParts } },
+        ));
+
+        assert_eq!(fs::read_to_string(trn1_path).unwrap(), TRN1);
+        assert_eq!(fs::read_to_string(trn2_path).unwrap(), TRN2);
+    }
+
     #[test]
     fn test_generate_route_with_buchfahrplan() {
         let tmp_dir = tempdir().unwrap();
@@ -506,6 +631,7 @@ mod tests {
                     apply_schedule: None,
                 },
             ],
+            includes: vec![],
         };
 
         let expected = ResolvedRoute {
@@ -607,7 +733,7 @@ mod tests {
             mindest_bremshundertstel: 0.,
         };
 
-        let generated_route = generate_route(&env, route_config).unwrap();
+        let generated_route = generate_route(&env, route_config, &ResolvedRoutes::new()).unwrap();
 
         assert_eq!(generated_route, expected);
 
@@ -1,4 +1,4 @@
-use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::{ResolvedRoutePart, RouteStartData};
+use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::{ResolvedRoutePart, ResolvedRoutes, RouteStartData};
 use crate::core::lib::file_error::FileError;
 use crate::core::lib::helpers::{delay_fahrplan_eintraege, override_with_non_default, read_buchfahrplan, read_zug};
 use crate::core::schedules::apply::{apply_schedule, ApplyScheduleError};
@@ -37,7 +37,7 @@ pub enum GenerateRoutePartError {
     #[error("The route source couldn't be read: {error}")]
     ReadRouteError {
         #[source]
-        error: FileError, // TODO: won't be always a FileError, e.g. if TrainConfigByNummer will be implemented
+        error: RouteSourceError,
     },
 
     #[error("The Buchfahrplan attached to the route couldn't be read: {error}")]
@@ -53,10 +53,24 @@ pub enum GenerateRoutePartError {
     },
 }
 
-pub fn generate_route_part(env: &ZusiEnvironment, route_part: RoutePart) -> Result<ResolvedRoutePart, GenerateRoutePartError> {
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RouteSourceError {
+    #[error("{error}")]
+    FileError {
+        #[from]
+        error: FileError,
+    },
+
+    #[error("No route was resolved for the Zug with Zugnummer '{nummer}'. Does a Zug with this Zugnummer exist and could it be generated?")]
+    UnresolvedZugNummer {
+        nummer: String,
+    },
+}
+
+pub fn generate_route_part(env: &ZusiEnvironment, route_part: RoutePart, resolved_routes: &ResolvedRoutes) -> Result<ResolvedRoutePart, GenerateRoutePartError> {
     let mut resolved_route_part = match route_part.source {
         RoutePartSource::TrainFileByPath { ref path } => retrieve_route_part_by_path(env, path),
-        RoutePartSource::TrainConfigByNummer { .. } => todo!(),
+        RoutePartSource::TrainConfigByNummer { ref nummer } => retrieve_route_part_by_nummer(resolved_routes, nummer),
     }?;
     if resolved_route_part.fahrplan_eintraege.is_empty() {
         Err(GenerateRoutePartError::EmptyRoutePart)
@@ -65,7 +79,7 @@ pub fn generate_route_part(env: &ZusiEnvironment, route_part: RoutePart) -> Resu
             let prejoined_path = env.path_to_prejoined_zusi_path(&path)
                 .map_err(|error| GenerateRoutePartError::ReadScheduleError { error })?;
             let schedule = Schedule::from_xml_file_by_path(prejoined_path.full_path())
-                .map_err(|error| GenerateRoutePartError::ReadRouteError { error: (prejoined_path.full_path(), error).into() })?;
+                .map_err(|error| GenerateRoutePartError::ReadScheduleError { error: (prejoined_path.full_path(), error).into() })?;
             apply_schedule(&mut resolved_route_part.fahrplan_eintraege, &schedule)?;
             adjust_environ_stop_times(&mut resolved_route_part, first_stop_time, last_stop_time)
         }
@@ -107,9 +121,9 @@ fn adjust_environ_stop_times(resolved_route_part: &mut ResolvedRoutePart, first:
 
 fn retrieve_route_part_by_path(env: &ZusiEnvironment, path: &PathBuf) -> Result<ResolvedRoutePart, GenerateRoutePartError> {
     let path = env.path_to_prejoined_zusi_path(path)
-        .map_err(|error| GenerateRoutePartError::ReadRouteError { error })?;
+        .map_err(|error| GenerateRoutePartError::ReadRouteError { error: error.into() })?;
     let mut route_template = read_zug(path.full_path())
-        .map_err(|error| GenerateRoutePartError::ReadRouteError { error })?.value;
+        .map_err(|error| GenerateRoutePartError::ReadRouteError { error: error.into() })?.value;
 
     let (fahrplan_zeilen, km_start, gnt_spalte) = if let Some(Datei { dateiname, .. }) = route_template.buchfahrplan_roh_datei {
         let buchfahrplan_path = env.zusi_path_to_prejoined_zusi_path(dateiname);
@@ -139,9 +153,17 @@ fn retrieve_route_part_by_path(env: &ZusiEnvironment, path: &PathBuf) -> Result<
     )
 }
 
+fn retrieve_route_part_by_nummer(resolved_routes: &ResolvedRoutes, nummer: &String) -> Result<ResolvedRoutePart, GenerateRoutePartError> {
+    resolved_routes
+        .get(nummer)
+        .map(|route| route.clone().into())
+        .ok_or_else(|| GenerateRoutePartError::ReadRouteError { error: RouteSourceError::UnresolvedZugNummer { nummer: nummer.clone() } })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::ResolvedRoute;
     use crate::core::lib::file_error::FileErrorKind;
     use crate::input::fahrplan_config::non_default_fahrzeug_verband_aktion::NonDefaultFahrzeugVerbandAktion;
     use crate::input::fahrplan_config::StartFahrzeugVerbandAktion;
@@ -279,7 +301,7 @@ mod tests {
             mindest_bremshundertstel: 0.,
         };
 
-        let resolved_route_part = generate_route_part(&env, route_part).unwrap();
+        let resolved_route_part = generate_route_part(&env, route_part, &ResolvedRoutes::new()).unwrap();
 
         assert_eq!(resolved_route_part, expected);
 
@@ -395,7 +417,7 @@ mod tests {
             mindest_bremshundertstel: 0.,
         };
 
-        let resolved_route_part = generate_route_part(&env, route_part).unwrap();
+        let resolved_route_part = generate_route_part(&env, route_part, &ResolvedRoutes::new()).unwrap();
 
         assert_eq!(resolved_route_part, expected);
 
@@ -422,16 +444,108 @@ mod tests {
         };
 
         assert!(matches!(
-            generate_route_part(&env, route_part).unwrap_err(),
+            generate_route_part(&env, route_part, &ResolvedRoutes::new()).unwrap_err(),
             GenerateRoutePartError::ReadRouteError {
-                error: FileError {
-                    kind: FileErrorKind::IOError { .. },
-                    ..
+                error: RouteSourceError::FileError {
+                    error: FileError {
+                        kind: FileErrorKind::IOError { .. },
+                        ..
+                    },
                 },
             },
         ));
     }
 
+    #[test]
+    fn test_generate_route_part_by_nummer() {
+        let tmp_dir = tempdir().unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().to_owned(),
+        };
+
+        let start_data = RouteStartData {
+            aufgleis_fahrstrasse: "Aufgleispunkt -> Hildesheim Hbf F".into(),
+            standort_modus: StandortModus::Automatisch,
+            start_vorschubweg: 0.0,
+            speed_anfang: 0.0,
+            km_start: None,
+            gnt_spalte: None,
+            fahrzeug_verband_aktion: None,
+        };
+
+        let mut resolved_routes = ResolvedRoutes::new();
+        resolved_routes.insert("10000".into(), ResolvedRoute {
+            start_data: start_data.clone(),
+            fahrplan_eintraege: vec![
+                FahrplanEintrag::builder()
+                    .abfahrt(Some(datetime!(2024-06-20 08:45:00)))
+                    .betriebsstelle("Mehle Hp".into())
+                    .build(),
+                FahrplanEintrag::builder()
+                    .ankunft(Some(datetime!(2024-06-20 08:48:00)))
+                    .abfahrt(Some(datetime!(2024-06-20 08:48:40)))
+                    .betriebsstelle("Osterwald Hp".into())
+                    .build(),
+            ],
+            fahrplan_zeilen: vec![],
+            mindest_bremshundertstel: 1.4,
+        });
+
+        let route_part = RoutePart {
+            source: RoutePartSource::TrainConfigByNummer { nummer: "10000".into() },
+            start_fahrzeug_verband_aktion: None,
+            time_fix: Some(RouteTimeFix { fix_type: RouteTimeFixType::EndAnk, value: datetime!(2024-06-20 09:48:00) }),
+            apply_schedule: None,
+        };
+
+        let expected = ResolvedRoutePart {
+            start_data,
+            fahrplan_eintraege: vec![
+                FahrplanEintrag::builder()
+                    .abfahrt(Some(datetime!(2024-06-20 09:45:00)))
+                    .betriebsstelle("Mehle Hp".into())
+                    .build(),
+                FahrplanEintrag::builder()
+                    .ankunft(Some(datetime!(2024-06-20 09:48:00)))
+                    .abfahrt(Some(datetime!(2024-06-20 09:48:40)))
+                    .betriebsstelle("Osterwald Hp".into())
+                    .build(),
+            ],
+            has_time_fix: true,
+            fahrplan_zeilen: vec![],
+            mindest_bremshundertstel: 1.4,
+        };
+
+        assert_eq!(generate_route_part(&env, route_part, &resolved_routes).unwrap(), expected);
+        assert_eq!(resolved_routes.get("10000").unwrap().fahrplan_eintraege[0].abfahrt, Some(datetime!(2024-06-20 08:45:00)));
+    }
+
+    #[test]
+    fn test_generate_route_part_by_unresolved_nummer() {
+        let tmp_dir = tempdir().unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().to_owned(),
+        };
+
+        let route_part = RoutePart {
+            source: RoutePartSource::TrainConfigByNummer { nummer: "10000".into() },
+            start_fahrzeug_verband_aktion: None,
+            time_fix: None,
+            apply_schedule: None,
+        };
+
+        assert_eq!(
+            generate_route_part(&env, route_part, &ResolvedRoutes::new()).unwrap_err(),
+            GenerateRoutePartError::ReadRouteError {
+                error: RouteSourceError::UnresolvedZugNummer { nummer: "10000".into() },
+            },
+        );
+    }
+
     #[test]
     fn test_generate_route_part_with_buchfahrplan() {
         const TRN: &str = r#"
@@ -563,7 +677,7 @@ mod tests {
             mindest_bremshundertstel: 0.,
         };
 
-        let resolved_route_part = generate_route_part(&env, route_part).unwrap();
+        let resolved_route_part = generate_route_part(&env, route_part, &ResolvedRoutes::new()).unwrap();
 
         assert_eq!(resolved_route_part, expected);
 
@@ -731,7 +845,7 @@ mod tests {
             mindest_bremshundertstel: 0.,
         };
 
-        let resolved_route_part = generate_route_part(&env, route_part).unwrap();
+        let resolved_route_part = generate_route_part(&env, route_part, &ResolvedRoutes::new()).unwrap();
 
         assert_eq!(resolved_route_part, expected);
 
@@ -892,7 +1006,7 @@ mod tests {
             mindest_bremshundertstel: 0.,
         };
 
-        let resolved_route_part = generate_route_part(&env, route_part).unwrap();
+        let resolved_route_part = generate_route_part(&env, route_part, &ResolvedRoutes::new()).unwrap();
 
         assert_eq!(resolved_route_part, expected);
 
@@ -935,7 +1049,7 @@ mod tests {
         };
 
         assert!(matches!(
-            generate_route_part(&env, route_part).unwrap_err(),
+            generate_route_part(&env, route_part, &ResolvedRoutes::new()).unwrap_err(),
             GenerateRoutePartError::ReadBuchfahrplanError {
                 error: FileError {
                     kind: FileErrorKind::IOError { .. },
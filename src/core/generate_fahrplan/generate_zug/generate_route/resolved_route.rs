@@ -1,4 +1,5 @@
 use crate::core::lib::generated_zug::RawGeneratedZug;
+use std::collections::HashMap;
 use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::FahrplanZeile;
 use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
 use zusi_xml_lib::xml::zusi::zug::standort_modus::StandortModus;
@@ -24,6 +25,9 @@ impl From<ResolvedRoutePart> for ResolvedRoute {
     }
 }
 
+/// Already resolved routes by the Zugnummer of the Zug they were generated for
+pub type ResolvedRoutes = HashMap<String, ResolvedRoute>;
+
 // TODO: refactor using builder?
 #[derive(Debug, Clone, PartialEq)]
 pub struct ResolvedRoutePart {
@@ -46,6 +50,12 @@ impl ResolvedRoutePart {
     }
 }
 
+impl From<ResolvedRoute> for ResolvedRoutePart {
+    fn from(ResolvedRoute { start_data, fahrplan_eintraege, fahrplan_zeilen, mindest_bremshundertstel }: ResolvedRoute) -> Self {
+        Self::new(start_data, fahrplan_eintraege, fahrplan_zeilen, mindest_bremshundertstel)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct RouteStartData {
     pub aufgleis_fahrstrasse: String,
@@ -0,0 +1,215 @@
+use crate::core::lib::generated_zug::RawGeneratedZug;
+use crate::core::lib::zug_nummer::ZugNummer;
+use crate::input::copy_delay_config::CopyDelayConfig;
+use crate::input::fahrplan_config::{ReturnConfig, RouteTimeFix, RouteTimeFixType, StartFahrzeugVerbandAktion, ZugConfig};
+use std::num::ParseIntError;
+use thiserror::Error;
+use time::PrimitiveDateTime;
+use zusi_xml_lib::xml::zusi::zug::Zug;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ReturnZugError {
+    #[error("The Zug has no Ankunft the return Zug could depart after.")]
+    NoAnkunft,
+
+    #[error("The Zugnummer is invalid: {error}")]
+    InvalidZugNummer {
+        #[from]
+        error: ParseIntError,
+    },
+
+    #[error("The Zugnummer of the return Zug must be positive.")]
+    ZugNummerCanNotBeNegative,
+
+    #[error("The return Zug departs at {abfahrt} but the minimum turnaround time allows {earliest_abfahrt} at the earliest.")]
+    TurnaroundTooShort {
+        abfahrt: PrimitiveDateTime,
+        earliest_abfahrt: PrimitiveDateTime,
+    },
+}
+
+pub fn return_zug_nummer(zug_nummer: &str, config: &ReturnConfig) -> Result<String, ReturnZugError> {
+    Ok(
+        ZugNummer::try_from(zug_nummer)?
+            .to_new_incremented(config.increment)
+            .map_err(|_| ReturnZugError::ZugNummerCanNotBeNegative)?
+            .into()
+    )
+}
+
+/// Sets the vehicle turn at the last FahrplanEintrag of the Zug.
+pub fn apply_vehicle_turn(zug: &mut Zug, aktion: &StartFahrzeugVerbandAktion) {
+    if let Some(last) = zug.fahrplan_eintraege.last_mut() {
+        last.fahrzeug_verband_aktion = aktion.aktion.clone().into();
+        last.fahrzeug_verband_aktion_wende_signal = aktion.wende_signal;
+        last.fahrzeug_verband_aktion_wende_signal_abstand = aktion.wende_signal_abstand;
+    }
+}
+
+/// Creates the config of the return Zug based on the config of the Zug and the generated Zug itself.
+/// The first part of the return route is fixed to depart the minimum turnaround time after the Ankunft of the Zug.
+/// A StartAbf TimeFix configured for that part is kept if it isn't earlier, other TimeFix types are kept unchecked.
+/// The copies of the Zug are passed as copy delay config, so every copy gets a matching return Zug.
+pub fn return_zug_config(zug_config: ZugConfig, return_config: ReturnConfig, zug: &RawGeneratedZug, copy_delay_config: Option<CopyDelayConfig>) -> Result<ZugConfig, ReturnZugError> {
+    let ankunft = zug.zug.fahrplan_eintraege
+        .iter()
+        .rev()
+        .find_map(|eintrag| eintrag.ankunft.or(eintrag.abfahrt))
+        .ok_or(ReturnZugError::NoAnkunft)?;
+
+    let earliest_abfahrt = ankunft + return_config.min_turnaround_time;
+    let mut route = return_config.route.clone();
+    if let Some(first_part) = route.parts.first_mut() {
+        match &first_part.time_fix {
+            Some(RouteTimeFix { fix_type: RouteTimeFixType::StartAbf, value }) if *value < earliest_abfahrt => {
+                return Err(ReturnZugError::TurnaroundTooShort { abfahrt: *value, earliest_abfahrt });
+            },
+            Some(_) => {},
+            None => first_part.time_fix = Some(RouteTimeFix {
+                fix_type: RouteTimeFixType::StartAbf,
+                value: earliest_abfahrt,
+            }),
+        }
+    }
+
+    Ok(ZugConfig {
+        nummer: return_zug_nummer(&zug_config.nummer, &return_config)?,
+        zuglauf: if return_config.zuglauf.is_empty() { zug_config.zuglauf } else { return_config.zuglauf },
+        route,
+        copy_delay_config,
+        takt: None,
+        return_config: None,
+        ..zug_config
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fahrplan_config::non_default_fahrzeug_verband_aktion::NonDefaultFahrzeugVerbandAktion;
+    use crate::input::fahrplan_config::{RouteConfig, RoutePart, RoutePartSource};
+    use crate::input::rolling_stock_config::RollingStockConfig;
+    use time::macros::datetime;
+    use time::Duration;
+    use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+    use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::fahrzeug_verband_aktion::FahrzeugVerbandAktion;
+    use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
+    use zusi_xml_lib::xml::zusi::zug::fahrzeug_varianten::FahrzeugVarianten;
+
+    fn route_config(path: &str) -> RouteConfig {
+        RouteConfig {
+            parts: vec![
+                RoutePart {
+                    source: RoutePartSource::TrainFileByPath { path: path.into() },
+                    start_fahrzeug_verband_aktion: None,
+                    time_fix: None,
+                    apply_schedule: None,
+                },
+            ],
+            includes: vec![],
+        }
+    }
+
+    fn zug() -> RawGeneratedZug {
+        RawGeneratedZug {
+            zug: Zug::builder()
+                .fahrplan_datei(Datei::builder().build())
+                .fahrzeug_varianten(FahrzeugVarianten::builder().build())
+                .fahrplan_eintraege(vec![
+                    FahrplanEintrag::builder().betriebsstelle("A".into()).abfahrt(Some(datetime!(2024-06-20 08:00:00))).build(),
+                    FahrplanEintrag::builder().betriebsstelle("B".into()).ankunft(Some(datetime!(2024-06-20 08:31:00))).abfahrt(Some(datetime!(2024-06-20 08:32:00))).build(),
+                ])
+                .build(),
+            buchfahrplan: None,
+        }
+    }
+
+    fn zug_config() -> ZugConfig {
+        ZugConfig {
+            nummer: "20001".into(),
+            gattung: "RB".into(),
+            extends: None,
+            zuglauf: "A - B".into(),
+            fahrplan_gruppe: "A - B".into(),
+            meta_data: None,
+            route: route_config("outbound.trn"),
+            rolling_stock: RollingStockConfig { path: "rolling-stock.trn".into() },
+            copy_delay_config: None,
+            takt: None,
+            return_config: None,
+            time_rounding: None,
+        }
+    }
+
+    fn return_config() -> ReturnConfig {
+        ReturnConfig {
+            min_turnaround_time: Duration::minutes(12),
+            increment: 1,
+            zuglauf: "B - A".into(),
+            route: route_config("return.trn"),
+            start_fahrzeug_verband_aktion: None,
+        }
+    }
+
+    fn start_abf(value: PrimitiveDateTime) -> Option<RouteTimeFix> {
+        Some(RouteTimeFix { fix_type: RouteTimeFixType::StartAbf, value })
+    }
+
+    #[test]
+    fn test_return_zug_config() {
+        let mut expected_route = route_config("return.trn");
+        expected_route.parts[0].time_fix = start_abf(datetime!(2024-06-20 08:43:00));
+
+        assert_eq!(
+            return_zug_config(zug_config(), return_config(), &zug(), None).unwrap(),
+            ZugConfig {
+                nummer: "20002".into(),
+                gattung: "RB".into(),
+                extends: None,
+                zuglauf: "B - A".into(),
+                fahrplan_gruppe: "A - B".into(),
+                meta_data: None,
+                route: expected_route,
+                rolling_stock: RollingStockConfig { path: "rolling-stock.trn".into() },
+                copy_delay_config: None,
+                takt: None,
+                return_config: None,
+                time_rounding: None,
+            },
+        );
+    }
+
+    #[test]
+    fn test_return_zug_config_with_time_fix() {
+        let mut return_config = return_config();
+        return_config.route.parts[0].time_fix = start_abf(datetime!(2024-06-20 08:50:00));
+
+        assert_eq!(
+            return_zug_config(zug_config(), return_config.clone(), &zug(), None).unwrap().route,
+            return_config.route,
+        );
+
+        return_config.route.parts[0].time_fix = start_abf(datetime!(2024-06-20 08:40:00));
+
+        assert_eq!(
+            return_zug_config(zug_config(), return_config, &zug(), None),
+            Err(ReturnZugError::TurnaroundTooShort {
+                abfahrt: datetime!(2024-06-20 08:40:00),
+                earliest_abfahrt: datetime!(2024-06-20 08:43:00),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_apply_vehicle_turn() {
+        let mut zug = zug();
+        apply_vehicle_turn(&mut zug.zug, &StartFahrzeugVerbandAktion {
+            aktion: NonDefaultFahrzeugVerbandAktion::ZugDrehen,
+            wende_signal: false,
+            wende_signal_abstand: 0.,
+        });
+
+        assert_eq!(zug.zug.fahrplan_eintraege[0].fahrzeug_verband_aktion, FahrzeugVerbandAktion::default());
+        assert_eq!(zug.zug.fahrplan_eintraege[1].fahrzeug_verband_aktion, FahrzeugVerbandAktion::ZugDrehen);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,108 @@
+use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::ResolvedRoute;
+use crate::core::lib::generated_zug::RawGeneratedZug;
+use crate::core::lib::time_rounding::round_stop;
+use crate::input::fahrplan_config::TimeRounding;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_abfahrt::FahrplanAbfahrt;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_ankunft::FahrplanAnkunft;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::FahrplanZeile;
+use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
+
+/// Rounds all times of the FahrplanEintraege and, if present, of the FahrplanZeilen of the Buchfahrplan.
+/// Both are rounded by the same rules, so related entries stay equal.
+pub fn round_times(zug: &mut RawGeneratedZug, rounding: &TimeRounding) {
+    round_fahrplan_eintraege(&mut zug.zug.fahrplan_eintraege, rounding);
+    if let Some(buchfahrplan) = &mut zug.buchfahrplan {
+        round_fahrplan_zeilen(&mut buchfahrplan.fahrplan_zeilen, rounding);
+    }
+}
+
+/// Rounds the times of a resolved route the same way as [round_times], so Zuege referencing it get the rounded times.
+pub fn round_route(route: &mut ResolvedRoute, rounding: &TimeRounding) {
+    round_fahrplan_eintraege(&mut route.fahrplan_eintraege, rounding);
+    round_fahrplan_zeilen(&mut route.fahrplan_zeilen, rounding);
+}
+
+fn round_fahrplan_eintraege(fahrplan_eintraege: &mut [FahrplanEintrag], rounding: &TimeRounding) {
+    fahrplan_eintraege.iter_mut().for_each(|eintrag| {
+        (eintrag.ankunft, eintrag.abfahrt) = round_stop(eintrag.ankunft, eintrag.abfahrt, rounding.granularity, rounding.direction);
+    });
+}
+
+fn round_fahrplan_zeilen(fahrplan_zeilen: &mut [FahrplanZeile], rounding: &TimeRounding) {
+    fahrplan_zeilen.iter_mut().for_each(|zeile| {
+        let (ankunft, abfahrt) = round_stop(
+            zeile.fahrplan_ankunft.as_ref().map(|FahrplanAnkunft { ankunft, .. }| *ankunft),
+            zeile.fahrplan_abfahrt.as_ref().map(|FahrplanAbfahrt { abfahrt, .. }| *abfahrt),
+            rounding.granularity,
+            rounding.direction,
+        );
+        if let (Some(fahrplan_ankunft), Some(ankunft)) = (&mut zeile.fahrplan_ankunft, ankunft) {
+            fahrplan_ankunft.ankunft = ankunft;
+        }
+        if let (Some(fahrplan_abfahrt), Some(abfahrt)) = (&mut zeile.fahrplan_abfahrt, abfahrt) {
+            fahrplan_abfahrt.abfahrt = abfahrt;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::lib::time_rounding::{RoundingDirection, RoundingGranularity};
+    use time::macros::datetime;
+    use zusi_xml_lib::xml::zusi::buchfahrplan::Buchfahrplan;
+    use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+    use zusi_xml_lib::xml::zusi::lib::utm::UTM;
+    use zusi_xml_lib::xml::zusi::zug::fahrzeug_varianten::FahrzeugVarianten;
+    use zusi_xml_lib::xml::zusi::zug::Zug;
+
+    #[test]
+    fn test_round_times() {
+        let mut zug = RawGeneratedZug {
+            zug: Zug::builder()
+                .fahrplan_datei(Datei::builder().build())
+                .fahrzeug_varianten(FahrzeugVarianten::builder().build())
+                .fahrplan_eintraege(vec![
+                    FahrplanEintrag::builder().betriebsstelle("A".into()).ankunft(Some(datetime!(2024-06-20 08:39:37))).abfahrt(Some(datetime!(2024-06-20 08:40:02))).build(),
+                    FahrplanEintrag::builder().betriebsstelle("B".into()).abfahrt(Some(datetime!(2024-06-20 08:45:29))).build(),
+                ])
+                .build(),
+            buchfahrplan: Some(Buchfahrplan::builder()
+                .datei_trn(Datei::builder().build())
+                .datei_fpn(Datei::builder().build())
+                .utm(UTM::builder().build())
+                .fahrplan_zeilen(vec![
+                    FahrplanZeile::builder()
+                        .fahrplan_ankunft(Some(FahrplanAnkunft::builder().ankunft(datetime!(2024-06-20 08:39:37)).build()))
+                        .fahrplan_abfahrt(Some(FahrplanAbfahrt::builder().abfahrt(datetime!(2024-06-20 08:40:02)).build()))
+                        .build(),
+                    FahrplanZeile::builder().build(),
+                    FahrplanZeile::builder()
+                        .fahrplan_abfahrt(Some(FahrplanAbfahrt::builder().abfahrt(datetime!(2024-06-20 08:45:29)).build()))
+                        .build(),
+                ])
+                .build()),
+        };
+
+        round_times(&mut zug, &TimeRounding { granularity: RoundingGranularity::Minute, direction: RoundingDirection::Nearest });
+
+        assert_eq!(
+            zug.zug.fahrplan_eintraege.iter().map(|eintrag| (eintrag.ankunft, eintrag.abfahrt)).collect::<Vec<_>>(),
+            vec![
+                (Some(datetime!(2024-06-20 08:40:00)), Some(datetime!(2024-06-20 08:41:00))),
+                (None, Some(datetime!(2024-06-20 08:45:00))),
+            ],
+        );
+        assert_eq!(
+            zug.buchfahrplan.unwrap().fahrplan_zeilen.into_iter().map(|zeile| (
+                zeile.fahrplan_ankunft.map(|ankunft| ankunft.ankunft),
+                zeile.fahrplan_abfahrt.map(|abfahrt| abfahrt.abfahrt),
+            )).collect::<Vec<_>>(),
+            vec![
+                (Some(datetime!(2024-06-20 08:40:00)), Some(datetime!(2024-06-20 08:41:00))),
+                (None, None),
+                (None, Some(datetime!(2024-06-20 08:45:00))),
+            ],
+        );
+    }
+}
\ No newline at end of file
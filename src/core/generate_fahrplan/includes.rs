@@ -0,0 +1,347 @@
+use crate::core::lib::file_error::{FileError, FileErrorKind};
+use crate::input::config_file::read_config_with_variables;
+use crate::input::config_fragment::ConfigFragment;
+use crate::input::copy_delay_config::CopyDelayConfig;
+use crate::input::environment::zusi_environment::ZusiEnvironment;
+use crate::input::fahrplan_config::{FahrplanConfig, RouteConfig, RoutePart, RoutePartSource, ZugConfig, ZugTemplate};
+use crate::input::variables::VariableConfig;
+use std::mem;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum IncludeError {
+    #[error("The included file couldn't be read: {error}")]
+    ReadError {
+        error: FileError,
+    },
+
+    #[error("The includes form a cycle: {}", paths.iter().map(|path| format!(r#""{}""#, path.display())).collect::<Vec<_>>().join(" -> "))]
+    Cycle {
+        paths: Vec<PathBuf>,
+    },
+
+    #[error(r#"The included file "{}" contains no RouteParts named '{name}'."#, path.display())]
+    MissingRouteParts {
+        path: PathBuf,
+        name: String,
+    },
+}
+
+impl From<FileError> for IncludeError {
+    fn from(error: FileError) -> Self {
+        IncludeError::ReadError { error }
+    }
+}
+
+/// Files which are currently included and the variables used in the included files
+struct IncludeStack {
+    files: Vec<PathBuf>,
+    variables: Vec<VariableConfig>,
+}
+
+/// Replaces all includes of the config by the ZugTemplates, Zuege and RouteParts of the included ConfigFragments.
+/// Relative paths of the included ZugTemplates, Zuege and RouteParts are rewritten to be relative to the config dir.
+/// The variables of the config are replaced in the included files as well.
+pub fn resolve_includes(env: &ZusiEnvironment, config: &mut FahrplanConfig) -> Result<(), IncludeError> {
+    let mut stack = IncludeStack {
+        files: vec![],
+        variables: config.variables.variables.clone(),
+    };
+    for template in &mut config.templates {
+        resolve_route_includes(env, env, &mut template.route, &mut stack)?;
+    }
+    for zug in &mut config.zuege {
+        resolve_zug_includes(env, env, zug, &mut stack)?;
+    }
+    for include in mem::take(&mut config.includes) {
+        include_fragment(env, env, &include.path, config, &mut stack)?;
+    }
+    Ok(())
+}
+
+/// Adds the ZugTemplates and Zuege of the fragment at the path and all fragments included by it to the config
+fn include_fragment(env: &ZusiEnvironment, file_env: &ZusiEnvironment, path: &Path, config: &mut FahrplanConfig, stack: &mut IncludeStack) -> Result<(), IncludeError> {
+    let (fragment_env, fragment) = read_fragment(file_env, path, stack)?;
+    let base = relative_path(&fragment_env.config_dir, &env.config_dir);
+    for mut template in fragment.templates {
+        rebase_template(&mut template, &base);
+        resolve_route_includes(env, &fragment_env, &mut template.route, stack)?;
+        config.templates.push(template);
+    }
+    for mut zug in fragment.zuege {
+        rebase_zug(&mut zug, &base);
+        resolve_zug_includes(env, &fragment_env, &mut zug, stack)?;
+        config.zuege.push(zug);
+    }
+    for include in fragment.includes {
+        include_fragment(env, &fragment_env, &include.path, config, stack)?;
+    }
+    stack.files.pop();
+    Ok(())
+}
+
+fn resolve_zug_includes(env: &ZusiEnvironment, file_env: &ZusiEnvironment, zug: &mut ZugConfig, stack: &mut IncludeStack) -> Result<(), IncludeError> {
+    resolve_route_includes(env, file_env, &mut zug.route, stack)?;
+    if let Some(return_config) = &mut zug.return_config {
+        resolve_route_includes(env, file_env, &mut return_config.route, stack)?;
+    }
+    Ok(())
+}
+
+fn resolve_route_includes(env: &ZusiEnvironment, file_env: &ZusiEnvironment, route: &mut RouteConfig, stack: &mut IncludeStack) -> Result<(), IncludeError> {
+    // inserting from the back keeps the indices of the preceding includes valid
+    for route_include in mem::take(&mut route.includes).into_iter().rev() {
+        let parts = included_route_parts(env, file_env, &route_include.include.path, &route_include.include.name, stack)?
+            .ok_or_else(|| IncludeError::MissingRouteParts {
+                path: route_include.include.path.clone(),
+                name: route_include.include.name.clone(),
+            })?;
+        route.parts.splice(route_include.index..route_include.index, parts);
+    }
+    Ok(())
+}
+
+/// RouteParts of the list with the given name, searched in the fragment at the path and all fragments included by it
+fn included_route_parts(env: &ZusiEnvironment, file_env: &ZusiEnvironment, path: &Path, name: &str, stack: &mut IncludeStack) -> Result<Option<Vec<RoutePart>>, IncludeError> {
+    let (fragment_env, fragment) = read_fragment(file_env, path, stack)?;
+    let mut parts = match fragment.route_parts.into_iter().find(|route_parts| route_parts.name == name) {
+        Some(route_parts) => {
+            let base = relative_path(&fragment_env.config_dir, &env.config_dir);
+            let mut parts = route_parts.parts;
+            parts.iter_mut().for_each(|part| rebase_route_part(part, &base));
+            Some(parts)
+        },
+        None => None,
+    };
+    for include in fragment.includes {
+        if parts.is_some() {
+            break;
+        }
+        parts = included_route_parts(env, &fragment_env, &include.path, name, stack)?;
+    }
+    stack.files.pop();
+    Ok(parts)
+}
+
+/// Reads the fragment and pushes its path onto the stack of the files which are currently included.
+/// The returned environment resolves relative paths relative to the fragment.
+fn read_fragment(file_env: &ZusiEnvironment, path: &Path, stack: &mut IncludeStack) -> Result<(ZusiEnvironment, ConfigFragment), IncludeError> {
+    let path = file_env.path_to_prejoined_zusi_path(path)?.full_path().to_owned();
+    if stack.files.contains(&path) {
+        let mut paths = stack.files.clone();
+        paths.push(path);
+        return Err(IncludeError::Cycle { paths });
+    }
+    let fragment: ConfigFragment = read_config_with_variables(&path, &stack.variables)?;
+    let config_dir = path.parent().ok_or_else(|| FileError::from((&path, FileErrorKind::MustHaveParent)))?;
+    let fragment_env = ZusiEnvironment {
+        data_dir: file_env.data_dir.clone(),
+        config_dir: config_dir.to_owned(),
+    };
+    stack.files.push(path);
+    Ok((fragment_env, fragment))
+}
+
+fn rebase_template(template: &mut ZugTemplate, base: &Path) {
+    if let Some(meta_data) = &mut template.meta_data {
+        rebase_path(&mut meta_data.path, base);
+    }
+    template.route.parts.iter_mut().for_each(|part| rebase_route_part(part, base));
+    rebase_path(&mut template.rolling_stock.path, base);
+    if let Some(copy_delay_config) = &mut template.copy_delay_config {
+        rebase_copy_delay_config(copy_delay_config, base);
+    }
+}
+
+fn rebase_zug(zug: &mut ZugConfig, base: &Path) {
+    if let Some(meta_data) = &mut zug.meta_data {
+        rebase_path(&mut meta_data.path, base);
+    }
+    zug.route.parts.iter_mut().for_each(|part| rebase_route_part(part, base));
+    rebase_path(&mut zug.rolling_stock.path, base);
+    if let Some(copy_delay_config) = &mut zug.copy_delay_config {
+        rebase_copy_delay_config(copy_delay_config, base);
+    }
+    if let Some(return_config) = &mut zug.return_config {
+        return_config.route.parts.iter_mut().for_each(|part| rebase_route_part(part, base));
+    }
+}
+
+fn rebase_copy_delay_config(copy_delay_config: &mut CopyDelayConfig, base: &Path) {
+    copy_delay_config.tasks
+        .iter_mut()
+        .filter_map(|task| task.custom_rolling_stock.as_mut())
+        .for_each(|rolling_stock| rebase_path(&mut rolling_stock.path, base));
+}
+
+fn rebase_route_part(part: &mut RoutePart, base: &Path) {
+    if let RoutePartSource::TrainFileByPath { path } = &mut part.source {
+        rebase_path(path, base);
+    }
+    if let Some(apply_schedule) = &mut part.apply_schedule {
+        rebase_path(&mut apply_schedule.path, base);
+    }
+}
+
+/// Absolute paths are relative to the data dir and stay unchanged, empty paths mean the value is taken from a ZugTemplate
+fn rebase_path(path: &mut PathBuf, base: &Path) {
+    if path.is_relative() && !path.as_os_str().is_empty() {
+        *path = base.join(path.as_path());
+    }
+}
+
+/// Path which leads from the directory `from` to `to`, both paths have to be absolute
+fn relative_path(to: &Path, from: &Path) -> PathBuf {
+    let to: Vec<_> = to.components().collect();
+    let from: Vec<_> = from.components().collect();
+    let common = to.iter().zip(&from).take_while(|(a, b)| a == b).count();
+    from[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .chain(to[common..].iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fahrplan_config::{IncludeConfig, RouteInclude, RouteIncludeConfig};
+    use crate::input::rolling_stock_config::RollingStockConfig;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const LINE_FRAGMENT: &str = r#"
+        <ConfigFragment>
+            <Include path="../shared/routes.xml"/>
+            <ZugTemplate id="rb" gattung="RB">
+                <RollingStock path="./rolling-stock.trn"/>
+            </ZugTemplate>
+            <Zug nummer="20000" extends="rb">
+                <Route>
+                    <RoutePart>
+                        <TrainFileByPath path="./route-part-1.trn"/>
+                    </RoutePart>
+                    <Include path="../shared/routes.xml" name="main-line"/>
+                </Route>
+            </Zug>
+        </ConfigFragment>
+    "#;
+
+    const SHARED_FRAGMENT: &str = r#"
+        <ConfigFragment>
+            <RouteParts name="main-line">
+                <RoutePart>
+                    <TrainFileByPath path="./route-part-2.trn"/>
+                </RoutePart>
+            </RouteParts>
+        </ConfigFragment>
+    "#;
+
+    fn route_part(path: &str) -> RoutePart {
+        RoutePart {
+            source: RoutePartSource::TrainFileByPath { path: path.into() },
+            start_fahrzeug_verband_aktion: None,
+            time_fix: None,
+            apply_schedule: None,
+        }
+    }
+
+    fn config(includes: Vec<IncludeConfig>) -> FahrplanConfig {
+        FahrplanConfig {
+            generate_at: "./out.fpn".into(),
+            generate_from: "./template.fpn".into(),
+            collect_errors: false,
+            zug_collisions: Default::default(),
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![],
+            includes,
+            zuege: vec![
+                ZugConfig {
+                    nummer: "10000".into(),
+                    gattung: "RB".into(),
+                    extends: None,
+                    zuglauf: "".into(),
+                    fahrplan_gruppe: "".into(),
+                    meta_data: None,
+                    route: RouteConfig {
+                        parts: vec![],
+                        includes: vec![
+                            RouteInclude {
+                                index: 0,
+                                include: RouteIncludeConfig { path: "./shared/routes.xml".into(), name: "main-line".into() },
+                            },
+                        ],
+                    },
+                    rolling_stock: RollingStockConfig { path: "./rolling-stock.trn".into() },
+                    copy_delay_config: None,
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let tmp_dir = tempdir().unwrap();
+        let tmp_dir = tmp_dir.path().canonicalize().unwrap();
+
+        fs::create_dir_all(tmp_dir.join("dev/line-a")).unwrap();
+        fs::create_dir_all(tmp_dir.join("dev/shared")).unwrap();
+        fs::write(tmp_dir.join("dev/line-a/line.xml"), LINE_FRAGMENT).unwrap();
+        fs::write(tmp_dir.join("dev/shared/routes.xml"), SHARED_FRAGMENT).unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.clone(),
+            config_dir: tmp_dir.join("dev"),
+        };
+
+        let mut config = config(vec![IncludeConfig { path: "./line-a/line.xml".into() }]);
+        resolve_includes(&env, &mut config).unwrap();
+
+        assert!(config.includes.is_empty());
+        assert_eq!(config.zuege.len(), 2);
+        assert_eq!(config.zuege[0].route.parts, vec![route_part("shared/route-part-2.trn")]);
+        assert!(config.zuege[0].route.includes.is_empty());
+        assert_eq!(config.zuege[1].nummer, "20000");
+        assert_eq!(
+            config.zuege[1].route.parts,
+            vec![route_part("line-a/route-part-1.trn"), route_part("shared/route-part-2.trn")],
+        );
+        assert_eq!(config.zuege[1].rolling_stock.path, PathBuf::new());
+        assert_eq!(config.templates.len(), 1);
+        assert_eq!(config.templates[0].rolling_stock.path, PathBuf::from("line-a/rolling-stock.trn"));
+    }
+
+    #[test]
+    fn test_resolve_includes_with_cycle() {
+        let tmp_dir = tempdir().unwrap();
+        let tmp_dir = tmp_dir.path().canonicalize().unwrap();
+
+        fs::create_dir_all(tmp_dir.join("dev")).unwrap();
+        fs::write(tmp_dir.join("dev/a.xml"), r#"<ConfigFragment><Include path="./b.xml"/></ConfigFragment>"#).unwrap();
+        fs::write(tmp_dir.join("dev/b.xml"), r#"<ConfigFragment><Include path="./a.xml"/></ConfigFragment>"#).unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.clone(),
+            config_dir: tmp_dir.join("dev"),
+        };
+
+        let mut config = config(vec![IncludeConfig { path: "./a.xml".into() }]);
+        config.zuege = vec![];
+
+        assert_eq!(
+            resolve_includes(&env, &mut config),
+            Err(IncludeError::Cycle {
+                paths: vec![tmp_dir.join("dev/a.xml"), tmp_dir.join("dev/b.xml"), tmp_dir.join("dev/a.xml")],
+            }),
+        );
+    }
+}
\ No newline at end of file
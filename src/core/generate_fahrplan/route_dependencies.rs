@@ -0,0 +1,163 @@
+use crate::input::fahrplan_config::{RoutePartSource, ZugConfig};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RouteDependencyError {
+    #[error("The routes of the following Zuege depend on each other in a cycle: {}", nummern.join(" -> "))]
+    CyclicDependency {
+        nummern: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VisitState {
+    Unvisited,
+    InProgress,
+    Done,
+}
+
+/// Sorts the given Zug configs so that each one is placed after all Zug configs its route references by Zugnummer.
+/// The original order is kept as far as possible. References to unknown Zugnummern are ignored.
+pub fn sort_by_route_dependencies(zuege: Vec<ZugConfig>) -> Result<Vec<ZugConfig>, RouteDependencyError> {
+    let order = {
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        zuege.iter().enumerate().for_each(|(index, zug)| {
+            indices.entry(zug.nummer.as_str()).or_insert(index);
+        });
+
+        let mut states = vec![VisitState::Unvisited; zuege.len()];
+        let mut order = Vec::with_capacity(zuege.len());
+        (0..zuege.len()).try_for_each(|index|
+            visit(index, &zuege, &indices, &mut states, &mut vec![], &mut order)
+        )?;
+        order
+    };
+
+    let mut zuege: Vec<Option<ZugConfig>> = zuege.into_iter().map(Some).collect();
+    Ok(order.into_iter().map(|index| zuege[index].take().unwrap()).collect()) // every index is contained exactly once
+}
+
+/// Zugnummern referenced by the route and the return route of the given Zug config
+pub fn route_dependencies(zug: &ZugConfig) -> impl Iterator<Item = &String> {
+    let return_parts = zug.return_config.iter().flat_map(|return_config| return_config.route.parts.iter());
+    zug.route.parts.iter().chain(return_parts).filter_map(|part| match &part.source {
+        RoutePartSource::TrainConfigByNummer { nummer } => Some(nummer),
+        RoutePartSource::TrainFileByPath { .. } => None,
+    })
+}
+
+fn visit(
+    index: usize,
+    zuege: &[ZugConfig],
+    indices: &HashMap<&str, usize>,
+    states: &mut [VisitState],
+    path: &mut Vec<usize>,
+    order: &mut Vec<usize>,
+) -> Result<(), RouteDependencyError> {
+    match states[index] {
+        VisitState::Done => return Ok(()),
+        VisitState::InProgress => {
+            let cycle_start = path.iter().position(|visited| *visited == index).unwrap(); // InProgress entries are always part of the path
+            let mut nummern: Vec<String> = path[cycle_start..].iter().map(|visited| zuege[*visited].nummer.clone()).collect();
+            nummern.push(zuege[index].nummer.clone());
+            return Err(RouteDependencyError::CyclicDependency { nummern });
+        },
+        VisitState::Unvisited => {},
+    }
+
+    states[index] = VisitState::InProgress;
+    path.push(index);
+    route_dependencies(&zuege[index])
+        .filter_map(|nummer| indices.get(nummer.as_str()))
+        .try_for_each(|dependency| visit(*dependency, zuege, indices, states, path, order))?;
+    path.pop();
+    states[index] = VisitState::Done;
+    order.push(index);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fahrplan_config::{RouteConfig, RoutePart};
+    use crate::input::rolling_stock_config::RollingStockConfig;
+
+    fn zug_config(nummer: &str, dependencies: Vec<&str>) -> ZugConfig {
+        let mut parts = vec![
+            RoutePart {
+                source: RoutePartSource::TrainFileByPath { path: "route.trn".into() },
+                start_fahrzeug_verband_aktion: None,
+                time_fix: None,
+                apply_schedule: None,
+            },
+        ];
+        parts.extend(dependencies.into_iter().map(|dependency| RoutePart {
+            source: RoutePartSource::TrainConfigByNummer { nummer: dependency.into() },
+            start_fahrzeug_verband_aktion: None,
+            time_fix: None,
+            apply_schedule: None,
+        }));
+        ZugConfig {
+            nummer: nummer.into(),
+            gattung: "RB".into(),
+            extends: None,
+            zuglauf: "".into(),
+            fahrplan_gruppe: "".into(),
+            meta_data: None,
+            route: RouteConfig { parts, includes: vec![] },
+            rolling_stock: RollingStockConfig { path: "rolling-stock.trn".into() },
+            copy_delay_config: None,
+            takt: None,
+            return_config: None,
+            time_rounding: None,
+        }
+    }
+
+    fn nummern(zuege: Vec<ZugConfig>) -> Vec<String> {
+        zuege.into_iter().map(|zug| zug.nummer).collect()
+    }
+
+    #[test]
+    fn test_sort_by_route_dependencies() {
+        let zuege = vec![
+            zug_config("1", vec!["3"]),
+            zug_config("2", vec![]),
+            zug_config("3", vec!["4", "2"]),
+            zug_config("4", vec![]),
+            zug_config("5", vec!["99"]),
+        ];
+
+        assert_eq!(
+            nummern(sort_by_route_dependencies(zuege).unwrap()),
+            vec!["4", "2", "3", "1", "5"],
+        );
+    }
+
+    #[test]
+    fn test_sort_by_route_dependencies_with_cycle() {
+        let zuege = vec![
+            zug_config("1", vec![]),
+            zug_config("2", vec!["3"]),
+            zug_config("3", vec!["4"]),
+            zug_config("4", vec!["2"]),
+        ];
+
+        assert_eq!(
+            sort_by_route_dependencies(zuege).unwrap_err(),
+            RouteDependencyError::CyclicDependency { nummern: vec!["2".into(), "3".into(), "4".into(), "2".into()] },
+        );
+    }
+
+    #[test]
+    fn test_sort_by_route_dependencies_with_self_reference() {
+        let zuege = vec![
+            zug_config("1", vec!["1"]),
+        ];
+
+        assert_eq!(
+            sort_by_route_dependencies(zuege).unwrap_err(),
+            RouteDependencyError::CyclicDependency { nummern: vec!["1".into(), "1".into()] },
+        );
+    }
+}
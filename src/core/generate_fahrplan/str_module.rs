@@ -0,0 +1,217 @@
+use crate::core::lib::file_error::FileError;
+use crate::core::lib::helpers::{datei_from_prejoined_zusi_path, read_fahrplan, read_zug};
+use crate::input::environment::zusi_environment::ZusiEnvironment;
+use crate::input::fahrplan_config::{RoutePartSource, StrModuleConfig, ZugConfig};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use thiserror::Error;
+use zusi_xml_lib::xml::zusi::fahrplan::str_modul::StrModul;
+use zusi_xml_lib::xml::zusi::fahrplan::Fahrplan;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum StrModuleError {
+    #[error("The route template couldn't be read: {error}")]
+    ReadRouteError {
+        error: FileError,
+    },
+
+    #[error("The Fahrplan of the route template {route:?} couldn't be read: {error}")]
+    ReadRouteFahrplanError {
+        route: PathBuf,
+        error: FileError,
+    },
+
+    #[error("The Fahrplan of the route template {route:?} has a different UTM position than the generated Fahrplan, its Streckenmodule can't be added.")]
+    DifferentUtm {
+        route: PathBuf,
+    },
+
+    #[error("The path of the StrModul is invalid: {error}")]
+    InvalidPath {
+        error: FileError,
+    },
+}
+
+/// Adds the configured Streckenmodule to the Fahrplan which aren't contained yet.
+/// If `from_routes` is set, the Streckenmodule of the Fahrplaene the route templates of the Zuege were created in are added as well.
+/// These Fahrplaene must have the same UTM position as the generated Fahrplan, as the Streckenmodule are placed relative to it.
+pub fn add_str_module(env: &ZusiEnvironment, config: &StrModuleConfig, zuege: &[ZugConfig], fahrplan: &mut Fahrplan) -> Result<(), StrModuleError> {
+    if config.from_routes {
+        let mut visited_fahrplaene = HashSet::new();
+        for path in route_template_paths(zuege) {
+            let route_path = env.path_to_prejoined_zusi_path(path)
+                .map_err(|error| StrModuleError::ReadRouteError { error })?;
+            let dateiname = read_zug(route_path.full_path())
+                .map_err(|error| StrModuleError::ReadRouteError { error })?
+                .value.fahrplan_datei.dateiname;
+            if dateiname.get().as_os_str().is_empty() || !visited_fahrplaene.insert(dateiname.clone()) {
+                continue;
+            }
+            let route_fahrplan = read_fahrplan(env.zusi_path_to_prejoined_zusi_path(dateiname).full_path())
+                .map_err(|error| StrModuleError::ReadRouteFahrplanError { route: path.clone(), error })?;
+            if route_fahrplan.value.utm != fahrplan.utm {
+                return Err(StrModuleError::DifferentUtm { route: path.clone() });
+            }
+            route_fahrplan.value.str_module.into_iter().for_each(|str_modul| add_str_modul(fahrplan, str_modul));
+        }
+    }
+    for str_modul_config in &config.modules {
+        let datei = env.path_to_prejoined_zusi_path(&str_modul_config.path)
+            .and_then(|path| datei_from_prejoined_zusi_path(path, false))
+            .map_err(|error| StrModuleError::InvalidPath { error })?;
+        add_str_modul(fahrplan, StrModul::builder().datei(datei).build());
+    }
+    Ok(())
+}
+
+/// Paths of all route templates used by the routes and return routes of the given Zug configs
+fn route_template_paths(zuege: &[ZugConfig]) -> impl Iterator<Item = &PathBuf> {
+    zuege.iter().flat_map(|zug| {
+        let return_parts = zug.return_config.iter().flat_map(|return_config| return_config.route.parts.iter());
+        zug.route.parts.iter().chain(return_parts).filter_map(|part| match &part.source {
+            RoutePartSource::TrainFileByPath { path } => Some(path),
+            RoutePartSource::TrainConfigByNummer { .. } => None,
+        })
+    })
+}
+
+fn add_str_modul(fahrplan: &mut Fahrplan, str_modul: StrModul) {
+    if !fahrplan.str_module.iter().any(|existing| existing.datei.dateiname == str_modul.datei.dateiname) {
+        fahrplan.str_module.push(str_modul);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fahrplan_config::{RouteConfig, RoutePart, StrModulConfig};
+    use std::fs;
+    use tempfile::tempdir;
+
+    const ROUTE_FPN: &str = r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <Zusi>
+            <Info DateiTyp="Fahrplan" Version="A.5" MinVersion="A.1"/>
+            <Fahrplan AnfangsZeit="2024-06-20 07:30:00" trnDateien="1">
+                <BefehlsKonfiguration/>
+                <LaPDF/>
+                <StrebuPDF/>
+                <ErsatzfahrplaenePDF/>
+                <Begruessungsdatei/>
+                <StrModul>
+                    <Datei Dateiname="Routes/Deutschland/32U_0005_0058/000526_005772_Hameln/Hameln_1998.st3"/>
+                    <p/>
+                    <phi/>
+                </StrModul>
+                <StrModul>
+                    <Datei Dateiname="Routes/Deutschland/32U_0005_0058/000532_005773_Behrensen/Behrensen_2004.st3"/>
+                    <p/>
+                    <phi/>
+                </StrModul>
+                <UTM UTM_WE="0" UTM_NS="0" UTM_Zone="0" UTM_Zone2=""/>
+            </Fahrplan>
+        </Zusi>
+    "#;
+
+    const ROUTE_TRN: &str = r#"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <Zusi>
+            <Info DateiTyp="Zug" Version="A.5" MinVersion="A.1"/>
+            <Zug FahrstrName="Aufgleispunkt -&gt; Hildesheim Hbf F">
+                <Datei Dateiname="dev/route.fpn" NurInfo="1"/>
+                <FahrplanEintrag Ank="2024-06-20 08:39:00" Abf="2024-06-20 08:41:40" Signalvorlauf="180" Betrst="Elze"/>
+                <FahrzeugVarianten/>
+            </Zug>
+        </Zusi>
+    "#;
+
+    fn zuege() -> Vec<ZugConfig> {
+        let route = RouteConfig {
+            parts: vec![
+                RoutePart {
+                    source: RoutePartSource::TrainFileByPath { path: "./route.trn".into() },
+                    start_fahrzeug_verband_aktion: None,
+                    time_fix: None,
+                    apply_schedule: None,
+                },
+            ],
+            includes: vec![],
+        };
+        vec![
+            ZugConfig {
+                nummer: "20000".into(),
+                gattung: "RB".into(),
+                extends: None,
+                zuglauf: "".into(),
+                fahrplan_gruppe: "".into(),
+                meta_data: None,
+                route,
+                rolling_stock: Default::default(),
+                copy_delay_config: None,
+                takt: None,
+                return_config: None,
+                time_rounding: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_add_str_module() {
+        let tmp_dir = tempdir().unwrap();
+        let dev_dir = tmp_dir.path().join("dev");
+        fs::create_dir(&dev_dir).unwrap();
+        fs::write(dev_dir.join("route.fpn"), ROUTE_FPN).unwrap();
+        fs::write(dev_dir.join("route.trn"), ROUTE_TRN).unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: dev_dir.clone(),
+        };
+        let config = StrModuleConfig {
+            from_routes: true,
+            modules: vec![
+                StrModulConfig { path: "/Routes/Deutschland/32U_0006_0058/000551_005775_Elze/Elze_2003.st3".into() },
+                StrModulConfig { path: "/Routes/Deutschland/32U_0005_0058/000526_005772_Hameln/Hameln_1998.st3".into() },
+            ],
+        };
+
+        let mut fahrplan = read_fahrplan(dev_dir.join("route.fpn")).unwrap().value;
+        fahrplan.str_module.truncate(1);
+
+        add_str_module(&env, &config, &zuege(), &mut fahrplan).unwrap();
+
+        assert_eq!(
+            fahrplan.str_module.iter().map(|str_modul| str_modul.datei.dateiname.get().to_str().unwrap()).collect::<Vec<_>>(),
+            vec![
+                "Routes/Deutschland/32U_0005_0058/000526_005772_Hameln/Hameln_1998.st3",
+                "Routes/Deutschland/32U_0005_0058/000532_005773_Behrensen/Behrensen_2004.st3",
+                "Routes/Deutschland/32U_0006_0058/000551_005775_Elze/Elze_2003.st3",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_add_str_module_with_different_utm() {
+        let tmp_dir = tempdir().unwrap();
+        let dev_dir = tmp_dir.path().join("dev");
+        fs::create_dir(&dev_dir).unwrap();
+        fs::write(dev_dir.join("route.fpn"), ROUTE_FPN).unwrap();
+        fs::write(dev_dir.join("route.trn"), ROUTE_TRN).unwrap();
+        fs::write(dev_dir.join("template.fpn"), ROUTE_FPN.replace(r#"UTM_WE="0" UTM_NS="0" UTM_Zone="0""#, r#"UTM_WE="551" UTM_NS="5775" UTM_Zone="32""#)).unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: dev_dir.clone(),
+        };
+        let config = StrModuleConfig { from_routes: true, modules: vec![] };
+
+        let mut fahrplan = read_fahrplan(dev_dir.join("template.fpn")).unwrap().value;
+        fahrplan.str_module.clear();
+
+        assert_eq!(
+            add_str_module(&env, &config, &zuege(), &mut fahrplan),
+            Err(StrModuleError::DifferentUtm { route: "./route.trn".into() }),
+        );
+        assert!(fahrplan.str_module.is_empty());
+    }
+}
\ No newline at end of file
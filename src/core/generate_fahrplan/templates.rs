@@ -0,0 +1,262 @@
+use crate::core::lib::helpers::override_default;
+use crate::input::fahrplan_config::{FahrplanConfig, UmlaufConfig, ZugConfig, ZugTemplate};
+use serde_helpers::default::IsDefault;
+use std::mem;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("The ZugTemplate id '{id}' is used multiple times.")]
+    DuplicateTemplate {
+        id: String,
+    },
+
+    #[error("Zug with Zugnummer '{nummer}' extends the unknown ZugTemplate '{id}'.")]
+    UnknownTemplate {
+        nummer: String,
+        id: String,
+    },
+
+    #[error("Zug with Zugnummer '{nummer}' has no Gattung, neither set by itself nor by a ZugTemplate.")]
+    MissingGattung {
+        nummer: String,
+    },
+
+    #[error("Zug with Zugnummer '{nummer}' has no Route, neither set by itself nor by a ZugTemplate.")]
+    MissingRoute {
+        nummer: String,
+    },
+
+    #[error("Zug with Zugnummer '{nummer}' has no RollingStock, neither set by itself nor by a ZugTemplate.")]
+    MissingRollingStock {
+        nummer: String,
+    },
+}
+
+/// Fills all values of the Zuege which aren't set by the Zuege themselves with the values of the extended ZugTemplates.
+/// Afterward the config contains no templates anymore.
+pub fn resolve_templates(config: &mut FahrplanConfig) -> Result<(), TemplateError> {
+    match resolve_templates_collecting_errors(config).into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Like [resolve_templates], but continues after an error and returns all found errors.
+pub fn resolve_templates_collecting_errors(config: &mut FahrplanConfig) -> Vec<TemplateError> {
+    let mut errors = vec![];
+    let templates = mem::take(&mut config.templates);
+    for (index, template) in templates.iter().enumerate() {
+        if templates[..index].iter().any(|other| other.id == template.id) {
+            errors.push(TemplateError::DuplicateTemplate { id: template.id.clone() });
+        }
+    }
+    for zug in &mut config.zuege {
+        if let Some(id) = zug.extends.take() {
+            match templates.iter().find(|template| template.id == id) {
+                Some(template) => apply_template(zug, template),
+                None => {
+                    errors.push(TemplateError::UnknownTemplate { nummer: zug.nummer.clone(), id });
+                    continue;
+                },
+            }
+        }
+        if zug.gattung.is_empty() {
+            errors.push(TemplateError::MissingGattung { nummer: zug.nummer.clone() });
+        }
+        if zug.route.parts.is_empty() && zug.route.includes.is_empty() {
+            errors.push(TemplateError::MissingRoute { nummer: zug.nummer.clone() });
+        }
+        if zug.rolling_stock.is_default() && !is_following_umlauf_zug(&config.umlaeufe, &zug.nummer) {
+            errors.push(TemplateError::MissingRollingStock { nummer: zug.nummer.clone() });
+        }
+    }
+    errors
+}
+
+/// Zuege following the first one of an Umlauf get its rolling stock, so they don't need their own
+fn is_following_umlauf_zug(umlaeufe: &[UmlaufConfig], nummer: &str) -> bool {
+    umlaeufe.iter().any(|umlauf| umlauf.zuege.iter().skip(1).any(|umlauf_zug| umlauf_zug.nummer == nummer))
+}
+
+fn apply_template(zug: &mut ZugConfig, template: &ZugTemplate) {
+    override_default(&mut zug.gattung, template.gattung.clone());
+    override_default(&mut zug.meta_data, template.meta_data.clone());
+    override_default(&mut zug.route, template.route.clone());
+    override_default(&mut zug.rolling_stock, template.rolling_stock.clone());
+    override_default(&mut zug.copy_delay_config, template.copy_delay_config.clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::copy_delay_config::{CopyDelayConfig, CopyDelayTask};
+    use crate::input::fahrplan_config::{MetaDataConfig, RouteConfig, RoutePart, RoutePartSource, UmlaufZug};
+    use crate::input::rolling_stock_config::RollingStockConfig;
+    use time::Duration;
+
+    fn route(path: &str) -> RouteConfig {
+        RouteConfig {
+            parts: vec![
+                RoutePart {
+                    source: RoutePartSource::TrainFileByPath { path: path.into() },
+                    start_fahrzeug_verband_aktion: None,
+                    time_fix: None,
+                    apply_schedule: None,
+                },
+            ],
+            includes: vec![],
+        }
+    }
+
+    fn template() -> ZugTemplate {
+        ZugTemplate {
+            id: "rb".into(),
+            gattung: "RB".into(),
+            meta_data: Some(MetaDataConfig { path: "./meta-data.trn".into() }),
+            route: route("./template-route.trn"),
+            rolling_stock: RollingStockConfig { path: "./rolling-stock.trn".into() },
+            copy_delay_config: Some(CopyDelayConfig {
+                tasks: vec![
+                    CopyDelayTask {
+                        delay: Duration::hours(1),
+                        first_delay: None,
+                        increment: 2,
+                        first_increment: None,
+                        count: 4,
+                        custom_rolling_stock: None,
+                    },
+                ],
+            }),
+        }
+    }
+
+    fn zug_config(nummer: &str, extends: Option<&str>) -> ZugConfig {
+        ZugConfig {
+            nummer: nummer.into(),
+            gattung: "".into(),
+            extends: extends.map(|id| id.into()),
+            zuglauf: "".into(),
+            fahrplan_gruppe: "".into(),
+            meta_data: None,
+            route: route("./route.trn"),
+            rolling_stock: Default::default(),
+            copy_delay_config: None,
+            takt: None,
+            return_config: None,
+            time_rounding: None,
+        }
+    }
+
+    fn config(zuege: Vec<ZugConfig>) -> FahrplanConfig {
+        FahrplanConfig {
+            generate_at: "./out.fpn".into(),
+            generate_from: "./template.fpn".into(),
+            collect_errors: false,
+            zug_collisions: Default::default(),
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![template()],
+            includes: vec![],
+            zuege,
+        }
+    }
+
+    #[test]
+    fn test_resolve_templates() {
+        let mut zug = zug_config("20000", Some("rb"));
+        zug.gattung = "RE".into();
+        let mut config = config(vec![zug]);
+
+        resolve_templates(&mut config).unwrap();
+
+        assert!(config.templates.is_empty());
+        assert_eq!(
+            config.zuege,
+            vec![
+                ZugConfig {
+                    gattung: "RE".into(),
+                    extends: None,
+                    meta_data: template().meta_data,
+                    rolling_stock: template().rolling_stock,
+                    copy_delay_config: template().copy_delay_config,
+                    ..zug_config("20000", None)
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_resolve_templates_with_errors() {
+        assert_eq!(
+            resolve_templates(&mut config(vec![zug_config("20000", Some("re"))])),
+            Err(TemplateError::UnknownTemplate { nummer: "20000".into(), id: "re".into() }),
+        );
+        assert_eq!(
+            resolve_templates(&mut config(vec![zug_config("20000", None)])),
+            Err(TemplateError::MissingGattung { nummer: "20000".into() }),
+        );
+        assert_eq!(
+            resolve_templates(&mut config(vec![ZugConfig { gattung: "RB".into(), route: Default::default(), ..zug_config("20000", None) }])),
+            Err(TemplateError::MissingRoute { nummer: "20000".into() }),
+        );
+        assert_eq!(
+            resolve_templates(&mut config(vec![ZugConfig { gattung: "RB".into(), ..zug_config("20000", None) }])),
+            Err(TemplateError::MissingRollingStock { nummer: "20000".into() }),
+        );
+    }
+
+    #[test]
+    fn test_resolve_templates_route_from_template() {
+        let zug = ZugConfig { route: Default::default(), ..zug_config("20000", Some("rb")) };
+        let mut config = config(vec![zug]);
+
+        resolve_templates(&mut config).unwrap();
+
+        assert_eq!(config.zuege[0].gattung, "RB");
+        assert_eq!(config.zuege[0].route, route("./template-route.trn"));
+    }
+    #[test]
+    fn test_resolve_templates_rolling_stock_from_umlauf() {
+        let mut config = config(vec![
+            zug_config("20000", Some("rb")),
+            ZugConfig { gattung: "RB".into(), ..zug_config("20001", None) },
+        ]);
+        config.umlaeufe = vec![
+            UmlaufConfig {
+                min_turnaround_time: None,
+                zuege: vec![UmlaufZug { nummer: "20000".into() }, UmlaufZug { nummer: "20001".into() }],
+                start_fahrzeug_verband_aktion: None,
+            },
+        ];
+
+        assert_eq!(resolve_templates(&mut config), Ok(()));
+    }
+
+    #[test]
+    fn test_resolve_templates_collecting_errors() {
+        let mut config = config(vec![
+            zug_config("20000", Some("re")),
+            ZugConfig { route: Default::default(), ..zug_config("20002", None) },
+            zug_config("20004", Some("rb")),
+        ]);
+        config.templates.push(template());
+
+        assert_eq!(
+            resolve_templates_collecting_errors(&mut config),
+            vec![
+                TemplateError::DuplicateTemplate { id: "rb".into() },
+                TemplateError::UnknownTemplate { nummer: "20000".into(), id: "re".into() },
+                TemplateError::MissingGattung { nummer: "20002".into() },
+                TemplateError::MissingRoute { nummer: "20002".into() },
+                TemplateError::MissingRollingStock { nummer: "20002".into() },
+            ],
+        );
+        assert!(config.templates.is_empty());
+        assert_eq!(config.zuege[2].gattung, "RB");
+    }
+}
\ No newline at end of file
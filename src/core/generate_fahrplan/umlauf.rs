@@ -0,0 +1,400 @@
+use crate::core::generate_fahrplan::generate_zug::return_zug::apply_vehicle_turn;
+use crate::core::lib::generated_zug::{GeneratedZug, ZugOrigin};
+use crate::input::fahrplan_config::{UmlaufConfig, ZugConfig};
+use crate::input::rolling_stock_config::RollingStockConfig;
+use serde_helpers::default::IsDefault;
+use thiserror::Error;
+use time::Duration;
+use zusi_xml_lib::xml::zusi::zug::Zug;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum UmlaufError {
+    #[error("The Zug config with Zugnummer '{nummer}' of the Umlauf doesn't exist or no Zug was generated by it.")]
+    UnknownZug {
+        nummer: String,
+    },
+
+    #[error("The Zug '{nummer}' has no Ankunft or Abfahrt to connect the Umlauf with.")]
+    MissingTimes {
+        nummer: String,
+    },
+
+    #[error("The Zug '{next}' starts at '{start}', but the previous Zug '{previous}' of the Umlauf ends at '{end}'.")]
+    DifferentBetriebsstelle {
+        previous: String,
+        next: String,
+        end: String,
+        start: String,
+    },
+
+    #[error("The Zug '{next}' departs {turnaround} after the Ankunft of the previous Zug '{previous}' of the Umlauf, but at least {min_turnaround} are required.")]
+    TurnaroundTooShort {
+        previous: String,
+        next: String,
+        turnaround: Duration,
+        min_turnaround: Duration,
+    },
+
+    #[error("The Zug '{nummer}' has no counterpart created by the same CopyDelayTask and copy in the Zug config '{config_nummer}' of the Umlauf.")]
+    MissingCounterpart {
+        nummer: String,
+        config_nummer: String,
+    },
+
+    #[error("The Zug config '{nummer}' sets a different RollingStock than the first Zug config '{first_nummer}' of the Umlauf, whose RollingStock is used for the whole Umlauf.")]
+    ConflictingRollingStock {
+        nummer: String,
+        first_nummer: String,
+    },
+}
+
+/// Uses the rolling stock of the first Zug config of every Umlauf for all following Zug configs, which therefore don't need to set one.
+/// The custom rolling stock of each CopyDelayTask is carried to the task at the same position, as its copies are connected to each other.
+/// Following Zug configs setting a different rolling stock are reported and kept unchanged instead of discarding their config.
+pub fn carry_rolling_stock(zug_configs: &mut [ZugConfig], umlaeufe: &[UmlaufConfig]) -> Vec<UmlaufError> {
+    let mut errors = vec![];
+    umlaeufe.iter().for_each(|umlauf| {
+        let Some(first_nummer) = umlauf.zuege.first().map(|first| &first.nummer) else {
+            return;
+        };
+        let first = zug_configs
+            .iter()
+            .find(|zug_config| &zug_config.nummer == first_nummer)
+            .map(|zug_config| (zug_config.rolling_stock.clone(), custom_rolling_stock(zug_config)));
+        if let Some((rolling_stock, first_custom_rolling_stock)) = first {
+            zug_configs
+                .iter_mut()
+                .filter(|zug_config| &zug_config.nummer != first_nummer)
+                .filter(|zug_config| umlauf.zuege.iter().any(|umlauf_zug| umlauf_zug.nummer == zug_config.nummer))
+                .for_each(|zug_config| {
+                    let carried_custom_rolling_stock = |index: usize| first_custom_rolling_stock.get(index).cloned().flatten();
+                    let conflicting = (!zug_config.rolling_stock.is_default() && zug_config.rolling_stock != rolling_stock)
+                        || custom_rolling_stock(zug_config)
+                            .into_iter()
+                            .enumerate()
+                            .any(|(index, custom)| custom.is_some() && custom != carried_custom_rolling_stock(index));
+                    if conflicting {
+                        errors.push(UmlaufError::ConflictingRollingStock { nummer: zug_config.nummer.clone(), first_nummer: first_nummer.clone() });
+                        return;
+                    }
+                    zug_config.rolling_stock = rolling_stock.clone();
+                    if let Some(copy_delay_config) = &mut zug_config.copy_delay_config {
+                        copy_delay_config.tasks
+                            .iter_mut()
+                            .enumerate()
+                            .for_each(|(index, task)| task.custom_rolling_stock = carried_custom_rolling_stock(index));
+                    }
+                });
+        }
+    });
+    errors
+}
+
+fn custom_rolling_stock(zug_config: &ZugConfig) -> Vec<Option<RollingStockConfig>> {
+    zug_config.copy_delay_config
+        .iter()
+        .flat_map(|copy_delay_config| copy_delay_config.tasks.iter())
+        .map(|task| task.custom_rolling_stock.clone())
+        .collect()
+}
+
+/// Checks that every Zug of an Umlauf departs where and after the previous one arrived.
+/// Copies of the Zug configs are connected to the copy created by the same CopyDelayTask, copies without such a counterpart are reported.
+/// If the Umlauf has a vehicle turn, it is set at the end of every connected Zug except the last one.
+pub fn check_umlaeufe(zuege: &mut [GeneratedZug], umlaeufe: &[UmlaufConfig]) -> Vec<UmlaufError> {
+    umlaeufe
+        .iter()
+        .flat_map(|umlauf| check_umlauf(zuege, umlauf))
+        .collect()
+}
+
+fn check_umlauf(zuege: &mut [GeneratedZug], umlauf: &UmlaufConfig) -> Vec<UmlaufError> {
+    let mut errors = vec![];
+
+    // indices of all Zuege generated by each Zug config of the Umlauf, keyed by their copy
+    let chain: Vec<Vec<(Option<(usize, u32)>, usize)>> = umlauf.zuege
+        .iter()
+        .map(|umlauf_zug| {
+            let generated: Vec<_> = zuege
+                .iter()
+                .enumerate()
+                .filter(|(_, zug)| zug.origin.config_nummer == umlauf_zug.nummer)
+                .map(|(index, zug)| (copy_key(&zug.origin), index))
+                .collect();
+            if generated.is_empty() {
+                errors.push(UmlaufError::UnknownZug { nummer: umlauf_zug.nummer.clone() });
+            }
+            generated
+        })
+        .collect();
+
+    let min_turnaround = umlauf.min_turnaround_time.unwrap_or(Duration::ZERO);
+    chain.windows(2).zip(umlauf.zuege.windows(2)).for_each(|(pair, umlauf_zuege)| {
+        // Zug configs without any generated Zug are already reported as unknown
+        if pair[0].is_empty() || pair[1].is_empty() {
+            return;
+        }
+        pair[1]
+            .iter()
+            .filter(|(key, _)| !pair[0].iter().any(|(previous_key, _)| previous_key == key))
+            .for_each(|(_, next)| errors.push(UmlaufError::MissingCounterpart {
+                nummer: zuege[*next].zug.value.nummer.clone(),
+                config_nummer: umlauf_zuege[0].nummer.clone(),
+            }));
+        pair[0].iter().for_each(|(key, previous)| {
+            let Some((_, next)) = pair[1].iter().find(|(next_key, _)| next_key == key) else {
+                errors.push(UmlaufError::MissingCounterpart {
+                    nummer: zuege[*previous].zug.value.nummer.clone(),
+                    config_nummer: umlauf_zuege[1].nummer.clone(),
+                });
+                return;
+            };
+            if let Err(error) = check_connection(&zuege[*previous].zug.value, &zuege[*next].zug.value, min_turnaround) {
+                errors.push(error);
+            }
+            if let Some(aktion) = &umlauf.start_fahrzeug_verband_aktion {
+                apply_vehicle_turn(&mut zuege[*previous].zug.value, aktion);
+            }
+        });
+    });
+
+    errors
+}
+
+fn copy_key(origin: &ZugOrigin) -> Option<(usize, u32)> {
+    origin.copy_delay.as_ref().map(|copy_delay| (copy_delay.task, copy_delay.copy))
+}
+
+fn check_connection(previous: &Zug, next: &Zug, min_turnaround: Duration) -> Result<(), UmlaufError> {
+    let (end, ankunft) = previous.fahrplan_eintraege
+        .iter()
+        .rev()
+        .find_map(|eintrag| eintrag.ankunft.or(eintrag.abfahrt).map(|ankunft| (eintrag, ankunft)))
+        .ok_or_else(|| UmlaufError::MissingTimes { nummer: previous.nummer.clone() })?;
+    let (start, abfahrt) = next.fahrplan_eintraege
+        .iter()
+        .find_map(|eintrag| eintrag.abfahrt.map(|abfahrt| (eintrag, abfahrt)))
+        .ok_or_else(|| UmlaufError::MissingTimes { nummer: next.nummer.clone() })?;
+
+    if end.betriebsstelle != start.betriebsstelle {
+        Err(UmlaufError::DifferentBetriebsstelle {
+            previous: previous.nummer.clone(),
+            next: next.nummer.clone(),
+            end: end.betriebsstelle.clone(),
+            start: start.betriebsstelle.clone(),
+        })
+    } else if abfahrt - ankunft < min_turnaround {
+        Err(UmlaufError::TurnaroundTooShort {
+            previous: previous.nummer.clone(),
+            next: next.nummer.clone(),
+            turnaround: abfahrt - ankunft,
+            min_turnaround,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::lib::generated_zug::{CopyDelayOrigin, RawGeneratedZug};
+    use crate::input::copy_delay_config::{CopyDelayConfig, CopyDelayTask};
+    use crate::input::fahrplan_config::non_default_fahrzeug_verband_aktion::NonDefaultFahrzeugVerbandAktion;
+    use crate::input::fahrplan_config::{RouteConfig, StartFahrzeugVerbandAktion, UmlaufZug};
+    use std::path::PathBuf;
+    use time::macros::datetime;
+    use time::PrimitiveDateTime;
+    use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+    use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::fahrzeug_verband_aktion::FahrzeugVerbandAktion;
+    use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
+    use zusi_xml_lib::xml::zusi::zug::fahrzeug_varianten::FahrzeugVarianten;
+
+    fn generated_zug(nummer: &str, config_nummer: &str, copy: Option<u32>, start: (&str, PrimitiveDateTime), end: (&str, PrimitiveDateTime)) -> GeneratedZug {
+        let raw = RawGeneratedZug {
+            zug: Zug::builder()
+                .nummer(nummer.into())
+                .fahrplan_datei(Datei::builder().build())
+                .fahrzeug_varianten(FahrzeugVarianten::builder().build())
+                .fahrplan_eintraege(vec![
+                    FahrplanEintrag::builder().betriebsstelle(start.0.into()).abfahrt(Some(start.1)).build(),
+                    FahrplanEintrag::builder().betriebsstelle(end.0.into()).ankunft(Some(end.1)).build(),
+                ])
+                .build(),
+            buchfahrplan: None,
+        };
+        let origin = ZugOrigin {
+            config_nummer: config_nummer.into(),
+            copy_delay: copy.map(|copy| CopyDelayOrigin {
+                task: 1,
+                copy,
+                delay: Duration::hours(copy as i64),
+                increment: copy as i32 * 2,
+            }),
+            rolling_stock: "./rolling-stock.trn".into(),
+        };
+        (raw, origin).into()
+    }
+
+    fn umlauf(nummern: &[&str]) -> UmlaufConfig {
+        UmlaufConfig {
+            min_turnaround_time: Some(Duration::minutes(5)),
+            zuege: nummern.iter().map(|nummer| UmlaufZug { nummer: (*nummer).into() }).collect(),
+            start_fahrzeug_verband_aktion: Some(StartFahrzeugVerbandAktion {
+                aktion: NonDefaultFahrzeugVerbandAktion::ZugDrehen,
+                wende_signal: false,
+                wende_signal_abstand: 0.,
+            }),
+        }
+    }
+
+    fn zug_config(nummer: &str, rolling_stock: &str) -> ZugConfig {
+        ZugConfig {
+            nummer: nummer.into(),
+            gattung: "RB".into(),
+            extends: None,
+            zuglauf: "".into(),
+            fahrplan_gruppe: "".into(),
+            meta_data: None,
+            route: RouteConfig { parts: vec![], includes: vec![] },
+            rolling_stock: RollingStockConfig { path: rolling_stock.into() },
+            copy_delay_config: None,
+            takt: None,
+            return_config: None,
+            time_rounding: None,
+        }
+    }
+
+    #[test]
+    fn test_carry_rolling_stock() {
+        let mut zug_configs = vec![
+            zug_config("10000", ""),
+            zug_config("20000", "b.trn"),
+            zug_config("30000", "c.trn"),
+        ];
+
+        assert_eq!(carry_rolling_stock(&mut zug_configs, &[umlauf(&["20000", "10000"])]), vec![]);
+
+        assert_eq!(zug_configs[0].rolling_stock.path, PathBuf::from("b.trn"));
+        assert_eq!(zug_configs[1].rolling_stock.path, PathBuf::from("b.trn"));
+        assert_eq!(zug_configs[2].rolling_stock.path, PathBuf::from("c.trn"));
+    }
+
+    #[test]
+    fn test_carry_rolling_stock_with_conflicting_rolling_stock() {
+        let mut zug_configs = vec![
+            zug_config("10000", "a.trn"),
+            zug_config("20000", "b.trn"),
+            zug_config("30000", "b.trn"),
+        ];
+
+        assert_eq!(
+            carry_rolling_stock(&mut zug_configs, &[umlauf(&["20000", "10000", "30000"])]),
+            vec![UmlaufError::ConflictingRollingStock { nummer: "10000".into(), first_nummer: "20000".into() }],
+        );
+
+        assert_eq!(zug_configs[0].rolling_stock.path, PathBuf::from("a.trn"));
+        assert_eq!(zug_configs[2].rolling_stock.path, PathBuf::from("b.trn"));
+    }
+
+    fn task(custom_rolling_stock: Option<&str>) -> CopyDelayTask {
+        CopyDelayTask {
+            delay: Duration::hours(1),
+            first_delay: None,
+            increment: 2,
+            first_increment: None,
+            count: 1,
+            custom_rolling_stock: custom_rolling_stock.map(|path| RollingStockConfig { path: path.into() }),
+        }
+    }
+
+    #[test]
+    fn test_carry_custom_rolling_stock() {
+        let mut first = zug_config("10000", "a.trn");
+        first.copy_delay_config = Some(CopyDelayConfig { tasks: vec![task(Some("a-custom.trn")), task(None)] });
+        let mut next = zug_config("10001", "");
+        next.copy_delay_config = Some(CopyDelayConfig { tasks: vec![task(None), task(None)] });
+        let mut zug_configs = vec![first, next];
+
+        assert_eq!(carry_rolling_stock(&mut zug_configs, &[umlauf(&["10000", "10001"])]), vec![]);
+
+        assert_eq!(zug_configs[1].rolling_stock, zug_configs[0].rolling_stock);
+        assert_eq!(zug_configs[1].copy_delay_config, zug_configs[0].copy_delay_config);
+    }
+
+    #[test]
+    fn test_carry_custom_rolling_stock_with_conflicting_rolling_stock() {
+        let mut first = zug_config("10000", "a.trn");
+        first.copy_delay_config = Some(CopyDelayConfig { tasks: vec![task(Some("a-custom.trn")), task(None)] });
+        let mut next = zug_config("10001", "a.trn");
+        next.copy_delay_config = Some(CopyDelayConfig { tasks: vec![task(None), task(Some("b-custom.trn"))] });
+        let mut zug_configs = vec![first, next];
+
+        assert_eq!(
+            carry_rolling_stock(&mut zug_configs, &[umlauf(&["10000", "10001"])]),
+            vec![UmlaufError::ConflictingRollingStock { nummer: "10001".into(), first_nummer: "10000".into() }],
+        );
+        assert_eq!(zug_configs[1].copy_delay_config.as_ref().unwrap().tasks[1], task(Some("b-custom.trn")));
+    }
+
+    #[test]
+    fn test_check_umlaeufe() {
+        let mut zuege = vec![
+            generated_zug("10000", "10000", None, ("A", datetime!(2024-06-20 08:00:00)), ("B", datetime!(2024-06-20 08:30:00))),
+            generated_zug("10002", "10000", Some(1), ("A", datetime!(2024-06-20 09:00:00)), ("B", datetime!(2024-06-20 09:30:00))),
+            generated_zug("10001", "10001", None, ("B", datetime!(2024-06-20 08:40:00)), ("A", datetime!(2024-06-20 09:10:00))),
+            generated_zug("10003", "10001", Some(1), ("B", datetime!(2024-06-20 09:40:00)), ("A", datetime!(2024-06-20 10:10:00))),
+        ];
+
+        assert_eq!(check_umlaeufe(&mut zuege, &[umlauf(&["10000", "10001"])]), vec![]);
+        assert_eq!(zuege[0].zug.value.fahrplan_eintraege[1].fahrzeug_verband_aktion, FahrzeugVerbandAktion::ZugDrehen);
+        assert_eq!(zuege[1].zug.value.fahrplan_eintraege[1].fahrzeug_verband_aktion, FahrzeugVerbandAktion::ZugDrehen);
+        assert_eq!(zuege[2].zug.value.fahrplan_eintraege[1].fahrzeug_verband_aktion, FahrzeugVerbandAktion::default());
+    }
+
+    #[test]
+    fn test_check_umlaeufe_with_errors() {
+        let mut zuege = vec![
+            generated_zug("10000", "10000", None, ("A", datetime!(2024-06-20 08:00:00)), ("B", datetime!(2024-06-20 08:30:00))),
+            generated_zug("10001", "10001", None, ("B", datetime!(2024-06-20 08:33:00)), ("A", datetime!(2024-06-20 09:10:00))),
+            generated_zug("10003", "10003", None, ("C", datetime!(2024-06-20 09:40:00)), ("A", datetime!(2024-06-20 10:10:00))),
+        ];
+
+        assert_eq!(
+            check_umlaeufe(&mut zuege, &[umlauf(&["10000", "10001", "10003", "10005"])]),
+            vec![
+                UmlaufError::UnknownZug { nummer: "10005".into() },
+                UmlaufError::TurnaroundTooShort {
+                    previous: "10000".into(),
+                    next: "10001".into(),
+                    turnaround: Duration::minutes(3),
+                    min_turnaround: Duration::minutes(5),
+                },
+                UmlaufError::DifferentBetriebsstelle {
+                    previous: "10001".into(),
+                    next: "10003".into(),
+                    end: "A".into(),
+                    start: "C".into(),
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_check_umlaeufe_with_missing_counterparts() {
+        let mut zuege = vec![
+            generated_zug("10000", "10000", None, ("A", datetime!(2024-06-20 08:00:00)), ("B", datetime!(2024-06-20 08:30:00))),
+            generated_zug("10002", "10000", Some(1), ("A", datetime!(2024-06-20 09:00:00)), ("B", datetime!(2024-06-20 09:30:00))),
+            generated_zug("10001", "10001", None, ("B", datetime!(2024-06-20 08:40:00)), ("A", datetime!(2024-06-20 09:10:00))),
+            generated_zug("10005", "10001", Some(2), ("B", datetime!(2024-06-20 10:40:00)), ("A", datetime!(2024-06-20 11:10:00))),
+        ];
+
+        assert_eq!(
+            check_umlaeufe(&mut zuege, &[umlauf(&["10000", "10001"])]),
+            vec![
+                UmlaufError::MissingCounterpart { nummer: "10005".into(), config_nummer: "10000".into() },
+                UmlaufError::MissingCounterpart { nummer: "10002".into(), config_nummer: "10001".into() },
+            ],
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,432 @@
+use crate::core::generate_fahrplan::fahrplan_header::{validate_fahrplan_header, FahrplanHeaderError};
+use crate::core::generate_fahrplan::generate_zug::generate_route::resolved_route::ResolvedRoutes;
+use crate::core::generate_fahrplan::generate_zug::generate_route::{validate_route, ValidateRouteError};
+use crate::core::generate_fahrplan::generate_zug::return_zug::{return_zug_nummer, ReturnZugError};
+use crate::core::generate_fahrplan::includes::{resolve_includes, IncludeError};
+use crate::core::generate_fahrplan::route_dependencies::{sort_by_route_dependencies, RouteDependencyError};
+use crate::core::generate_fahrplan::str_module::{add_str_module, StrModuleError};
+use crate::core::generate_fahrplan::templates::{resolve_templates_collecting_errors, TemplateError};
+use crate::core::generate_fahrplan::umlauf::{carry_rolling_stock, UmlaufError};
+use crate::core::lib::copy_delay::{validate_copy_delay, CopyDelayError};
+use crate::core::lib::file_error::FileError;
+use crate::core::lib::takt::{validate_takt, TaktError};
+use crate::core::lib::helpers::{read_fahrplan, read_zug};
+use crate::core::replace_rolling_stock::{validate_rolling_stock, ReplaceRollingStockError};
+use crate::input::environment::zusi_environment::ZusiEnvironment;
+use crate::input::fahrplan_config::{FahrplanConfig, ZugConfig};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValidateFahrplanError {
+    #[error("The given Fahrplan template couldn't be read: {error}")]
+    ReadFahrplanTemplateError {
+        error: FileError,
+    },
+
+    #[error("The path of the generated Fahrplan is invalid: {error}")]
+    InvalidGenerateAtPath {
+        error: FileError,
+    },
+
+    #[error("The Fahrplan header is invalid: {error}")]
+    FahrplanHeaderError {
+        error: FahrplanHeaderError,
+    },
+
+    #[error("The Streckenmodule couldn't be added: {error}")]
+    StrModuleError {
+        error: StrModuleError,
+    },
+
+    #[error("The included configs couldn't be resolved: {error}")]
+    IncludeError {
+        error: IncludeError,
+    },
+
+    #[error("The ZugTemplates couldn't be applied: {error}")]
+    TemplateError {
+        error: TemplateError,
+    },
+
+    #[error("The route dependencies between the Zuege couldn't be resolved: {error}")]
+    RouteDependencyError {
+        error: RouteDependencyError,
+    },
+
+    #[error("Zug with Zugnummer '{zug_nummer}': {error}")]
+    ValidateZugError {
+        zug_nummer: String,
+        error: ValidateZugError,
+    },
+
+    #[error("The Umlauf is invalid: {error}")]
+    UmlaufError {
+        error: UmlaufError,
+    },
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValidateZugError {
+    #[error("The route is invalid: {error}")]
+    RouteError {
+        #[from]
+        error: ValidateRouteError,
+    },
+
+    #[error("Couldn't read the meta data template file: {error}")]
+    ReadMetaDataError {
+        #[from]
+        error: FileError,
+    },
+
+    #[error("The rolling stock is invalid: {error}")]
+    RollingStockError {
+        #[from]
+        error: ReplaceRollingStockError,
+    },
+
+    #[error("The copy delay config is invalid: {error}")]
+    CopyDelayError {
+        #[from]
+        error: CopyDelayError,
+    },
+
+    #[error("The Takt is invalid: {error}")]
+    TaktError {
+        #[from]
+        error: TaktError,
+    },
+
+    #[error("The return route is invalid: {error}")]
+    ReturnRouteError {
+        error: ValidateRouteError,
+    },
+
+    #[error("The return Zug is invalid: {error}")]
+    ReturnZugError {
+        #[from]
+        error: ReturnZugError,
+    },
+}
+
+/// Checks all files referenced by the config and tries to resolve and merge all routes without writing any files.
+/// In contrast to the generation all found problems are returned instead of only the first one.
+pub fn validate_fahrplan(env: &ZusiEnvironment, mut config: FahrplanConfig) -> Vec<ValidateFahrplanError> {
+    let mut errors = vec![];
+
+    if let Err(error) = resolve_includes(env, &mut config) {
+        errors.push(ValidateFahrplanError::IncludeError { error });
+    }
+    errors.extend(
+        resolve_templates_collecting_errors(&mut config)
+            .into_iter()
+            .map(|error| ValidateFahrplanError::TemplateError { error })
+    );
+
+    match env.path_to_prejoined_zusi_path(&config.generate_from)
+        .and_then(|generate_from| read_fahrplan(generate_from.full_path())) {
+        Ok(mut fahrplan) => if let Some(str_module) = &config.str_module {
+            if let Err(error) = add_str_module(env, str_module, &config.zuege, &mut fahrplan.value) {
+                errors.push(ValidateFahrplanError::StrModuleError { error });
+            }
+        },
+        Err(error) => errors.push(ValidateFahrplanError::ReadFahrplanTemplateError { error }),
+    }
+    if let Err(error) = env.path_to_prejoined_zusi_path(&config.generate_at) {
+        errors.push(ValidateFahrplanError::InvalidGenerateAtPath { error });
+    }
+    if let Some(header) = &config.header {
+        errors.extend(
+            validate_fahrplan_header(env, header)
+                .into_iter()
+                .map(|error| ValidateFahrplanError::FahrplanHeaderError { error })
+        );
+    }
+
+    // Zugnummern of all Zug configs and their return Zuege, which can be part of an Umlauf
+    let zug_nummern: Vec<String> = config.zuege
+        .iter()
+        .flat_map(|zug_config| {
+            let return_nummer = zug_config.return_config
+                .as_ref()
+                .and_then(|return_config| return_zug_nummer(&zug_config.nummer, return_config).ok());
+            [Some(zug_config.nummer.clone()), return_nummer].into_iter().flatten()
+        })
+        .collect();
+
+    let mut zug_configs = match sort_by_route_dependencies(config.zuege.clone()) {
+        Ok(zug_configs) => zug_configs,
+        Err(error) => {
+            errors.push(ValidateFahrplanError::RouteDependencyError { error });
+            config.zuege
+        },
+    };
+    errors.extend(
+        carry_rolling_stock(&mut zug_configs, &config.umlaeufe)
+            .into_iter()
+            .map(|error| ValidateFahrplanError::UmlaufError { error })
+    );
+    let mut resolved_routes = ResolvedRoutes::new();
+
+    zug_configs.into_iter().for_each(|zug_config| {
+        let zug_nummer = zug_config.nummer.clone();
+        errors.extend(
+            validate_zug(env, zug_config, &mut resolved_routes)
+                .into_iter()
+                .map(|error| ValidateFahrplanError::ValidateZugError { zug_nummer: zug_nummer.clone(), error })
+        );
+    });
+
+    config.umlaeufe
+        .iter()
+        .flat_map(|umlauf| umlauf.zuege.iter())
+        .filter(|umlauf_zug| !zug_nummern.contains(&umlauf_zug.nummer))
+        .for_each(|umlauf_zug| errors.push(ValidateFahrplanError::UmlaufError {
+            error: UmlaufError::UnknownZug { nummer: umlauf_zug.nummer.clone() },
+        }));
+
+    errors
+}
+
+fn validate_zug(env: &ZusiEnvironment, zug_config: ZugConfig, resolved_routes: &mut ResolvedRoutes) -> Vec<ValidateZugError> {
+    let mut errors: Vec<ValidateZugError> = vec![];
+
+    match validate_route(env, zug_config.route, resolved_routes) {
+        Ok(route) => {
+            resolved_routes.insert(zug_config.nummer.clone(), route);
+        },
+        Err(route_errors) => errors.extend(route_errors.into_iter().map(ValidateZugError::from)),
+    }
+
+    if let Some(meta_data) = zug_config.meta_data {
+        if let Err(error) = env.path_to_prejoined_zusi_path(meta_data.path)
+            .and_then(|path| read_zug(path.full_path())) {
+            errors.push(error.into());
+        }
+    }
+
+    if let Err(error) = validate_rolling_stock(env, &zug_config.rolling_stock) {
+        errors.push(error.into());
+    }
+
+    if let Some(copy_delay_config) = &zug_config.copy_delay_config {
+        errors.extend(
+            validate_copy_delay(env, copy_delay_config, &zug_config.nummer)
+                .into_iter()
+                .map(ValidateZugError::from)
+        );
+    }
+
+    if let Some(return_config) = zug_config.return_config {
+        let return_zug_nummer = return_zug_nummer(&zug_config.nummer, &return_config)
+            .map_err(|error| errors.push(error.into()))
+            .ok();
+        match validate_route(env, return_config.route, resolved_routes) {
+            Ok(route) => {
+                if let Some(return_zug_nummer) = return_zug_nummer {
+                    resolved_routes.insert(return_zug_nummer, route);
+                }
+            },
+            Err(route_errors) => errors.extend(route_errors.into_iter().map(|error| ValidateZugError::ReturnRouteError { error })),
+        }
+    }
+
+    if let Some(takt) = zug_config.takt {
+        if zug_config.copy_delay_config.is_some() {
+            errors.push(TaktError::CombinedWithCopyDelay.into());
+        }
+        if let Err(error) = validate_takt(&takt) {
+            errors.push(error.into());
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::generate_fahrplan::generate_zug::generate_route::generate_route_part::{GenerateRoutePartError, RouteSourceError};
+    use crate::core::generate_fahrplan::generate_zug::generate_route::GenerateRouteError;
+    use crate::input::copy_delay_config::{CopyDelayConfig, CopyDelayTask};
+    use crate::input::fahrplan_config::{MetaDataConfig, RouteConfig, RoutePart, RoutePartSource, UmlaufConfig, UmlaufZug, ZugCollisionPolicy};
+    use crate::input::rolling_stock_config::RollingStockConfig;
+    use tempfile::tempdir;
+    use time::Duration;
+
+    fn route_part(source: RoutePartSource) -> RoutePart {
+        RoutePart {
+            source,
+            start_fahrzeug_verband_aktion: None,
+            time_fix: None,
+            apply_schedule: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_fahrplan_reports_all_errors() {
+        let tmp_dir = tempdir().unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().to_owned(),
+        };
+
+        let config = FahrplanConfig {
+            generate_at: "generated.fpn".into(),
+            generate_from: "non-existent.fpn".into(),
+            collect_errors: false,
+            zug_collisions: ZugCollisionPolicy::Warn,
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![
+                UmlaufConfig {
+                    min_turnaround_time: None,
+                    zuege: vec![UmlaufZug { nummer: "20000".into() }, UmlaufZug { nummer: "30000".into() }],
+                    start_fahrzeug_verband_aktion: None,
+                },
+            ],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![],
+            includes: vec![],
+            zuege: vec![
+                ZugConfig {
+                    nummer: "invalid".into(),
+                    gattung: "RB".into(),
+                    extends: None,
+                    zuglauf: "".into(),
+                    fahrplan_gruppe: "".into(),
+                    meta_data: Some(MetaDataConfig { path: "non-existent-meta-data.trn".into() }),
+                    route: RouteConfig {
+                        parts: vec![route_part(RoutePartSource::TrainFileByPath { path: "non-existent-route.trn".into() })],
+                        includes: vec![],
+                    },
+                    rolling_stock: RollingStockConfig { path: "non-existent-rolling-stock.trn".into() },
+                    copy_delay_config: Some(CopyDelayConfig {
+                        tasks: vec![
+                            CopyDelayTask {
+                                delay: Duration::hours(1),
+                                first_delay: None,
+                                increment: 2,
+                                first_increment: None,
+                                count: 1,
+                                custom_rolling_stock: None,
+                            },
+                        ],
+                    }),
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
+                },
+                ZugConfig {
+                    nummer: "20000".into(),
+                    gattung: "RB".into(),
+                    extends: None,
+                    zuglauf: "".into(),
+                    fahrplan_gruppe: "".into(),
+                    meta_data: None,
+                    route: RouteConfig {
+                        parts: vec![route_part(RoutePartSource::TrainConfigByNummer { nummer: "invalid".into() })],
+                        includes: vec![],
+                    },
+                    rolling_stock: RollingStockConfig { path: "non-existent-rolling-stock.trn".into() },
+                    copy_delay_config: None,
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
+                },
+            ],
+        };
+
+        let errors = validate_fahrplan(&env, config);
+
+        assert_eq!(errors.len(), 8);
+        assert!(matches!(errors[0], ValidateFahrplanError::ReadFahrplanTemplateError { .. }));
+        assert!(matches!(
+            &errors[1],
+            ValidateFahrplanError::ValidateZugError {
+                zug_nummer,
+                error: ValidateZugError::RouteError {
+                    error: ValidateRouteError::RoutePartError {
+                        part: 1,
+                        error: GenerateRouteError::GenerateRoutePartError { error: GenerateRoutePartError::ReadRouteError { .. }, .. },
+                    },
+                },
+            } if zug_nummer == "invalid",
+        ));
+        assert!(matches!(&errors[2], ValidateFahrplanError::ValidateZugError { error: ValidateZugError::ReadMetaDataError { .. }, .. }));
+        assert!(matches!(&errors[3], ValidateFahrplanError::ValidateZugError { error: ValidateZugError::RollingStockError { .. }, .. }));
+        assert!(matches!(
+            &errors[4],
+            ValidateFahrplanError::ValidateZugError { error: ValidateZugError::CopyDelayError { error: CopyDelayError::InvalidZugNummer { .. } }, .. },
+        ));
+        assert!(matches!(
+            &errors[5],
+            ValidateFahrplanError::ValidateZugError {
+                zug_nummer,
+                error: ValidateZugError::RouteError {
+                    error: ValidateRouteError::RoutePartError {
+                        part: 1,
+                        error: GenerateRouteError::GenerateRoutePartError {
+                            error: GenerateRoutePartError::ReadRouteError { error: RouteSourceError::UnresolvedZugNummer { .. } },
+                            ..
+                        },
+                    },
+                },
+            } if zug_nummer == "20000",
+        ));
+        assert!(matches!(&errors[6], ValidateFahrplanError::ValidateZugError { error: ValidateZugError::RollingStockError { .. }, .. }));
+        assert_eq!(errors[7], ValidateFahrplanError::UmlaufError { error: UmlaufError::UnknownZug { nummer: "30000".into() } });
+    }
+    #[test]
+    fn test_validate_fahrplan_reports_all_template_errors() {
+        let tmp_dir = tempdir().unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().to_owned(),
+        };
+
+        let zug_config = |nummer: &str, extends: &str| ZugConfig {
+            nummer: nummer.into(),
+            gattung: "RB".into(),
+            extends: Some(extends.into()),
+            zuglauf: "".into(),
+            fahrplan_gruppe: "".into(),
+            meta_data: None,
+            route: Default::default(),
+            rolling_stock: Default::default(),
+            copy_delay_config: None,
+            takt: None,
+            return_config: None,
+            time_rounding: None,
+        };
+        let config = FahrplanConfig {
+            generate_at: "generated.fpn".into(),
+            generate_from: "non-existent.fpn".into(),
+            collect_errors: false,
+            zug_collisions: ZugCollisionPolicy::Warn,
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![],
+            includes: vec![],
+            zuege: vec![zug_config("20000", "rb"), zug_config("30000", "re")],
+        };
+
+        let errors = validate_fahrplan(&env, config);
+
+        assert_eq!(
+            errors.into_iter().filter(|error| matches!(error, ValidateFahrplanError::TemplateError { .. })).collect::<Vec<_>>(),
+            vec![
+                ValidateFahrplanError::TemplateError { error: TemplateError::UnknownTemplate { nummer: "20000".into(), id: "rb".into() } },
+                ValidateFahrplanError::TemplateError { error: TemplateError::UnknownTemplate { nummer: "30000".into(), id: "re".into() } },
+            ],
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,231 @@
+use crate::core::lib::generated_zug::{GeneratedZug, ZugOrigin};
+use crate::core::lib::zug_nummer::ZugNummer;
+use crate::input::fahrplan_config::ZugCollisionPolicy;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::num::ParseIntError;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ZugCollisionError {
+    #[error("Multiple Zuege would be written to the same file:{}", collisions.iter().map(|collision| format!("\n- {collision}")).collect::<String>())]
+    Collisions {
+        collisions: Vec<ZugCollision>,
+    },
+
+    #[error("The Zug with Zugnummer '{nummer}' couldn't be renumbered as the Zugnummer is invalid: {error}")]
+    InvalidZugNummer {
+        nummer: String,
+        error: ParseIntError,
+    },
+}
+
+/// Multiple generated Zuege with the same Gattung and Zugnummer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZugCollision {
+    pub gattung: String,
+    pub nummer: String,
+
+    /// Origins of all colliding Zuege in the order they were generated
+    pub origins: Vec<ZugOrigin>,
+
+    /// New Zugnummern of all but the first colliding Zug, only set if they were renumbered
+    pub renumbered_to: Vec<String>,
+}
+
+impl Display for ZugCollision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{} was generated by: ", self.gattung, self.nummer)?;
+        let origins: Vec<String> = self.origins.iter().map(|origin| origin.to_string()).collect();
+        write!(f, "{}", origins.join(", "))?;
+        if !self.renumbered_to.is_empty() {
+            write!(f, "; renumbered to {}, Umlaeufe and route parts still reference {}", self.renumbered_to.join(", "), self.nummer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds all generated Zuege which share the same Gattung and Zugnummer and handles them according to the policy.
+/// Returns all found collisions unless the policy requires to fail.
+pub fn check_zug_collisions(zuege: &mut [GeneratedZug], policy: ZugCollisionPolicy) -> Result<Vec<ZugCollision>, ZugCollisionError> {
+    let collisions = find_zug_collisions(zuege);
+    match policy {
+        ZugCollisionPolicy::Warn => Ok(collisions.into_iter().map(|(collision, _)| collision).collect()),
+        ZugCollisionPolicy::Fail if collisions.is_empty() => Ok(vec![]),
+        ZugCollisionPolicy::Fail => Err(ZugCollisionError::Collisions {
+            collisions: collisions.into_iter().map(|(collision, _)| collision).collect(),
+        }),
+        ZugCollisionPolicy::Renumber => renumber_zug_collisions(zuege, collisions),
+    }
+}
+
+fn find_zug_collisions(zuege: &[GeneratedZug]) -> Vec<(ZugCollision, Vec<usize>)> {
+    let mut indices: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+    let mut keys = vec![];
+    zuege.iter().enumerate().for_each(|(index, zug)| {
+        let key = (zug.zug.value.gattung.as_str(), zug.zug.value.nummer.as_str());
+        let entry = indices.entry(key).or_default();
+        if entry.is_empty() {
+            keys.push(key);
+        }
+        entry.push(index);
+    });
+
+    keys
+        .into_iter()
+        .filter_map(|key| {
+            let colliding = indices.remove(&key).unwrap(); // every key was inserted before
+            (colliding.len() > 1).then(|| (
+                ZugCollision {
+                    gattung: key.0.into(),
+                    nummer: key.1.into(),
+                    origins: colliding.iter().map(|index| zuege[*index].origin.clone()).collect(),
+                    renumbered_to: vec![],
+                },
+                colliding,
+            ))
+        })
+        .collect()
+}
+
+fn renumber_zug_collisions(zuege: &mut [GeneratedZug], collisions: Vec<(ZugCollision, Vec<usize>)>) -> Result<Vec<ZugCollision>, ZugCollisionError> {
+    let mut used: HashSet<(String, String)> = zuege
+        .iter()
+        .map(|zug| (zug.zug.value.gattung.clone(), zug.zug.value.nummer.clone()))
+        .collect();
+
+    collisions
+        .into_iter()
+        .map(|(mut collision, colliding)| {
+            let zug_nummer = ZugNummer::try_from(&collision.nummer)
+                .map_err(|error| ZugCollisionError::InvalidZugNummer { nummer: collision.nummer.clone(), error })?;
+            for index in colliding.into_iter().skip(1) {
+                let new_nummer = (1..)
+                    .map_while(|increment| zug_nummer.to_new_incremented(increment).ok())
+                    .map(|nummer| nummer.to_string_with_width_of(&collision.nummer))
+                    .find(|nummer| !used.contains(&(collision.gattung.clone(), nummer.clone())))
+                    .unwrap(); // Zugnummern can always be incremented until there is an unused one
+                used.insert((collision.gattung.clone(), new_nummer.clone()));
+
+                let zug = &mut zuege[index];
+                zug.zug.value.nummer = new_nummer.clone();
+                if let Some(buchfahrplan) = &mut zug.buchfahrplan {
+                    buchfahrplan.value.nummer = new_nummer.clone();
+                }
+                collision.renumbered_to.push(new_nummer);
+            }
+            Ok(collision)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::lib::generated_zug::{CopyDelayOrigin, RawGeneratedZug};
+    use time::Duration;
+    use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+    use zusi_xml_lib::xml::zusi::zug::fahrzeug_varianten::FahrzeugVarianten;
+    use zusi_xml_lib::xml::zusi::zug::Zug;
+
+    fn generated_zug(gattung: &str, nummer: &str, origin: ZugOrigin) -> GeneratedZug {
+        let zug = RawGeneratedZug {
+            zug: Zug::builder()
+                .fahrplan_datei(Datei::builder().build())
+                .gattung(gattung.into())
+                .nummer(nummer.into())
+                .fahrzeug_varianten(FahrzeugVarianten::builder().build())
+                .build(),
+            buchfahrplan: None,
+        };
+        (zug, origin).into()
+    }
+
+    fn origin(config_nummer: &str) -> ZugOrigin {
+        ZugOrigin { config_nummer: config_nummer.into(), copy_delay: None, rolling_stock: "./rolling-stock.trn".into() }
+    }
+
+    fn copy_origin(config_nummer: &str, increment: i32) -> ZugOrigin {
+        ZugOrigin {
+            config_nummer: config_nummer.into(),
+            copy_delay: Some(CopyDelayOrigin { task: 1, copy: 1, delay: Duration::hours(1), increment }),
+            rolling_stock: "./rolling-stock.trn".into(),
+        }
+    }
+
+    fn zuege() -> Vec<GeneratedZug> {
+        vec![
+            generated_zug("RB", "10000", origin("10000")),
+            generated_zug("RB", "10002", copy_origin("10000", 2)),
+            generated_zug("RE", "10002", origin("10002")),
+            generated_zug("RB", "10001", origin("10001")),
+            generated_zug("RB", "10002", copy_origin("10001", 1)),
+        ]
+    }
+
+    #[test]
+    fn test_check_zug_collisions_warn() {
+        let mut zuege = zuege();
+
+        assert_eq!(
+            check_zug_collisions(&mut zuege, ZugCollisionPolicy::Warn).unwrap(),
+            vec![
+                ZugCollision {
+                    gattung: "RB".into(),
+                    nummer: "10002".into(),
+                    origins: vec![copy_origin("10000", 2), copy_origin("10001", 1)],
+                    renumbered_to: vec![],
+                },
+            ],
+        );
+        assert_eq!(zuege, self::zuege());
+    }
+
+    #[test]
+    fn test_check_zug_collisions_fail() {
+        let mut zuege = zuege();
+
+        assert!(matches!(
+            check_zug_collisions(&mut zuege, ZugCollisionPolicy::Fail).unwrap_err(),
+            ZugCollisionError::Collisions { collisions } if collisions.len() == 1,
+        ));
+
+        let mut zuege = vec![generated_zug("RB", "10000", origin("10000"))];
+        assert_eq!(check_zug_collisions(&mut zuege, ZugCollisionPolicy::Fail).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_check_zug_collisions_renumber() {
+        let mut zuege = zuege();
+
+        assert_eq!(
+            check_zug_collisions(&mut zuege, ZugCollisionPolicy::Renumber).unwrap(),
+            vec![
+                ZugCollision {
+                    gattung: "RB".into(),
+                    nummer: "10002".into(),
+                    origins: vec![copy_origin("10000", 2), copy_origin("10001", 1)],
+                    renumbered_to: vec!["10003".into()],
+                },
+            ],
+        );
+        assert_eq!(
+            zuege.iter().map(|zug| zug.zug.value.nummer.as_str()).collect::<Vec<_>>(),
+            vec!["10000", "10002", "10002", "10001", "10003"],
+        );
+    }
+
+    #[test]
+    fn test_check_zug_collisions_renumber_keeps_width() {
+        let mut zuege = vec![
+            generated_zug("RB", "01234", origin("01234")),
+            generated_zug("RB", "01234", copy_origin("01233", 1)),
+        ];
+
+        let collisions = check_zug_collisions(&mut zuege, ZugCollisionPolicy::Renumber).unwrap();
+
+        assert_eq!(collisions[0].renumbered_to, vec![String::from("01235")]);
+        assert_eq!(zuege[1].zug.value.nummer, "01235");
+        assert!(collisions[0].to_string().ends_with("; renumbered to 01235, Umlaeufe and route parts still reference 01234"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,3 @@
+pub mod gtfs;
+pub mod import_zuege;
+pub mod timetable_csv;
\ No newline at end of file
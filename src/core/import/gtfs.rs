@@ -0,0 +1,408 @@
+use crate::core::import::import_zuege::{ImportedEintrag, ImportedZug};
+use crate::core::import::timetable_csv::{parse_time, split_row};
+use crate::input::stop_mapping::StopMapping;
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use thiserror::Error;
+use time::macros::format_description;
+use time::Date;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GtfsError {
+    #[error("The GTFS feed couldn't be read: {error}")]
+    ReadZip {
+        error: String,
+    },
+
+    #[error("The GTFS feed doesn't contain the file '{file}'.")]
+    MissingFile {
+        file: String,
+    },
+
+    #[error("The file '{file}' has no column '{column}'.")]
+    MissingColumn {
+        file: String,
+        column: String,
+    },
+
+    #[error("Row {row} of the file '{file}' contains a quote which is never closed.")]
+    UnclosedQuote {
+        file: String,
+        row: usize,
+    },
+
+    #[error("The value '{value}' of the column '{column}' in the file '{file}' is invalid.")]
+    InvalidValue {
+        file: String,
+        column: String,
+        value: String,
+    },
+
+    #[error("The trip '{trip_id}' contains the invalid time '{value}'.")]
+    InvalidTime {
+        trip_id: String,
+        value: String,
+    },
+
+    #[error("The trip '{trip_id}' belongs to the route '{route_id}' which doesn't exist.")]
+    UnknownRoute {
+        trip_id: String,
+        route_id: String,
+    },
+
+    #[error("The route_short_name of the route '{route_id}' doesn't start with a category which could be used as Gattung.")]
+    MissingGattung {
+        route_id: String,
+    },
+
+    #[error("The trip '{trip_id}' has no trip_short_name which could be used as Zugnummer.")]
+    MissingTripShortName {
+        trip_id: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtfsStop {
+    pub stop_id: String,
+    pub parent_station: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtfsRoute {
+    pub route_id: String,
+    pub route_short_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtfsTrip {
+    pub route_id: String,
+    pub service_id: String,
+    pub trip_id: String,
+    pub trip_short_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtfsStopTime {
+    pub trip_id: String,
+    pub arrival_time: String,
+    pub departure_time: String,
+    pub stop_id: String,
+    pub stop_sequence: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtfsCalendar {
+    pub service_id: String,
+
+    /// Whether the service runs on each day of the week, starting with Monday
+    pub weekdays: [bool; 7],
+    pub start_date: Date,
+    pub end_date: Date,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtfsCalendarDate {
+    pub service_id: String,
+    pub date: Date,
+
+    /// 1 if the service is added for the date, 2 if it is removed
+    pub exception_type: u8,
+}
+
+/// The parts of a GTFS feed which are needed to import Zuege
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GtfsFeed {
+    pub stops: Vec<GtfsStop>,
+    pub routes: Vec<GtfsRoute>,
+    pub trips: Vec<GtfsTrip>,
+    pub stop_times: Vec<GtfsStopTime>,
+    pub calendar: Vec<GtfsCalendar>,
+    pub calendar_dates: Vec<GtfsCalendarDate>,
+}
+
+impl GtfsFeed {
+    /// Reads the feed from its files, `read_file` returns [None] if a file doesn't exist.
+    pub fn parse<F: FnMut(&str) -> Result<Option<String>, GtfsError>>(mut read_file: F) -> Result<Self, GtfsError> {
+        let mut required_file = |file: &str| -> Result<String, GtfsError> { read_file(file)?.ok_or_else(|| GtfsError::MissingFile { file: file.into() }) };
+        let stops = required_file("stops.txt")?;
+        let routes = required_file("routes.txt")?;
+        let trips = required_file("trips.txt")?;
+        let stop_times = required_file("stop_times.txt")?;
+        let calendar = read_file("calendar.txt")?;
+        let calendar_dates = read_file("calendar_dates.txt")?;
+        if calendar.is_none() && calendar_dates.is_none() {
+            return Err(GtfsError::MissingFile { file: "calendar.txt".into() });
+        }
+
+        Ok(GtfsFeed {
+            stops: read_table("stops.txt", &stops, &["stop_id"], &["parent_station"])?
+                .into_iter()
+                .map(|[stop_id, parent_station]| GtfsStop { stop_id, parent_station })
+                .collect(),
+            routes: read_table("routes.txt", &routes, &["route_id"], &["route_short_name"])?
+                .into_iter()
+                .map(|[route_id, route_short_name]| GtfsRoute { route_id, route_short_name })
+                .collect(),
+            trips: read_table("trips.txt", &trips, &["route_id", "service_id", "trip_id"], &["trip_short_name"])?
+                .into_iter()
+                .map(|[route_id, service_id, trip_id, trip_short_name]| GtfsTrip { route_id, service_id, trip_id, trip_short_name })
+                .collect(),
+            stop_times: parse_stop_times(&stop_times)?,
+            calendar: calendar.as_deref().map(parse_calendar).transpose()?.unwrap_or_default(),
+            calendar_dates: calendar_dates.as_deref().map(parse_calendar_dates).transpose()?.unwrap_or_default(),
+        })
+    }
+
+    fn runs_on(&self, service_id: &str, date: Date) -> bool {
+        let exception = self.calendar_dates
+            .iter()
+            .find(|calendar_date| calendar_date.service_id == service_id && calendar_date.date == date);
+        match exception.map(|calendar_date| calendar_date.exception_type) {
+            Some(1) => true,
+            Some(2) => false,
+            _ => self.calendar.iter().any(|calendar|
+                calendar.service_id == service_id &&
+                    calendar.start_date <= date && date <= calendar.end_date &&
+                    calendar.weekdays[date.weekday().number_days_from_monday() as usize]
+            ),
+        }
+    }
+
+    fn betriebsstelle<'m>(&self, mapping: &'m StopMapping, stop_id: &str) -> Option<&'m str> {
+        mapping.betriebsstelle(stop_id).or_else(|| {
+            self.stops
+                .iter()
+                .find(|stop| stop.stop_id == stop_id && !stop.parent_station.is_empty())
+                .and_then(|stop| mapping.betriebsstelle(&stop.parent_station))
+        })
+    }
+}
+
+pub fn read_gtfs_feed<R: Read + Seek>(reader: R) -> Result<GtfsFeed, GtfsError> {
+    let mut archive = ZipArchive::new(reader).map_err(|error| GtfsError::ReadZip { error: format!("{error}") })?;
+    GtfsFeed::parse(|file| match archive.by_name(file) {
+        Ok(mut zip_file) => {
+            let mut content = String::new();
+            zip_file.read_to_string(&mut content).map_err(|error| GtfsError::ReadZip { error: format!("{error}") })?;
+            Ok(Some(content))
+        },
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(error) => Err(GtfsError::ReadZip { error: format!("{error}") }),
+    })
+}
+
+/// Converts all trips running at the date which stop at or pass at least two mapped stops.
+/// Unmapped stops are skipped. The trip_short_name is used as Zugnummer and the leading letters of the route_short_name as Gattung.
+pub fn read_gtfs_zuege(feed: &GtfsFeed, mapping: &StopMapping, date: Date) -> Result<Vec<ImportedZug>, GtfsError> {
+    let mut stop_times_by_trip: HashMap<&str, Vec<&GtfsStopTime>> = HashMap::new();
+    feed.stop_times
+        .iter()
+        .for_each(|stop_time| stop_times_by_trip.entry(stop_time.trip_id.as_str()).or_default().push(stop_time));
+
+    let mut zuege = vec![];
+    for trip in feed.trips.iter().filter(|trip| feed.runs_on(&trip.service_id, date)) {
+        let mut stop_times = stop_times_by_trip.get(trip.trip_id.as_str()).cloned().unwrap_or_default();
+        stop_times.sort_by_key(|stop_time| stop_time.stop_sequence);
+
+        let last_index = stop_times.len().saturating_sub(1);
+        let eintraege = stop_times
+            .iter()
+            .enumerate()
+            .filter_map(|(index, stop_time)| feed.betriebsstelle(mapping, &stop_time.stop_id).map(|betriebsstelle| (index, stop_time, betriebsstelle)))
+            .map(|(index, stop_time, betriebsstelle)| -> Result<_, GtfsError> {
+                let time = |value: &str| parse_time(value, date, None)
+                    .ok_or_else(|| GtfsError::InvalidTime { trip_id: trip.trip_id.clone(), value: value.into() });
+                let arrival = time(&stop_time.arrival_time)?;
+                let departure = time(&stop_time.departure_time)?;
+                let (ankunft, abfahrt) = if index == last_index {
+                    (arrival.or(departure), None)
+                } else if index == 0 || arrival == departure {
+                    (None, departure.or(arrival))
+                } else {
+                    (arrival, departure)
+                };
+                Ok(ImportedEintrag { betriebsstelle: betriebsstelle.into(), ankunft, abfahrt })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if eintraege.len() < 2 {
+            continue;
+        }
+
+        let route = feed.routes
+            .iter()
+            .find(|route| route.route_id == trip.route_id)
+            .ok_or_else(|| GtfsError::UnknownRoute { trip_id: trip.trip_id.clone(), route_id: trip.route_id.clone() })?;
+        let gattung: String = route.route_short_name.trim().chars().take_while(|char| char.is_alphabetic()).collect();
+        if gattung.is_empty() {
+            return Err(GtfsError::MissingGattung { route_id: route.route_id.clone() });
+        }
+        if trip.trip_short_name.trim().is_empty() {
+            return Err(GtfsError::MissingTripShortName { trip_id: trip.trip_id.clone() });
+        }
+
+        zuege.push(ImportedZug { gattung, nummer: trip.trip_short_name.trim().into(), eintraege });
+    }
+    Ok(zuege)
+}
+
+fn parse_stop_times(content: &str) -> Result<Vec<GtfsStopTime>, GtfsError> {
+    read_table("stop_times.txt", content, &["trip_id", "arrival_time", "departure_time", "stop_id", "stop_sequence"], &[])?
+        .into_iter()
+        .map(|[trip_id, arrival_time, departure_time, stop_id, stop_sequence]| -> Result<_, GtfsError> {
+            Ok(GtfsStopTime {
+                trip_id,
+                arrival_time,
+                departure_time,
+                stop_id,
+                stop_sequence: parse_value("stop_times.txt", "stop_sequence", &stop_sequence, |value| value.parse().ok())?,
+            })
+        })
+        .collect()
+}
+
+fn parse_calendar(content: &str) -> Result<Vec<GtfsCalendar>, GtfsError> {
+    let columns = ["service_id", "monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday", "start_date", "end_date"];
+    read_table::<10>("calendar.txt", content, &columns, &[])?
+        .into_iter()
+        .map(|[service_id, weekdays @ .., start_date, end_date]| -> Result<_, GtfsError> {
+            let weekdays = weekdays
+                .iter()
+                .zip(&columns[1..])
+                .map(|(value, column)| parse_value("calendar.txt", column, value, |value| match value {
+                    "0" => Some(false),
+                    "1" => Some(true),
+                    _ => None,
+                }))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(GtfsCalendar {
+                service_id,
+                weekdays: weekdays.try_into().unwrap(), // the pattern contains seven weekdays
+                start_date: parse_value("calendar.txt", "start_date", &start_date, parse_date)?,
+                end_date: parse_value("calendar.txt", "end_date", &end_date, parse_date)?,
+            })
+        })
+        .collect()
+}
+
+fn parse_calendar_dates(content: &str) -> Result<Vec<GtfsCalendarDate>, GtfsError> {
+    read_table("calendar_dates.txt", content, &["service_id", "date", "exception_type"], &[])?
+        .into_iter()
+        .map(|[service_id, date, exception_type]| -> Result<_, GtfsError> {
+            Ok(GtfsCalendarDate {
+                service_id,
+                date: parse_value("calendar_dates.txt", "date", &date, parse_date)?,
+                exception_type: parse_value("calendar_dates.txt", "exception_type", &exception_type, |value| value.parse().ok())?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the values of the required and optional columns for every row, missing optional columns are empty.
+fn read_table<const N: usize>(file: &str, content: &str, required: &[&str], optional: &[&str]) -> Result<Vec<[String; N]>, GtfsError> {
+    let mut lines = content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| split_row(line, ',').ok_or_else(|| GtfsError::UnclosedQuote { file: file.into(), row: index + 1 }));
+    let header = lines.next().transpose()?.unwrap_or_default();
+    let position = |column: &str| header.iter().position(|name| name.trim() == column);
+    let indices = required
+        .iter()
+        .map(|column| position(column).map(Some).ok_or_else(|| GtfsError::MissingColumn { file: file.into(), column: (*column).into() }))
+        .chain(optional.iter().map(|column| Ok(position(column))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    lines
+        .map(|fields| {
+            let fields = fields?;
+            let values: Vec<String> = indices
+                .iter()
+                .map(|index| index.and_then(|index| fields.get(index)).map(|value| value.trim().to_string()).unwrap_or_default())
+                .collect();
+            Ok(values.try_into().unwrap()) // one value per required and optional column
+        })
+        .collect()
+}
+
+fn parse_value<T>(file: &str, column: &str, value: &str, parse: impl Fn(&str) -> Option<T>) -> Result<T, GtfsError> {
+    parse(value).ok_or_else(|| GtfsError::InvalidValue { file: file.into(), column: column.into(), value: value.into() })
+}
+
+fn parse_date(value: &str) -> Option<Date> {
+    Date::parse(value, format_description!("[year][month][day]")).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::stop_mapping::StopMappingEntry;
+    use std::collections::HashMap;
+    use time::macros::{date, datetime};
+
+    fn feed() -> GtfsFeed {
+        let files = HashMap::from([
+            ("stops.txt", "stop_id,stop_name,parent_station\nelze,Elze,\nelze:1,Elze Gleis 1,elze\nmehle,Mehle,\nhannover,Hannover Hbf,\n"),
+            ("routes.txt", "route_id,agency_id,route_short_name,route_type\nr1,db,RB 77,2\n"),
+            ("trips.txt", "route_id,service_id,trip_id,trip_short_name\nr1,weekdays,t1,30001\nr1,weekends,t2,30003\nr1,weekdays,t3,30005\n"),
+            ("stop_times.txt", concat!(
+                "trip_id,arrival_time,departure_time,stop_id,stop_sequence\n",
+                "t1,08:10:00,08:10:00,hannover,1\n",
+                "t1,08:40:00,08:41:00,elze:1,2\n",
+                "t1,08:45:00,08:45:00,mehle,3\n",
+                "t2,09:40:00,09:40:00,elze,1\n",
+                "t2,09:45:00,09:45:00,mehle,2\n",
+                "t3,24:40:00,24:41:00,elze,2\n",
+                "t3,24:10:00,24:10:00,hannover,1\n",
+            )),
+            ("calendar.txt", concat!(
+                "service_id,monday,tuesday,wednesday,thursday,friday,saturday,sunday,start_date,end_date\n",
+                "weekdays,1,1,1,1,1,0,0,20240101,20241231\n",
+                "weekends,0,0,0,0,0,1,1,20240101,20241231\n",
+            )),
+        ]);
+        GtfsFeed::parse(|file| Ok(files.get(file).map(|content| content.to_string()))).unwrap()
+    }
+
+    #[test]
+    fn test_read_gtfs_zuege() {
+        let mapping = StopMapping {
+            stops: vec![
+                StopMappingEntry { stop_id: "elze".into(), betriebsstelle: "Elze".into() },
+                StopMappingEntry { stop_id: "mehle".into(), betriebsstelle: "Mehle Hp".into() },
+            ],
+        };
+
+        assert_eq!(
+            read_gtfs_zuege(&feed(), &mapping, date!(2024-06-20)).unwrap(),
+            vec![
+                ImportedZug {
+                    gattung: "RB".into(),
+                    nummer: "30001".into(),
+                    eintraege: vec![
+                        ImportedEintrag { betriebsstelle: "Elze".into(), ankunft: Some(datetime!(2024-06-20 08:40:00)), abfahrt: Some(datetime!(2024-06-20 08:41:00)) },
+                        ImportedEintrag { betriebsstelle: "Mehle Hp".into(), ankunft: Some(datetime!(2024-06-20 08:45:00)), abfahrt: None },
+                    ],
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parse_gtfs_feed_with_missing_column() {
+        let files = HashMap::from([
+            ("stops.txt", "stop_id\n"),
+            ("routes.txt", "route_id\n"),
+            ("trips.txt", "route_id,trip_id\n"),
+            ("stop_times.txt", ""),
+            ("calendar_dates.txt", ""),
+        ]);
+
+        assert_eq!(
+            GtfsFeed::parse(|file| Ok(files.get(file).map(|content| content.to_string()))),
+            Err(GtfsError::MissingColumn { file: "trips.txt".into(), column: "service_id".into() }),
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,375 @@
+use crate::core::schedules::apply::{apply_schedule, ApplyScheduleError};
+use crate::core::schedules::generate::generate_schedule;
+use crate::core::schedules::prepare_entries::prepare_entries;
+use crate::input::fahrplan_config::{ApplySchedule, RouteConfig, RoutePart, RoutePartSource, RouteTimeFix, RouteTimeFixType, ZugConfig};
+use crate::input::rolling_stock_config::RollingStockConfig;
+use crate::input::schedule::{Schedule, ScheduleEntry};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use time::PrimitiveDateTime;
+use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    #[error("No route template shares at least two consecutive Betriebsstellen with the Zug '{nummer}'.")]
+    NoMatchingTemplate {
+        nummer: String,
+    },
+
+    #[error(r#"The first FahrplanEintrag of the route template "{}" has no Abfahrt to fix the times of the Zug '{nummer}' with."#, path.display())]
+    TemplateWithoutAbfahrt {
+        nummer: String,
+        path: PathBuf,
+    },
+
+    #[error("Couldn't apply the schedule of the Zug '{nummer}' to the route template: {error}")]
+    CouldNotApplySchedule {
+        nummer: String,
+
+        #[source]
+        error: ApplyScheduleError,
+    },
+}
+
+/// Differences between an imported Zug and its route template which are skipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportWarning {
+    SkippedBetriebsstellen {
+        nummer: String,
+        betriebsstellen: Vec<String>,
+    },
+    StopAtPass {
+        nummer: String,
+        betriebsstelle: String,
+    },
+    DuplicateZug {
+        gattung: String,
+        nummer: String,
+        schedule_path: PathBuf,
+    },
+}
+
+impl Display for ImportWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportWarning::SkippedBetriebsstellen { nummer, betriebsstellen } =>
+                write!(f, "{} of the Zug '{nummer}' are not part of the route template and were skipped", betriebsstellen.join(", ")),
+            ImportWarning::StopAtPass { nummer, betriebsstelle } =>
+                write!(f, "the Zug '{nummer}' stops at '{betriebsstelle}', but the route template passes it, so the stop time was skipped"),
+            ImportWarning::DuplicateZug { gattung, nummer, schedule_path } =>
+                write!(f, r#"the Zug '{gattung} {nummer}' was imported multiple times, its schedule is written to "{}""#, schedule_path.display()),
+        }
+    }
+}
+
+/// Zug read from an external timetable
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedZug {
+    pub gattung: String,
+    pub nummer: String,
+    pub eintraege: Vec<ImportedEintrag>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedEintrag {
+    pub betriebsstelle: String,
+    pub ankunft: Option<PrimitiveDateTime>,
+    pub abfahrt: Option<PrimitiveDateTime>,
+}
+
+/// .trn file the imported Zuege are matched against, the path is written to the config as is
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteTemplate {
+    pub path: PathBuf,
+    pub fahrplan_eintraege: Vec<FahrplanEintrag>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedSchedule {
+    pub path: PathBuf,
+    pub schedule: Schedule,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportResult {
+    pub schedules: Vec<ImportedSchedule>,
+    pub zug_configs: Vec<ZugConfig>,
+    pub warnings: Vec<ImportWarning>,
+}
+
+/// Pairs every imported Zug with the route template sharing the most consecutive Betriebsstellen.
+/// For each Zug, a schedule containing the shared Betriebsstellen and a Zug config applying it to the template are created.
+/// The Zug config fixes the start of the template, so the first shared Betriebsstelle is departed at the imported time.
+/// Zuege with the same Gattung and Zugnummer get schedule files with a numbered suffix.
+pub fn import_zuege(zuege: &[ImportedZug], templates: &[RouteTemplate], rolling_stock: &Path) -> Result<ImportResult, ImportError> {
+    let mut result = ImportResult {
+        schedules: vec![],
+        zug_configs: vec![],
+        warnings: vec![],
+    };
+
+    for zug in zuege {
+        let schedule = generate_schedule(&fahrplan_eintraege(zug));
+        // iterate in reverse since max_by_key returns the last of equal elements, so the first template wins ties
+        let (template, matched) = templates
+            .iter()
+            .rev()
+            .map(|template| (template, match_template(template, &schedule)))
+            .max_by_key(|(_, matched)| matched.len())
+            .filter(|(_, matched)| matched.len() >= 2)
+            .ok_or_else(|| ImportError::NoMatchingTemplate { nummer: zug.nummer.clone() })?;
+
+        let skipped: Vec<String> = schedule.entries
+            .iter()
+            .filter(|entry| !matched.iter().any(|(_, _, matched_entry)| std::ptr::eq(*entry, *matched_entry)))
+            .map(|entry| entry.betriebsstelle.clone())
+            .collect();
+        if !skipped.is_empty() {
+            result.warnings.push(ImportWarning::SkippedBetriebsstellen { nummer: zug.nummer.clone(), betriebsstellen: skipped });
+        }
+
+        let matched_schedule = Schedule {
+            entries: matched
+                .iter()
+                .map(|(_, is_stop, entry)| {
+                    let mut entry = (*entry).clone();
+                    if !is_stop && entry.stop_time.is_some() {
+                        result.warnings.push(ImportWarning::StopAtPass { nummer: zug.nummer.clone(), betriebsstelle: entry.betriebsstelle.clone() });
+                        entry.stop_time = None;
+                    }
+                    entry
+                })
+                .collect(),
+        };
+
+        let (first_index, _, first_entry) = matched[0];
+        let imported_abfahrt = zug.eintraege
+            .iter()
+            .filter_map(|eintrag| eintrag.abfahrt.or(eintrag.ankunft))
+            .nth(schedule.entries.iter().position(|entry| std::ptr::eq(entry, first_entry)).unwrap()) // the matched entry is part of the schedule
+            .unwrap(); // the schedule contains one entry per imported Eintrag with times
+        let start_abfahrt = start_abfahrt(zug, template, &matched_schedule, first_index, imported_abfahrt)?;
+
+        let schedule_path = unique_schedule_path(zug, &result.schedules);
+        if schedule_path != schedule_file_path(zug, 1) {
+            result.warnings.push(ImportWarning::DuplicateZug { gattung: zug.gattung.clone(), nummer: zug.nummer.clone(), schedule_path: schedule_path.clone() });
+        }
+        result.zug_configs.push(ZugConfig {
+            nummer: zug.nummer.clone(),
+            gattung: zug.gattung.clone(),
+            extends: None,
+            zuglauf: match (matched_schedule.entries.first(), matched_schedule.entries.last()) {
+                (Some(first), Some(last)) => format!("{} - {}", first.betriebsstelle, last.betriebsstelle),
+                _ => String::new(),
+            },
+            fahrplan_gruppe: String::new(),
+            meta_data: None,
+            route: RouteConfig {
+                parts: vec![
+                    RoutePart {
+                        source: RoutePartSource::TrainFileByPath { path: template.path.clone() },
+                        start_fahrzeug_verband_aktion: None,
+                        time_fix: Some(RouteTimeFix { fix_type: RouteTimeFixType::StartAbf, value: start_abfahrt }),
+                        apply_schedule: Some(ApplySchedule { path: schedule_path.clone(), first_stop_time: None, last_stop_time: None }),
+                    },
+                ],
+                includes: vec![],
+            },
+            rolling_stock: RollingStockConfig { path: rolling_stock.into() },
+            copy_delay_config: None,
+            takt: None,
+            return_config: None,
+            time_rounding: None,
+        });
+        result.schedules.push(ImportedSchedule { path: schedule_path, schedule: matched_schedule });
+    }
+
+    Ok(result)
+}
+
+/// First path of the schedule file of the Zug which is not used by the already imported schedules
+fn unique_schedule_path(zug: &ImportedZug, schedules: &[ImportedSchedule]) -> PathBuf {
+    (1..)
+        .map(|occurrence| schedule_file_path(zug, occurrence))
+        .find(|path| !schedules.iter().any(|schedule| &schedule.path == path))
+        .unwrap() // there are only finitely many schedules
+}
+
+/// Path of the schedule file of the Zug, characters other than letters, digits, `-` and `_` are replaced.
+/// Every occurrence after the first one gets a numbered suffix.
+fn schedule_file_path(zug: &ImportedZug, occurrence: usize) -> PathBuf {
+    let name: String = format!("{}{}", zug.gattung, zug.nummer)
+        .chars()
+        .map(|char| if char.is_alphanumeric() || char == '-' || char == '_' { char } else { '_' })
+        .collect();
+    match occurrence {
+        1 => PathBuf::from(format!("./{name}.schedule.xml")),
+        occurrence => PathBuf::from(format!("./{name}-{occurrence}.schedule.xml")),
+    }
+}
+
+/// Converts the imported Eintraege to FahrplanEintraege which all have an Abfahrt.
+/// Eintraege with only an Ankunft, like at the last Betriebsstelle, are converted to passes at that time.
+fn fahrplan_eintraege(zug: &ImportedZug) -> Vec<FahrplanEintrag> {
+    zug.eintraege
+        .iter()
+        .filter_map(|eintrag| eintrag.abfahrt.or(eintrag.ankunft).map(|abfahrt|
+            FahrplanEintrag::builder()
+                .betriebsstelle(eintrag.betriebsstelle.clone())
+                .ankunft(eintrag.abfahrt.and(eintrag.ankunft))
+                .abfahrt(Some(abfahrt))
+                .build()
+        ))
+        .collect()
+}
+
+/// Returns the index of every matched FahrplanEintrag with Abfahrt of the template,
+/// whether it is a stop and the matched schedule entry.
+fn match_template<'s>(template: &RouteTemplate, schedule: &'s Schedule) -> Vec<(usize, bool, &'s ScheduleEntry)> {
+    let mut fahrplan_eintraege = template.fahrplan_eintraege.clone();
+    prepare_entries(&mut fahrplan_eintraege, schedule)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, (eintrag, entry))| entry.map(|entry| (index, eintrag.ankunft.is_some(), entry)))
+        .collect()
+}
+
+/// Applies the schedule to the template to find the Abfahrt of its first FahrplanEintrag
+/// which results in the imported Abfahrt at the first matched FahrplanEintrag.
+fn start_abfahrt(zug: &ImportedZug, template: &RouteTemplate, schedule: &Schedule, first_index: usize, imported_abfahrt: PrimitiveDateTime) -> Result<PrimitiveDateTime, ImportError> {
+    let mut fahrplan_eintraege = template.fahrplan_eintraege.clone();
+    apply_schedule(&mut fahrplan_eintraege, schedule)
+        .map_err(|error| ImportError::CouldNotApplySchedule { nummer: zug.nummer.clone(), error })?;
+    let start = fahrplan_eintraege
+        .first()
+        .and_then(|eintrag| eintrag.abfahrt)
+        .ok_or_else(|| ImportError::TemplateWithoutAbfahrt { nummer: zug.nummer.clone(), path: template.path.clone() })?;
+    let matched_abfahrt = fahrplan_eintraege
+        .iter()
+        .filter_map(|eintrag| eintrag.abfahrt)
+        .nth(first_index)
+        .unwrap(); // index is based on the FahrplanEintraege with Abfahrt
+    Ok(start + (imported_abfahrt - matched_abfahrt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+    use time::Duration;
+
+    fn template(path: &str, betriebsstellen: &[&str]) -> RouteTemplate {
+        RouteTemplate {
+            path: path.into(),
+            fahrplan_eintraege: betriebsstellen
+                .iter()
+                .enumerate()
+                .map(|(index, betriebsstelle)| FahrplanEintrag::builder()
+                    .betriebsstelle((*betriebsstelle).into())
+                    .abfahrt(Some(datetime!(2024-06-20 08:00:00) + Duration::minutes(3 * index as i64)))
+                    .build()
+                )
+                .collect(),
+        }
+    }
+
+    fn eintrag(betriebsstelle: &str, ankunft: Option<PrimitiveDateTime>, abfahrt: Option<PrimitiveDateTime>) -> ImportedEintrag {
+        ImportedEintrag { betriebsstelle: betriebsstelle.into(), ankunft, abfahrt }
+    }
+
+    #[test]
+    fn test_import_zuege() {
+        let mut main_template = template("./main.trn", &["A", "B", "C", "D"]);
+        main_template.fahrplan_eintraege[2].ankunft = Some(datetime!(2024-06-20 08:05:00));
+        main_template.fahrplan_eintraege[2].abfahrt = Some(datetime!(2024-06-20 08:06:00));
+        main_template.fahrplan_eintraege[3].ankunft = Some(datetime!(2024-06-20 08:11:00));
+        main_template.fahrplan_eintraege[3].abfahrt = Some(datetime!(2024-06-20 08:12:00));
+        main_template.fahrplan_eintraege.insert(3, FahrplanEintrag::builder().betriebsstelle("D".into()).build());
+        let templates = vec![template("./other.trn", &["X", "B", "Y"]), main_template];
+
+        let zuege = vec![
+            ImportedZug {
+                gattung: "RB".into(),
+                nummer: "30001".into(),
+                eintraege: vec![
+                    eintrag("B", Some(datetime!(2024-06-20 08:59:00)), Some(datetime!(2024-06-20 09:00:00))),
+                    eintrag("C", Some(datetime!(2024-06-20 09:04:00)), Some(datetime!(2024-06-20 09:05:00))),
+                    eintrag("D", Some(datetime!(2024-06-20 09:10:00)), None),
+                    eintrag("E", Some(datetime!(2024-06-20 09:20:00)), None),
+                ],
+            },
+        ];
+
+        let result = import_zuege(&zuege, &templates, Path::new("./rolling-stock.trn")).unwrap();
+
+        assert_eq!(result.warnings, vec![
+            ImportWarning::SkippedBetriebsstellen { nummer: "30001".into(), betriebsstellen: vec!["E".into()] },
+            ImportWarning::StopAtPass { nummer: "30001".into(), betriebsstelle: "B".into() },
+        ]);
+        assert_eq!(result.schedules, vec![ImportedSchedule {
+            path: "./RB30001.schedule.xml".into(),
+            schedule: Schedule {
+                entries: vec![
+                    ScheduleEntry { betriebsstelle: "B".into(), driving_time: Duration::ZERO, stop_time: None, time_fix: None, km: None, laufweg: None },
+                    ScheduleEntry { betriebsstelle: "C".into(), driving_time: Duration::minutes(4), stop_time: Some(Duration::minutes(1)), time_fix: None, km: None, laufweg: None },
+                    ScheduleEntry { betriebsstelle: "D".into(), driving_time: Duration::minutes(5), stop_time: None, time_fix: None, km: None, laufweg: None },
+                ],
+            },
+        }]);
+        assert_eq!(result.zug_configs.len(), 1);
+        assert_eq!(result.zug_configs[0].zuglauf, "B - D");
+        assert_eq!(result.zug_configs[0].route, RouteConfig {
+            parts: vec![
+                RoutePart {
+                    source: RoutePartSource::TrainFileByPath { path: "./main.trn".into() },
+                    start_fahrzeug_verband_aktion: None,
+                    time_fix: Some(RouteTimeFix { fix_type: RouteTimeFixType::StartAbf, value: datetime!(2024-06-20 08:57:00) }),
+                    apply_schedule: Some(ApplySchedule { path: "./RB30001.schedule.xml".into(), first_stop_time: None, last_stop_time: None }),
+                },
+            ],
+            includes: vec![],
+        });
+    }
+
+    #[test]
+    fn test_import_duplicate_zuege() {
+        let zug = ImportedZug {
+            gattung: "RB".into(),
+            nummer: "30 001/2".into(),
+            eintraege: vec![
+                eintrag("A", None, Some(datetime!(2024-06-20 09:00:00))),
+                eintrag("B", Some(datetime!(2024-06-20 09:03:00)), None),
+            ],
+        };
+
+        let result = import_zuege(&[zug.clone(), zug], &[template("./main.trn", &["A", "B", "C"])], Path::new("./rolling-stock.trn")).unwrap();
+
+        assert_eq!(
+            result.schedules.iter().map(|schedule| schedule.path.clone()).collect::<Vec<_>>(),
+            vec![PathBuf::from("./RB30_001_2.schedule.xml"), PathBuf::from("./RB30_001_2-2.schedule.xml")],
+        );
+        assert_eq!(result.warnings, vec![
+            ImportWarning::DuplicateZug { gattung: "RB".into(), nummer: "30 001/2".into(), schedule_path: "./RB30_001_2-2.schedule.xml".into() },
+        ]);
+    }
+
+    #[test]
+    fn test_import_zuege_without_matching_template() {
+        let zuege = vec![
+            ImportedZug {
+                gattung: "RB".into(),
+                nummer: "30001".into(),
+                eintraege: vec![
+                    eintrag("A", None, Some(datetime!(2024-06-20 09:00:00))),
+                    eintrag("Z", Some(datetime!(2024-06-20 09:10:00)), None),
+                ],
+            },
+        ];
+
+        assert_eq!(
+            import_zuege(&zuege, &[template("./main.trn", &["A", "B", "C"])], Path::new("./rolling-stock.trn")),
+            Err(ImportError::NoMatchingTemplate { nummer: "30001".into() }),
+        );
+    }
+}
\ No newline at end of file
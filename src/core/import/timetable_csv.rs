@@ -0,0 +1,199 @@
+use crate::core::import::import_zuege::{ImportedEintrag, ImportedZug};
+use thiserror::Error;
+use time::{Date, Duration, PrimitiveDateTime};
+
+const COLUMNS: [&str; 3] = ["Betriebsstelle", "Ankunft", "Abfahrt"];
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ReadTimetableCsvError {
+    #[error("The CSV file must start with a row containing the Zuege and a row containing the column names.")]
+    MissingHeader,
+
+    #[error("Row {row} contains a quote which is never closed.")]
+    UnclosedQuote {
+        row: usize,
+    },
+
+    #[error("'{value}' in column {column} is not a Zug in the format 'Gattung Nummer'.")]
+    InvalidZug {
+        column: usize,
+        value: String,
+    },
+
+    #[error("Column {column} must be named '{expected}'.")]
+    InvalidColumnName {
+        column: usize,
+        expected: String,
+    },
+
+    #[error("'{value}' in row {row}, column {column} is not a time in the format 'HH:MM' or 'HH:MM:SS'.")]
+    InvalidTime {
+        row: usize,
+        column: usize,
+        value: String,
+    },
+}
+
+/// Reads a CSV timetable with one set of Betriebsstelle, Ankunft and Abfahrt columns per Zug.
+/// The first row contains the Zug as 'Gattung Nummer' in the first column of each set, the second row the column names.
+/// Times belong to the given date, times earlier than the previous one of the same Zug are moved to the next day.
+/// Rows with an empty Betriebsstelle are skipped for the Zug, so Zuege can have a different number of Betriebsstellen.
+pub fn read_timetable_csv(csv: &str, separator: char, date: Date) -> Result<Vec<ImportedZug>, ReadTimetableCsvError> {
+    let rows = csv
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| split_row(line, separator)
+            .map(|fields| (index + 1, fields))
+            .ok_or(ReadTimetableCsvError::UnclosedQuote { row: index + 1 })
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+    let [(_, zuege_row), (_, columns_row), rows @ ..] = rows.as_slice() else {
+        return Err(ReadTimetableCsvError::MissingHeader);
+    };
+
+    let zuege_len = zuege_row.iter().rposition(|field| !field.trim().is_empty()).map_or(0, |index| index + 1);
+    let mut zuege = zuege_row[..zuege_len]
+        .chunks(COLUMNS.len())
+        .enumerate()
+        .map(|(set, fields)| {
+            let value = fields[0].trim();
+            value
+                .rsplit_once(' ')
+                .filter(|(gattung, _)| !gattung.trim().is_empty())
+                .map(|(gattung, nummer)| ImportedZug { gattung: gattung.trim().into(), nummer: nummer.into(), eintraege: vec![] })
+                .ok_or_else(|| ReadTimetableCsvError::InvalidZug { column: set * COLUMNS.len() + 1, value: value.into() })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if zuege.is_empty() {
+        return Err(ReadTimetableCsvError::MissingHeader);
+    }
+
+    for column in 0..zuege.len() * COLUMNS.len() {
+        let expected = COLUMNS[column % COLUMNS.len()];
+        if columns_row.get(column).map(|name| name.trim()) != Some(expected) {
+            return Err(ReadTimetableCsvError::InvalidColumnName { column: column + 1, expected: expected.into() });
+        }
+    }
+
+    for (row, fields) in rows {
+        for (set, zug) in zuege.iter_mut().enumerate() {
+            let field = |offset: usize| fields.get(set * COLUMNS.len() + offset).map(|field| field.trim()).unwrap_or_default();
+            let betriebsstelle = field(0);
+            if betriebsstelle.is_empty() {
+                continue;
+            }
+            let previous = zug.eintraege.last().and_then(|eintrag| eintrag.abfahrt.or(eintrag.ankunft));
+            let invalid_time = |offset: usize| ReadTimetableCsvError::InvalidTime {
+                row: *row,
+                column: set * COLUMNS.len() + offset + 1,
+                value: field(offset).into(),
+            };
+            let ankunft = parse_time(field(1), date, previous).ok_or_else(|| invalid_time(1))?;
+            let abfahrt = parse_time(field(2), date, ankunft.or(previous)).ok_or_else(|| invalid_time(2))?;
+            zug.eintraege.push(ImportedEintrag { betriebsstelle: betriebsstelle.into(), ankunft, abfahrt });
+        }
+    }
+
+    Ok(zuege)
+}
+
+/// Splits a row at the separator, separators and double quotes inside quoted fields are kept.
+/// Returns [None] if a quoted field isn't closed.
+pub fn split_row(line: &str, separator: char) -> Option<Vec<String>> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            },
+            '"' => quoted = !quoted,
+            char if char == separator && !quoted => fields.push(String::new()),
+            char => fields.last_mut().unwrap().push(char),
+        }
+    }
+    (!quoted).then_some(fields)
+}
+
+/// Returns [None] if the value is no valid time and `Some(None)` if it is empty.
+pub fn parse_time(value: &str, date: Date, previous: Option<PrimitiveDateTime>) -> Option<Option<PrimitiveDateTime>> {
+    if value.is_empty() {
+        return Some(None);
+    }
+    let parts = value
+        .split(':')
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [hours, minutes] => (*hours, *minutes, 0),
+        [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+        _ => return None,
+    };
+    if minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    let time = date.midnight() + Duration::hours(hours.into()) + Duration::minutes(minutes.into()) + Duration::seconds(seconds.into());
+    Some(Some(
+        if previous.is_some_and(|previous| time < previous) { time + Duration::days(1) } else { time }
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{date, datetime};
+
+    #[test]
+    fn test_read_timetable_csv() {
+        let csv = concat!(
+            "RB 30001;;;IC 2000;;\n",
+            "Betriebsstelle;Ankunft;Abfahrt;Betriebsstelle;Ankunft;Abfahrt\n",
+            "A;;23:50;\"B; Hbf\";;09:00\n",
+            "B;23:58;00:00:30;C;09:10;\n",
+            "\n",
+            "C;00:10;;;;\n",
+        );
+
+        assert_eq!(
+            read_timetable_csv(csv, ';', date!(2024-06-20)).unwrap(),
+            vec![
+                ImportedZug {
+                    gattung: "RB".into(),
+                    nummer: "30001".into(),
+                    eintraege: vec![
+                        ImportedEintrag { betriebsstelle: "A".into(), ankunft: None, abfahrt: Some(datetime!(2024-06-20 23:50:00)) },
+                        ImportedEintrag { betriebsstelle: "B".into(), ankunft: Some(datetime!(2024-06-20 23:58:00)), abfahrt: Some(datetime!(2024-06-21 00:00:30)) },
+                        ImportedEintrag { betriebsstelle: "C".into(), ankunft: Some(datetime!(2024-06-21 00:10:00)), abfahrt: None },
+                    ],
+                },
+                ImportedZug {
+                    gattung: "IC".into(),
+                    nummer: "2000".into(),
+                    eintraege: vec![
+                        ImportedEintrag { betriebsstelle: "B; Hbf".into(), ankunft: None, abfahrt: Some(datetime!(2024-06-20 09:00:00)) },
+                        ImportedEintrag { betriebsstelle: "C".into(), ankunft: Some(datetime!(2024-06-20 09:10:00)), abfahrt: None },
+                    ],
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_read_timetable_csv_with_invalid_time() {
+        let csv = concat!(
+            "RB 30001,,\n",
+            "Betriebsstelle,Ankunft,Abfahrt\n",
+            "A,,08:00\n",
+            "B,08:61,08:62\n",
+        );
+
+        assert_eq!(
+            read_timetable_csv(csv, ',', date!(2024-06-20)),
+            Err(ReadTimetableCsvError::InvalidTime { row: 4, column: 2, value: "08:61".into() }),
+        );
+    }
+}
\ No newline at end of file
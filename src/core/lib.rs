@@ -3,4 +3,7 @@ pub mod helpers;
 pub mod longest_common_coherent_subsequence;
 pub mod zug_nummer;
 pub mod copy_delay;
-pub mod generated_zug;
\ No newline at end of file
+pub mod generated_zug;
+pub mod generated_fahrplan;
+pub mod time_rounding;
+pub mod takt;
\ No newline at end of file
@@ -1,11 +1,12 @@
-use crate::core::lib::generated_zug::RawGeneratedZug;
+use crate::core::lib::generated_zug::{CopyDelayOrigin, RawGeneratedZug};
 use crate::core::lib::helpers::{delay_fahrplan_eintraege, delay_fahrplan_zeilen};
 use crate::core::lib::zug_nummer::ZugNummer;
-use crate::core::replace_rolling_stock::{replace_rolling_stock, ReplaceRollingStockError};
+use crate::core::replace_rolling_stock::{replace_rolling_stock, validate_rolling_stock, ReplaceRollingStockError};
 use crate::input::copy_delay_config::{CopyDelayConfig, CopyDelayTask};
 use crate::input::environment::zusi_environment::ZusiEnvironment;
 use std::num::ParseIntError;
 use thiserror::Error;
+use time::Duration;
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum CopyDelayError {
@@ -50,16 +51,7 @@ fn apply_copy_delay_task(env: &ZusiEnvironment, task: CopyDelayTask, zug: &RawGe
         |mut zuege, n| {
             let mut zug = zug.clone();
 
-            let delay = if task.first_delay.is_some() {
-                task.delay * (n - 1) + task.first_delay.unwrap()
-            } else {
-                n * task.delay
-            };
-            let increment = if task.first_increment.is_some() {
-                task.increment * (n as i32 - 1) + task.first_increment.unwrap()
-            } else {
-                n as i32 * task.increment
-            };
+            let (delay, increment) = delay_and_increment(&task, n);
 
             zug.zug.nummer = zug_nummer
                 .to_new_incremented(increment)
@@ -78,6 +70,63 @@ fn apply_copy_delay_task(env: &ZusiEnvironment, task: CopyDelayTask, zug: &RawGe
     )
 }
 
+/// Checks the copy delay config for the given Zugnummer without copying any Zug and returns all found errors.
+pub fn validate_copy_delay(env: &ZusiEnvironment, config: &CopyDelayConfig, zug_nummer: &str) -> Vec<CopyDelayError> {
+    let mut errors = vec![];
+    let zug_nummer = match ZugNummer::try_from(zug_nummer) {
+        Ok(zug_nummer) => Some(zug_nummer),
+        Err(error) => {
+            errors.push(error.into());
+            None
+        },
+    };
+    config.tasks.iter().for_each(|task| {
+        if let Some(rolling_stock) = &task.custom_rolling_stock {
+            if let Err(error) = validate_rolling_stock(env, rolling_stock) {
+                errors.push(error.into());
+            }
+        }
+        if let Some(zug_nummer) = &zug_nummer {
+            let is_negative = (1..=task.count).any(|n| zug_nummer.to_new_incremented(delay_and_increment(task, n).1).is_err());
+            if is_negative {
+                errors.push(CopyDelayError::ZugNummerCanNotBeNegative);
+            }
+        }
+    });
+    errors
+}
+
+/// Origins of all copies created by [copy_delay] for the given config, in the same order as the copies.
+pub fn copy_delay_origins(config: &CopyDelayConfig) -> Vec<CopyDelayOrigin> {
+    config.tasks
+        .iter()
+        .enumerate()
+        .flat_map(|(index, task)| (1..=task.count).map(move |n| {
+            let (delay, increment) = delay_and_increment(task, n);
+            CopyDelayOrigin {
+                task: index + 1,
+                copy: n,
+                delay,
+                increment,
+            }
+        }))
+        .collect()
+}
+
+fn delay_and_increment(task: &CopyDelayTask, n: u32) -> (Duration, i32) {
+    let delay = if task.first_delay.is_some() {
+        task.delay * (n - 1) + task.first_delay.unwrap()
+    } else {
+        n * task.delay
+    };
+    let increment = if task.first_increment.is_some() {
+        task.increment * (n as i32 - 1) + task.first_increment.unwrap()
+    } else {
+        n as i32 * task.increment
+    };
+    (delay, increment)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -932,4 +981,46 @@ mod tests {
             CopyDelayError::ZugNummerCanNotBeNegative { .. },
         ));
     }
+
+    #[test]
+    fn test_validate_copy_delay() {
+        let tmp_dir = tempdir().unwrap();
+
+        let env = ZusiEnvironment {
+            data_dir: tmp_dir.path().to_owned(),
+            config_dir: tmp_dir.path().to_owned(),
+        };
+
+        let config = CopyDelayConfig {
+            tasks: vec![
+                CopyDelayTask {
+                    delay: Duration::hours(1),
+                    first_delay: None,
+                    increment: 2,
+                    first_increment: None,
+                    count: 2,
+                    custom_rolling_stock: Some(RollingStockConfig { path: "non-existent".into() }),
+                },
+                CopyDelayTask {
+                    delay: Duration::hours(-1),
+                    first_delay: None,
+                    increment: -3,
+                    first_increment: None,
+                    count: 2,
+                    custom_rolling_stock: None,
+                },
+            ],
+        };
+
+        let errors = validate_copy_delay(&env, &config, "4");
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[0], CopyDelayError::ReplaceRollingStockError { .. }));
+        assert_eq!(errors[1], CopyDelayError::ZugNummerCanNotBeNegative);
+
+        assert!(matches!(
+            validate_copy_delay(&env, &CopyDelayConfig { tasks: vec![] }, "invalid")[..],
+            [CopyDelayError::InvalidZugNummer { .. }],
+        ));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,269 @@
+use crate::core::generate_fahrplan::conflicts::Conflict;
+use crate::core::generate_fahrplan::generate_zug::GenerateZugError;
+use crate::core::generate_fahrplan::zug_collisions::ZugCollision;
+use crate::core::lib::generated_zug::{RawGeneratedZug, ZugOrigin};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_helpers::with::date_time::date_time_format;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use time::PrimitiveDateTime;
+use zusi_xml_lib::xml::zusi::buchfahrplan::Buchfahrplan;
+use zusi_xml_lib::xml::zusi::fahrplan::Fahrplan;
+use zusi_xml_lib::xml::zusi::lib::path::prejoined_zusi_path::PrejoinedZusiPath;
+use zusi_xml_lib::xml::zusi::zug::Zug;
+use zusi_xml_lib::xml::zusi::TypedZusi;
+
+/// Fully generated Fahrplan which is not yet written to disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedFahrplan {
+    pub path: PrejoinedZusiPath,
+    pub fahrplan: TypedZusi<Fahrplan>,
+    pub zuege: Vec<AttachedZug>,
+
+    /// Zuege which were skipped because they couldn't be generated
+    pub failed_zuege: Vec<GenerateZugError>,
+
+    /// Zuege which share the same Gattung and Zugnummer
+    pub zug_collisions: Vec<ZugCollision>,
+
+    /// Zuege which occupy the same Betriebsstelle or single-track section within the headway
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachedZug {
+    pub path: PrejoinedZusiPath,
+    pub zug: TypedZusi<Zug>,
+    pub buchfahrplan: Option<AttachedBuchfahrplan>,
+    pub origin: ZugOrigin,
+}
+
+impl From<&AttachedZug> for RawGeneratedZug {
+    fn from(zug: &AttachedZug) -> Self {
+        Self {
+            zug: zug.zug.value.clone(),
+            buchfahrplan: zug.buchfahrplan.as_ref().map(|buchfahrplan| buchfahrplan.buchfahrplan.value.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachedBuchfahrplan {
+    pub path: PrejoinedZusiPath,
+    pub buchfahrplan: TypedZusi<Buchfahrplan>,
+}
+
+impl GeneratedFahrplan {
+    /// Paths of all files which would be written, in the order they are written
+    pub fn file_paths(&self) -> Vec<&PrejoinedZusiPath> {
+        let mut paths: Vec<&PrejoinedZusiPath> = self.zuege
+            .iter()
+            .flat_map(|zug| {
+                zug.buchfahrplan
+                    .iter()
+                    .map(|buchfahrplan| &buchfahrplan.path)
+                    .chain([&zug.path])
+            })
+            .collect();
+        paths.push(&self.path);
+        paths
+    }
+
+    pub fn dry_run_report(&self) -> DryRunReport {
+        DryRunReport {
+            files: self.file_paths()
+                .into_iter()
+                .map(|path| PlannedFile {
+                    path: path.full_path().to_owned(),
+                    exists: path.full_path().exists(),
+                })
+                .collect(),
+            zug_dateien: self.fahrplan.value.zug_dateien
+                .iter()
+                .map(|eintrag| eintrag.datei.dateiname.get().to_owned())
+                .collect(),
+            failed_zuege: self.failed_zuege
+                .iter()
+                .map(|error| error.to_string())
+                .collect(),
+            zug_collisions: self.zug_collisions
+                .iter()
+                .map(|collision| collision.to_string())
+                .collect(),
+            conflicts: self.conflicts
+                .iter()
+                .map(|conflict| conflict.to_string())
+                .collect(),
+        }
+    }
+
+    pub fn report(&self) -> GenerationReport {
+        GenerationReport {
+            fahrplan: self.path.full_path().to_owned(),
+            zuege: self.zuege
+                .iter()
+                .map(|zug| self.zug_report(zug))
+                .collect(),
+            failed_zuege: self.failed_zuege
+                .iter()
+                .map(|error| error.to_string())
+                .collect(),
+        }
+    }
+
+    fn zug_report(&self, zug: &AttachedZug) -> ZugReport {
+        let value = &zug.zug.value;
+        let collisions = self.zug_collisions
+            .iter()
+            .filter(|collision| collision.origins.contains(&zug.origin))
+            .map(|collision| collision.to_string());
+        let conflicts = self.conflicts
+            .iter()
+            .filter(|conflict| [&conflict.first, &conflict.second]
+                .into_iter()
+                .any(|conflicting| conflicting.gattung == value.gattung && conflicting.nummer == value.nummer)
+            )
+            .map(|conflict| format!("conflict between {conflict}"));
+        ZugReport {
+            gattung: value.gattung.clone(),
+            nummer: value.nummer.clone(),
+            path: zug.path.full_path().to_owned(),
+            buchfahrplan: zug.buchfahrplan.as_ref().map(|buchfahrplan| buchfahrplan.path.full_path().to_owned()),
+            start: value.fahrplan_eintraege
+                .iter()
+                .find_map(|eintrag| eintrag.abfahrt.or(eintrag.ankunft).map(|time| ZugReportHalt {
+                    betriebsstelle: eintrag.betriebsstelle.clone(),
+                    time,
+                })),
+            end: value.fahrplan_eintraege
+                .iter()
+                .rev()
+                .find_map(|eintrag| eintrag.ankunft.or(eintrag.abfahrt).map(|time| ZugReportHalt {
+                    betriebsstelle: eintrag.betriebsstelle.clone(),
+                    time,
+                })),
+            origin: zug.origin.clone(),
+            warnings: collisions.chain(conflicts).collect(),
+        }
+    }
+}
+
+/// Formats in which the result of the generation can be reported
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+}
+
+/// Machine-readable summary of the generated Fahrplan
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GenerationReport {
+    pub fahrplan: PathBuf,
+    pub zuege: Vec<ZugReport>,
+
+    /// Error messages of the Zuege which couldn't be generated
+    pub failed_zuege: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ZugReport {
+    pub gattung: String,
+    pub nummer: String,
+    pub path: PathBuf,
+    pub buchfahrplan: Option<PathBuf>,
+
+    /// First FahrplanEintrag with a time, [None] if the Zug has no times
+    pub start: Option<ZugReportHalt>,
+
+    /// Last FahrplanEintrag with a time, [None] if the Zug has no times
+    pub end: Option<ZugReportHalt>,
+
+    pub origin: ZugOrigin,
+
+    /// Descriptions of the collisions and conflicts the Zug is involved in
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ZugReportHalt {
+    pub betriebsstelle: String,
+
+    #[serde(with = "date_time_format")]
+    pub time: PrimitiveDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRunReport {
+    pub files: Vec<PlannedFile>,
+
+    /// Zusi paths of the ZugDateiEintraege of the generated Fahrplan
+    pub zug_dateien: Vec<PathBuf>,
+
+    /// Error messages of the Zuege which couldn't be generated
+    pub failed_zuege: Vec<String>,
+
+    /// Descriptions of the Zuege which share the same Gattung and Zugnummer
+    pub zug_collisions: Vec<String>,
+
+    /// Descriptions of the conflicts between the Zuege
+    pub conflicts: Vec<String>,
+}
+
+impl DryRunReport {
+    /// Adds a file which is written in addition to the Fahrplan and its Zuege, like a report or an export.
+    pub fn add_file(&mut self, path: PathBuf) {
+        let exists = path.exists();
+        self.files.push(PlannedFile { path, exists });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFile {
+    pub path: PathBuf,
+
+    /// Whether the file already exists and would be overwritten
+    pub exists: bool,
+}
+
+impl Display for DryRunReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Dry run: no files were written.")?;
+        writeln!(f, "Files to write:")?;
+        for file in &self.files {
+            let action = if file.exists { "overwrite" } else { "create" };
+            writeln!(f, r#"  [{action}] "{}""#, file.path.display())?;
+        }
+        writeln!(f, "ZugDateiEintraege:")?;
+        for zug_datei in &self.zug_dateien {
+            writeln!(f, r#"  "{}""#, zug_datei.display())?;
+        }
+        if !self.failed_zuege.is_empty() {
+            writeln!(f, "Zuege which couldn't be generated:")?;
+            for error in &self.failed_zuege {
+                writeln!(f, "  {error}")?;
+            }
+        }
+        if !self.zug_collisions.is_empty() {
+            writeln!(f, "Colliding Zuege:")?;
+            for collision in &self.zug_collisions {
+                writeln!(f, "  {collision}")?;
+            }
+        }
+        if !self.conflicts.is_empty() {
+            writeln!(f, "Conflicts:")?;
+            for conflict in &self.conflicts {
+                writeln!(f, "  {conflict}")?;
+            }
+        }
+        let replaced: Vec<_> = self.files.iter().filter(|file| file.exists).collect();
+        if replaced.is_empty() {
+            writeln!(f, "Existing files which would be replaced: none")
+        } else {
+            writeln!(f, "Existing files which would be replaced:")?;
+            for file in replaced {
+                writeln!(f, r#"  "{}""#, file.path.display())?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -1,3 +1,8 @@
+use serde::Serialize;
+use serde_helpers::with::duration::duration_format;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use time::Duration;
 use zusi_xml_lib::xml::zusi::buchfahrplan::Buchfahrplan;
 use zusi_xml_lib::xml::zusi::info::{DateiTyp, Info};
 use zusi_xml_lib::xml::zusi::zug::Zug;
@@ -7,10 +12,11 @@ use zusi_xml_lib::xml::zusi::TypedZusi;
 pub struct GeneratedZug {
     pub zug: TypedZusi<Zug>,
     pub buchfahrplan: Option<TypedZusi<Buchfahrplan>>,
+    pub origin: ZugOrigin,
 }
 
-impl From<RawGeneratedZug> for GeneratedZug {
-    fn from(raw: RawGeneratedZug) -> Self {
+impl From<(RawGeneratedZug, ZugOrigin)> for GeneratedZug {
+    fn from((raw, origin): (RawGeneratedZug, ZugOrigin)) -> Self {
         Self {
             zug: TypedZusi::<Zug>::builder()
                 .info(Info::builder().datei_typ(DateiTyp::Zug).version("A.6".into()).min_version("A.6".into()).build())
@@ -22,10 +28,47 @@ impl From<RawGeneratedZug> for GeneratedZug {
                     .value(buchfahrplan)
                     .build()
             ),
+            origin,
         }
     }
 }
 
+/// Describes by which config a generated Zug was created
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ZugOrigin {
+    /// Zugnummer of the Zug config
+    pub config_nummer: String,
+
+    /// Only set if the Zug is a copy created by a CopyDelayTask
+    pub copy_delay: Option<CopyDelayOrigin>,
+
+    /// Path of the rolling stock as given in the config, custom rolling stock of the CopyDelayTask takes precedence
+    pub rolling_stock: PathBuf,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CopyDelayOrigin {
+    /// Position of the CopyDelayTask in the config, starting at 1
+    pub task: usize,
+
+    /// Number of the copy created by the task, starting at 1
+    pub copy: u32,
+
+    #[serde(with = "duration_format")]
+    pub delay: Duration,
+    pub increment: i32,
+}
+
+impl Display for ZugOrigin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Zug config '{}'", self.config_nummer)?;
+        if let Some(CopyDelayOrigin { task, copy, delay, increment }) = &self.copy_delay {
+            write!(f, " (CopyDelayTask {task}, copy {copy}, delay {delay}, increment {increment:+})")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct RawGeneratedZug {
     pub zug: Zug,
@@ -0,0 +1,223 @@
+use crate::core::lib::generated_zug::RawGeneratedZug;
+use crate::input::copy_delay_config::{CopyDelayConfig, CopyDelayTask};
+use crate::input::takt_config::TaktConfig;
+use thiserror::Error;
+use time::{Duration, Time};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TaktError {
+    #[error("All intervals of the Takt must be positive.")]
+    InvalidInterval,
+
+    #[error("The Zug has no Abfahrt the Takt could be aligned to.")]
+    NoAbfahrt,
+
+    #[error("The first Abfahrt of the Zug at {abfahrt} is no departure of the Takt.")]
+    AbfahrtNotInTakt {
+        abfahrt: Time,
+    },
+
+    #[error("Takt and CopyDelay can't be used for the same Zug.")]
+    CombinedWithCopyDelay,
+}
+
+/// Converts the Takt into a copy delay config with one CopyDelayTask per departure.
+/// The Zug itself is kept as it is, so the departure matching its first Abfahrt is skipped.
+/// The Zugnummern follow the departures, so earlier departures get lower Zugnummern than the Zug.
+/// Fails if no departure matches the first Abfahrt, as the Zug would run off the Takt.
+pub fn takt_to_copy_delay_config(config: &TaktConfig, zug: &RawGeneratedZug) -> Result<CopyDelayConfig, TaktError> {
+    let first_abfahrt = zug.zug.fahrplan_eintraege
+        .iter()
+        .find_map(|eintrag| eintrag.abfahrt)
+        .ok_or(TaktError::NoAbfahrt)?;
+    let midnight = first_abfahrt.date().midnight();
+    let delays: Vec<Duration> = takt_departures(config)?
+        .into_iter()
+        .map(|departure| midnight + departure - first_abfahrt)
+        .collect();
+    let own_index = delays
+        .iter()
+        .position(|delay| delay.is_zero())
+        .ok_or(TaktError::AbfahrtNotInTakt { abfahrt: first_abfahrt.time() })?;
+
+    Ok(CopyDelayConfig {
+        tasks: delays
+            .into_iter()
+            .zip(-(own_index as i32)..)
+            .filter(|(delay, _)| !delay.is_zero())
+            .map(|(delay, n)| CopyDelayTask {
+                delay,
+                first_delay: None,
+                increment: n * config.increment,
+                first_increment: None,
+                count: 1,
+                custom_rolling_stock: None,
+            })
+            .collect(),
+    })
+}
+
+pub fn validate_takt(config: &TaktConfig) -> Result<(), TaktError> {
+    let intervals = config.windows.iter().filter_map(|window| window.interval);
+    if [config.interval].into_iter().chain(intervals).any(|interval| !interval.is_positive()) {
+        Err(TaktError::InvalidInterval)
+    } else {
+        Ok(())
+    }
+}
+
+/// All departures from start to end. Within a window its interval is used, or no departures if it has none.
+fn takt_departures(config: &TaktConfig) -> Result<Vec<Duration>, TaktError> {
+    validate_takt(config)?;
+
+    let mut departures = vec![];
+    let mut departure = config.start;
+    while departure <= config.end {
+        let window = config.windows
+            .iter()
+            .find(|window| window.from <= departure && departure < window.to);
+        match window {
+            Some(window) if window.interval.is_none() => departure = window.to,
+            _ => {
+                departures.push(departure);
+                departure += window.and_then(|window| window.interval).unwrap_or(config.interval);
+            },
+        }
+    }
+    Ok(departures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::takt_config::TaktWindow;
+    use time::macros::{datetime, time};
+    use time::PrimitiveDateTime;
+    use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+    use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
+    use zusi_xml_lib::xml::zusi::zug::fahrzeug_varianten::FahrzeugVarianten;
+    use zusi_xml_lib::xml::zusi::zug::Zug;
+
+    fn takt_config() -> TaktConfig {
+        TaktConfig {
+            start: Duration::hours(5),
+            end: Duration::hours(9),
+            interval: Duration::hours(1),
+            increment: 2,
+            windows: vec![
+                TaktWindow {
+                    from: Duration::hours(6),
+                    to: Duration::hours(7),
+                    interval: Some(Duration::minutes(30)),
+                },
+                TaktWindow {
+                    from: Duration::hours(7) + Duration::minutes(30),
+                    to: Duration::hours(9),
+                    interval: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_takt_departures() {
+        assert_eq!(
+            takt_departures(&takt_config()).unwrap(),
+            vec![
+                Duration::hours(5),
+                Duration::hours(6),
+                Duration::hours(6) + Duration::minutes(30),
+                Duration::hours(7),
+                Duration::hours(9),
+            ],
+        );
+    }
+
+    fn zug(first_abfahrt: PrimitiveDateTime) -> RawGeneratedZug {
+        RawGeneratedZug {
+            zug: Zug::builder()
+                .fahrplan_datei(Datei::builder().build())
+                .fahrzeug_varianten(FahrzeugVarianten::builder().build())
+                .fahrplan_eintraege(vec![
+                    FahrplanEintrag::builder().betriebsstelle("A".into()).build(),
+                    FahrplanEintrag::builder().betriebsstelle("A".into()).abfahrt(Some(first_abfahrt)).build(),
+                    FahrplanEintrag::builder().betriebsstelle("B".into()).abfahrt(Some(first_abfahrt + Duration::minutes(10))).build(),
+                ])
+                .build(),
+            buchfahrplan: None,
+        }
+    }
+
+    #[test]
+    fn test_takt_to_copy_delay_config() {
+        let zug = zug(datetime!(2024-06-20 05:00:00));
+
+        let task = |delay: Duration, increment: i32| CopyDelayTask {
+            delay,
+            first_delay: None,
+            increment,
+            first_increment: None,
+            count: 1,
+            custom_rolling_stock: None,
+        };
+
+        assert_eq!(
+            takt_to_copy_delay_config(&takt_config(), &zug).unwrap(),
+            CopyDelayConfig {
+                tasks: vec![
+                    task(Duration::hours(1), 2),
+                    task(Duration::minutes(90), 4),
+                    task(Duration::hours(2), 6),
+                    task(Duration::hours(4), 8),
+                ],
+            },
+        );
+    }
+
+    #[test]
+    fn test_takt_to_copy_delay_config_zug_within_takt() {
+        let zug = zug(datetime!(2024-06-20 06:30:00));
+
+        let task = |delay: Duration, increment: i32| CopyDelayTask {
+            delay,
+            first_delay: None,
+            increment,
+            first_increment: None,
+            count: 1,
+            custom_rolling_stock: None,
+        };
+
+        assert_eq!(
+            takt_to_copy_delay_config(&takt_config(), &zug).unwrap(),
+            CopyDelayConfig {
+                tasks: vec![
+                    task(-Duration::minutes(90), -4),
+                    task(-Duration::minutes(30), -2),
+                    task(Duration::minutes(30), 2),
+                    task(Duration::minutes(150), 4),
+                ],
+            },
+        );
+    }
+
+    #[test]
+    fn test_takt_to_copy_delay_config_off_takt() {
+        assert_eq!(
+            takt_to_copy_delay_config(&takt_config(), &zug(datetime!(2024-06-20 05:02:00))),
+            Err(TaktError::AbfahrtNotInTakt { abfahrt: time!(05:02:00) }),
+        );
+        assert_eq!(
+            takt_to_copy_delay_config(&takt_config(), &zug(datetime!(2024-06-20 10:00:00))),
+            Err(TaktError::AbfahrtNotInTakt { abfahrt: time!(10:00:00) }),
+        );
+    }
+
+    #[test]
+    fn test_validate_takt() {
+        let mut config = takt_config();
+        assert_eq!(validate_takt(&config), Ok(()));
+
+        config.windows[0].interval = Some(Duration::ZERO);
+        assert_eq!(validate_takt(&config), Err(TaktError::InvalidInterval));
+    }
+}
\ No newline at end of file
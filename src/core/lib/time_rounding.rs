@@ -0,0 +1,135 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use time::{Duration, PrimitiveDateTime};
+
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingGranularity {
+    Minute,
+    HalfMinute,
+}
+
+impl RoundingGranularity {
+    fn seconds(&self) -> i64 {
+        match self {
+            RoundingGranularity::Minute => 60,
+            RoundingGranularity::HalfMinute => 30,
+        }
+    }
+
+    /// Smallest non-zero duration which is a multiple of the granularity
+    pub fn step(&self) -> Duration {
+        Duration::seconds(self.seconds())
+    }
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingDirection {
+    Up,
+    Down,
+
+    #[default]
+    Nearest,
+}
+
+/// Rounds the given duration to a multiple of the granularity, sub-second parts are dropped.
+/// Nearest rounds halfway values up.
+pub fn round_duration(duration: Duration, granularity: RoundingGranularity, direction: RoundingDirection) -> Duration {
+    let step = granularity.seconds();
+    let seconds = duration.whole_seconds();
+    let down = seconds.div_euclid(step) * step;
+    let rounded = match direction {
+        RoundingDirection::Down => down,
+        RoundingDirection::Up if down == seconds => down,
+        RoundingDirection::Up => down + step,
+        RoundingDirection::Nearest if (seconds - down) * 2 >= step => down + step,
+        RoundingDirection::Nearest => down,
+    };
+    Duration::seconds(rounded)
+}
+
+pub fn round_date_time(date_time: PrimitiveDateTime, granularity: RoundingGranularity, direction: RoundingDirection) -> PrimitiveDateTime {
+    let midnight = date_time.date().midnight();
+    midnight + round_duration(date_time - midnight, granularity, direction)
+}
+
+/// Rounds Ankunft and Abfahrt of a single stop or pass.
+/// If rounding would make the stop shorter than it was before, Abfahrt is rounded up instead.
+pub fn round_stop(
+    ankunft: Option<PrimitiveDateTime>,
+    abfahrt: Option<PrimitiveDateTime>,
+    granularity: RoundingGranularity,
+    direction: RoundingDirection,
+) -> (Option<PrimitiveDateTime>, Option<PrimitiveDateTime>) {
+    let rounded_ankunft = ankunft.map(|ankunft| round_date_time(ankunft, granularity, direction));
+    let rounded_abfahrt = match (ankunft, rounded_ankunft, abfahrt) {
+        (Some(ankunft), Some(rounded_ankunft), Some(abfahrt)) => {
+            let min_abfahrt = rounded_ankunft + (abfahrt - ankunft);
+            let rounded_abfahrt = round_date_time(abfahrt, granularity, direction);
+            if rounded_abfahrt < min_abfahrt {
+                Some(round_date_time(min_abfahrt, granularity, RoundingDirection::Up))
+            } else {
+                Some(rounded_abfahrt)
+            }
+        },
+        (_, _, abfahrt) => abfahrt.map(|abfahrt| round_date_time(abfahrt, granularity, direction)),
+    };
+    (rounded_ankunft, rounded_abfahrt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_round_duration() {
+        let duration = Duration::minutes(3) + Duration::seconds(37);
+
+        assert_eq!(round_duration(duration, RoundingGranularity::Minute, RoundingDirection::Up), Duration::minutes(4));
+        assert_eq!(round_duration(duration, RoundingGranularity::Minute, RoundingDirection::Down), Duration::minutes(3));
+        assert_eq!(round_duration(duration, RoundingGranularity::Minute, RoundingDirection::Nearest), Duration::minutes(4));
+        assert_eq!(round_duration(duration, RoundingGranularity::HalfMinute, RoundingDirection::Up), Duration::minutes(4));
+        assert_eq!(round_duration(duration, RoundingGranularity::HalfMinute, RoundingDirection::Down), Duration::seconds(210));
+        assert_eq!(round_duration(duration, RoundingGranularity::HalfMinute, RoundingDirection::Nearest), Duration::seconds(210));
+        assert_eq!(round_duration(Duration::seconds(45), RoundingGranularity::HalfMinute, RoundingDirection::Nearest), Duration::minutes(1));
+        assert_eq!(round_duration(Duration::minutes(2), RoundingGranularity::Minute, RoundingDirection::Up), Duration::minutes(2));
+    }
+
+    #[test]
+    fn test_round_date_time() {
+        assert_eq!(
+            round_date_time(datetime!(2024-06-20 23:59:37), RoundingGranularity::Minute, RoundingDirection::Nearest),
+            datetime!(2024-06-21 00:00:00),
+        );
+        assert_eq!(
+            round_date_time(datetime!(2024-06-20 08:41:40), RoundingGranularity::HalfMinute, RoundingDirection::Down),
+            datetime!(2024-06-20 08:41:30),
+        );
+    }
+
+    #[test]
+    fn test_round_stop_keeps_stop_time() {
+        assert_eq!(
+            round_stop(
+                Some(datetime!(2024-06-20 08:39:20)),
+                Some(datetime!(2024-06-20 08:40:05)),
+                RoundingGranularity::Minute,
+                RoundingDirection::Nearest,
+            ),
+            (Some(datetime!(2024-06-20 08:39:00)), Some(datetime!(2024-06-20 08:40:00))),
+        );
+        assert_eq!(
+            round_stop(
+                Some(datetime!(2024-06-20 08:39:40)),
+                Some(datetime!(2024-06-20 08:40:20)),
+                RoundingGranularity::Minute,
+                RoundingDirection::Nearest,
+            ),
+            (Some(datetime!(2024-06-20 08:40:00)), Some(datetime!(2024-06-20 08:41:00))),
+        );
+        assert_eq!(
+            round_stop(None, Some(datetime!(2024-06-20 08:45:10)), RoundingGranularity::HalfMinute, RoundingDirection::Up),
+            (None, Some(datetime!(2024-06-20 08:45:30))),
+        );
+    }
+}
\ No newline at end of file
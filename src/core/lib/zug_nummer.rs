@@ -41,6 +41,17 @@ impl ZugNummer {
             .join(MULTI_NUMMER_SEPARATOR)
     }
 
+    /// Like [ZugNummer::to_string] but pads each part with leading zeros to the width of the corresponding part of `original`.
+    pub fn to_string_with_width_of(&self, original: &str) -> String {
+        let widths: Vec<usize> = original.split(MULTI_NUMMER_SEPARATOR).map(|part| part.len()).collect();
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, nummer)| format!("{nummer:0width$}", width = widths.get(index).copied().unwrap_or(0)))
+            .collect::<Vec<_>>()
+            .join(MULTI_NUMMER_SEPARATOR)
+    }
+
     pub fn to_new_incremented(&self, increment: i32) -> Result<Self, TryFromIntError> {
         let mut new = self.clone();
         new.increment(increment)?;
@@ -114,6 +125,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_zug_nummer_to_string_with_width_of() {
+        assert_eq!(
+            ZugNummer(vec![1235]).to_string_with_width_of("01234"),
+            String::from("01235"),
+        );
+        assert_eq!(
+            ZugNummer(vec![4, 100, 23]).to_string_with_width_of("03_99_022"),
+            String::from("04_100_023"),
+        );
+    }
+
     #[test]
     fn test_increment_zug_nummer() {
         assert_eq!(
@@ -79,6 +79,22 @@ pub fn replace_rolling_stock(env: &ZusiEnvironment, config: RollingStockConfig,
     Ok(())
 }
 
+/// Reads the rolling stock template and its Buchfahrplan (if referenced) without applying them to any Zug.
+pub fn validate_rolling_stock(env: &ZusiEnvironment, config: &RollingStockConfig) -> Result<(), ReplaceRollingStockError> {
+    let rolling_stock_template_path = env.path_to_prejoined_zusi_path(&config.path)
+        .map_err(|error| ReplaceRollingStockError::ReadRollingStockError { error })?;
+    let rolling_stock_template = read_zug(rolling_stock_template_path.full_path())
+        .map_err(|error| ReplaceRollingStockError::ReadRollingStockError { error })?.value;
+
+    if let Some(Datei { dateiname, .. }) = rolling_stock_template.buchfahrplan_roh_datei {
+        let buchfahrplan_path = env.zusi_path_to_prejoined_zusi_path(dateiname);
+        read_buchfahrplan(buchfahrplan_path.full_path())
+            .map_err(|error| ReplaceRollingStockError::ReadBuchfahrplanError { error })?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
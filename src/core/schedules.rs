@@ -1,4 +1,6 @@
 pub mod apply;
+pub mod combine;
 pub mod prepare_entries;
 pub mod generate;
-pub mod update_buchfahrplan;
\ No newline at end of file
+pub mod update_buchfahrplan;
+pub mod transform;
\ No newline at end of file
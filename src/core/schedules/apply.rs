@@ -128,30 +128,40 @@ mod tests {
                     driving_time: Duration::minutes(0),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "C".into(),
                     driving_time: Duration::minutes(3),
                     stop_time: Some(Duration::seconds(20)),
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "D".into(),
                     driving_time: Duration::minutes(5),
                     stop_time: None,
                     time_fix: Some(TimeFix::Ankunft),
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "E".into(),
                     driving_time: Duration::minutes(3),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "F".into(),
                     driving_time: Duration::minutes(4),
                     stop_time: Some(Duration::seconds(40)),
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
             ],
         };
@@ -0,0 +1,175 @@
+use crate::core::lib::longest_common_coherent_subsequence::longest_common_coherent_subsequence;
+use crate::input::schedule::{Schedule, ScheduleEntry};
+use clap::ValueEnum;
+use thiserror::Error;
+use time::Duration;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CombineSchedulesError {
+    #[error("No schedules to combine.")]
+    NoSchedules,
+
+    #[error("The schedules don't have any Betriebsstellen in common.")]
+    NoCommonSection,
+}
+
+/// How the times of multiple schedules are combined per section
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CombineMethod {
+    Min,
+    Max,
+    Median,
+    Mean,
+}
+
+impl CombineMethod {
+    fn combine(&self, mut durations: Vec<Duration>) -> Duration {
+        durations.sort();
+        let len = durations.len();
+        match self {
+            CombineMethod::Min => durations[0],
+            CombineMethod::Max => durations[len - 1],
+            CombineMethod::Median if len % 2 == 0 => mean(&durations[len / 2 - 1..=len / 2]),
+            CombineMethod::Median => durations[len / 2],
+            CombineMethod::Mean => mean(&durations),
+        }
+    }
+}
+
+/// Rounded to whole seconds.
+fn mean(durations: &[Duration]) -> Duration {
+    let sum: i64 = durations.iter().map(|duration| duration.whole_seconds()).sum();
+    Duration::seconds((sum as f64 / durations.len() as f64).round() as i64)
+}
+
+/// Combines the schedules of multiple Zuege of the same line into a single schedule.
+/// The schedules are aligned by their Betriebsstellen using the longest common coherent subsequence,
+/// only the section contained in all schedules is kept. Entries without a stop time are treated as
+/// stops of zero seconds as long as at least one of the aligned entries has a stop time.
+pub fn combine_schedules(schedules: &[Schedule], method: CombineMethod) -> Result<Schedule, CombineSchedulesError> {
+    let (reference, others) = schedules.split_first().ok_or(CombineSchedulesError::NoSchedules)?;
+    let betriebsstellen = |schedule: &Schedule| -> Vec<String> {
+        schedule.entries.iter().map(|entry| entry.betriebsstelle.clone()).collect()
+    };
+    let reference_betriebsstellen = betriebsstellen(reference);
+
+    // offsets of each schedule's entries relative to the entries of the reference schedule
+    let mut section_start = 0;
+    let mut section_len = reference.entries.len();
+    let mut offsets: Vec<isize> = vec![0];
+    for other in others {
+        let lccs = longest_common_coherent_subsequence(
+            &reference_betriebsstellen[section_start..section_start + section_len],
+            betriebsstellen(other),
+        );
+        section_start += lccs.sec1_start;
+        section_len = lccs.len;
+        offsets.push(lccs.sec2_start as isize - section_start as isize);
+    }
+
+    if section_len == 0 {
+        return Err(CombineSchedulesError::NoCommonSection);
+    }
+
+    let entries = (section_start..section_start + section_len)
+        .map(|index| {
+            let aligned: Vec<&ScheduleEntry> = schedules
+                .iter()
+                .zip(offsets.iter())
+                .map(|(schedule, offset)| &schedule.entries[(index as isize + offset) as usize])
+                .collect();
+            let reference_entry = aligned[0];
+            ScheduleEntry {
+                betriebsstelle: reference_entry.betriebsstelle.clone(),
+                driving_time: if index == section_start {
+                    Duration::seconds(0)
+                } else {
+                    method.combine(aligned.iter().map(|entry| entry.driving_time).collect())
+                },
+                stop_time: if aligned.iter().any(|entry| entry.stop_time.is_some()) {
+                    Some(method.combine(aligned.iter().map(|entry| entry.stop_time.unwrap_or(Duration::ZERO)).collect()))
+                } else {
+                    None
+                },
+                time_fix: None,
+                km: reference_entry.km,
+                laufweg: reference_entry.laufweg,
+            }
+        })
+        .collect();
+
+    Ok(Schedule { entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(entries: Vec<(&str, i64, Option<i64>)>) -> Schedule {
+        Schedule {
+            entries: entries
+                .into_iter()
+                .map(|(betriebsstelle, driving_time, stop_time)| ScheduleEntry {
+                    betriebsstelle: betriebsstelle.into(),
+                    driving_time: Duration::seconds(driving_time),
+                    stop_time: stop_time.map(Duration::seconds),
+                    time_fix: None,
+                    km: None,
+                    laufweg: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn schedules() -> Vec<Schedule> {
+        vec![
+            schedule(vec![("A", 0, Some(60)), ("B", 180, None), ("C", 120, Some(30)), ("D", 240, Some(40))]),
+            schedule(vec![("X", 0, None), ("A", 100, Some(20)), ("B", 200, None), ("C", 140, None), ("D", 200, Some(60))]),
+            schedule(vec![("A", 0, None), ("B", 170, None), ("C", 110, Some(50)), ("D", 250, Some(40)), ("E", 60, None)]),
+        ]
+    }
+
+    #[test]
+    fn test_combine_schedules() {
+        assert_eq!(
+            combine_schedules(&schedules(), CombineMethod::Min).unwrap(),
+            schedule(vec![("A", 0, Some(0)), ("B", 170, None), ("C", 110, Some(0)), ("D", 200, Some(40))]),
+        );
+        assert_eq!(
+            combine_schedules(&schedules(), CombineMethod::Max).unwrap(),
+            schedule(vec![("A", 0, Some(60)), ("B", 200, None), ("C", 140, Some(50)), ("D", 250, Some(60))]),
+        );
+        assert_eq!(
+            combine_schedules(&schedules(), CombineMethod::Median).unwrap(),
+            schedule(vec![("A", 0, Some(20)), ("B", 180, None), ("C", 120, Some(30)), ("D", 240, Some(40))]),
+        );
+        assert_eq!(
+            combine_schedules(&schedules(), CombineMethod::Mean).unwrap(),
+            schedule(vec![("A", 0, Some(27)), ("B", 183, None), ("C", 123, Some(27)), ("D", 230, Some(47))]),
+        );
+    }
+
+    #[test]
+    fn test_combine_schedules_with_partial_overlap() {
+        let schedules = vec![
+            schedule(vec![("A", 0, None), ("B", 180, Some(30)), ("C", 120, None)]),
+            schedule(vec![("B", 0, Some(40)), ("C", 100, None), ("D", 60, None)]),
+        ];
+
+        assert_eq!(
+            combine_schedules(&schedules, CombineMethod::Median).unwrap(),
+            schedule(vec![("B", 0, Some(35)), ("C", 110, None)]),
+        );
+    }
+
+    #[test]
+    fn test_combine_schedules_without_common_section() {
+        let schedules = vec![
+            schedule(vec![("A", 0, None), ("B", 180, None)]),
+            schedule(vec![("C", 0, None), ("D", 100, None)]),
+        ];
+
+        assert_eq!(combine_schedules(&schedules, CombineMethod::Mean).unwrap_err(), CombineSchedulesError::NoCommonSection);
+        assert_eq!(combine_schedules(&[], CombineMethod::Mean).unwrap_err(), CombineSchedulesError::NoSchedules);
+    }
+}
\ No newline at end of file
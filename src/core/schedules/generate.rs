@@ -1,5 +1,9 @@
 use crate::input::schedule::{Schedule, ScheduleEntry};
 use time::{Duration, PrimitiveDateTime};
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_abfahrt::FahrplanAbfahrt;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_ankunft::FahrplanAnkunft;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_name::FahrplanName;
+use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::FahrplanZeile;
 use zusi_xml_lib::xml::zusi::zug::fahrplan_eintrag::FahrplanEintrag;
 
 struct GenerateScheduleState {
@@ -22,25 +26,85 @@ impl From<GenerateScheduleState> for Vec<ScheduleEntry> {
     }
 }
 
+/// Times of a single stop or pass which are relevant to generate a schedule
+struct ScheduleTimes {
+    betriebsstelle: String,
+    ankunft: Option<PrimitiveDateTime>,
+    abfahrt: PrimitiveDateTime,
+    km: Option<f32>,
+    laufweg: Option<f32>,
+}
+
 pub fn generate_schedule(fahrplan_eintraege: &Vec<FahrplanEintrag>) -> Schedule {
-    Schedule {
-        entries: fahrplan_eintraege
+    generate_schedule_from_times(
+        fahrplan_eintraege
+            .iter()
+            .filter_map(|eintrag| eintrag.abfahrt.map(|abfahrt| ScheduleTimes {
+                betriebsstelle: eintrag.betriebsstelle.clone(),
+                ankunft: eintrag.ankunft,
+                abfahrt,
+                km: None,
+                laufweg: None,
+            }))
+    )
+}
+
+/// Generates a schedule based on all FahrplanZeilen with FplName and FplAbf.
+/// Like in [generate_schedule], FahrplanZeilen with only FplAnk are skipped.
+/// Duplicate FahrplanZeilen for Regelgleis and Gegengleis are only considered once.
+/// If `with_extra_data` is set, km and FplLaufweg are added to the entries.
+pub fn generate_schedule_from_buchfahrplan(fahrplan_zeilen: &[FahrplanZeile], with_extra_data: bool) -> Schedule {
+    let mut previous: Option<&FahrplanZeile> = None;
+    generate_schedule_from_times(
+        fahrplan_zeilen
             .iter()
-            .filter(|eintrag| eintrag.abfahrt.is_some())
-            .fold(GenerateScheduleState::new(), |mut state, eintrag| {
-                let abfahrt = eintrag.abfahrt.unwrap(); // abfahrt is always Some due to filter
-                let ankunft = eintrag.ankunft.unwrap_or(abfahrt);
+            .filter(|zeile| zeile.fahrplan_abfahrt.is_some())
+            .filter(|zeile| {
+                let is_duplicate = previous.is_some_and(|previous|
+                    previous.fahrplan_name == zeile.fahrplan_name &&
+                        previous.fahrplan_ankunft == zeile.fahrplan_ankunft &&
+                        previous.fahrplan_abfahrt == zeile.fahrplan_abfahrt
+                );
+                previous = Some(zeile);
+                !is_duplicate
+            })
+            .filter_map(|zeile| match zeile {
+                FahrplanZeile {
+                    fahrplan_name: Some(FahrplanName { fahrplan_name_text, .. }),
+                    fahrplan_abfahrt: Some(FahrplanAbfahrt { abfahrt, .. }),
+                    ..
+                } => {
+                    Some(ScheduleTimes {
+                        betriebsstelle: fahrplan_name_text.clone(),
+                        ankunft: zeile.fahrplan_ankunft.as_ref().map(|FahrplanAnkunft { ankunft, .. }| *ankunft),
+                        abfahrt: *abfahrt,
+                        km: zeile.fahrplan_km.first().map(|fahrplan_km| fahrplan_km.km).filter(|_| with_extra_data),
+                        laufweg: Some(zeile.fahrplan_laufweg).filter(|_| with_extra_data),
+                    })
+                },
+                _ => None,
+            })
+    )
+}
+
+fn generate_schedule_from_times(times: impl Iterator<Item = ScheduleTimes>) -> Schedule {
+    Schedule {
+        entries: times
+            .fold(GenerateScheduleState::new(), |mut state, times| {
+                let ankunft = times.ankunft.unwrap_or(times.abfahrt);
                 let driving_time = match state.previous_abfahrt {
                     None => Duration::seconds(0),
                     Some(previous_abfahrt) => ankunft - previous_abfahrt,
                 };
                 state.entries.push(ScheduleEntry {
-                    betriebsstelle: eintrag.betriebsstelle.clone(),
+                    betriebsstelle: times.betriebsstelle,
                     driving_time,
-                    stop_time: if eintrag.ankunft.is_some() { Some(abfahrt - ankunft) } else { None },
+                    stop_time: if times.ankunft.is_some() { Some(times.abfahrt - ankunft) } else { None },
                     time_fix: None,
+                    km: times.km,
+                    laufweg: times.laufweg,
                 });
-                state.previous_abfahrt = Some(abfahrt);
+                state.previous_abfahrt = Some(times.abfahrt);
                 state
             })
             .into(),
@@ -51,6 +115,8 @@ pub fn generate_schedule(fahrplan_eintraege: &Vec<FahrplanEintrag>) -> Schedule
 mod tests {
     use super::*;
     use time::macros::datetime;
+    use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_km::FahrplanKm;
+    use zusi_xml_lib::xml::zusi::buchfahrplan::fahrplan_zeile::fahrplan_v_max::FahrplanVMax;
 
     #[test]
     fn test_generate_schedule() {
@@ -78,46 +144,152 @@ mod tests {
                     driving_time: Duration::seconds(0),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "B".into(),
                     driving_time: Duration::minutes(3),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "C".into(),
                     driving_time: Duration::minutes(2),
                     stop_time: Some(Duration::seconds(10)),
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "D".into(),
                     driving_time: Duration::minutes(3) + Duration::seconds(50),
                     stop_time: Some(Duration::minutes(1)),
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "E".into(),
                     driving_time: Duration::minutes(0),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "F".into(),
                     driving_time: Duration::minutes(4),
                     stop_time: Some(Duration::seconds(20)),
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "G".into(),
                     driving_time: Duration::minutes(2),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
             ],
         };
 
         assert_eq!(generate_schedule(&input), expected);
     }
+
+    fn fahrplan_zeilen() -> Vec<FahrplanZeile> {
+        vec![
+            FahrplanZeile::builder()
+                .fahrplan_laufweg(20092.018)
+                .fahrplan_km(vec![FahrplanKm::builder().km(32.8757).build()])
+                .fahrplan_name(Some(FahrplanName::builder().fahrplan_name_text("Elze".into()).build()))
+                .fahrplan_ankunft(Some(FahrplanAnkunft::builder().ankunft(datetime!(2024-06-20 08:39:00)).build()))
+                .fahrplan_abfahrt(Some(FahrplanAbfahrt::builder().abfahrt(datetime!(2024-06-20 08:41:40)).build()))
+                .build(),
+            FahrplanZeile::builder()
+                .fahrplan_regelgleis_gegengleis(1)
+                .fahrplan_laufweg(21799.445)
+                .fahrplan_v_max(Some(FahrplanVMax::builder().v_max(33.3333).build()))
+                .fahrplan_km(vec![FahrplanKm::builder().km(1.7792).build()])
+                .build(),
+            FahrplanZeile::builder()
+                .fahrplan_regelgleis_gegengleis(1)
+                .fahrplan_laufweg(24631.027)
+                .fahrplan_km(vec![FahrplanKm::builder().km(4.5357).build()])
+                .fahrplan_name(Some(FahrplanName::builder().fahrplan_name_text("Mehle Hp".into()).build()))
+                .fahrplan_abfahrt(Some(FahrplanAbfahrt::builder().abfahrt(datetime!(2024-06-20 08:45:00)).build()))
+                .build(),
+            FahrplanZeile::builder()
+                .fahrplan_regelgleis_gegengleis(2)
+                .fahrplan_laufweg(24631.027)
+                .fahrplan_km(vec![FahrplanKm::builder().km(4.5357).build()])
+                .fahrplan_name(Some(FahrplanName::builder().fahrplan_name_text("Mehle Hp".into()).build()))
+                .fahrplan_abfahrt(Some(FahrplanAbfahrt::builder().abfahrt(datetime!(2024-06-20 08:45:00)).build()))
+                .build(),
+            FahrplanZeile::builder()
+                .fahrplan_regelgleis_gegengleis(1)
+                .fahrplan_laufweg(29134.139)
+                .fahrplan_km(vec![FahrplanKm::builder().km(9.0405).build()])
+                .fahrplan_name(Some(FahrplanName::builder().fahrplan_name_text("Osterwald Hp".into()).build()))
+                .fahrplan_ankunft(Some(FahrplanAnkunft::builder().ankunft(datetime!(2024-06-20 08:48:00)).build()))
+                .build(),
+        ]
+    }
+
+    #[test]
+    fn test_generate_schedule_from_buchfahrplan() {
+        let expected = Schedule {
+            entries: vec![
+                ScheduleEntry {
+                    betriebsstelle: "Elze".into(),
+                    driving_time: Duration::seconds(0),
+                    stop_time: Some(Duration::minutes(2) + Duration::seconds(40)),
+                    time_fix: None,
+                    km: None,
+                    laufweg: None,
+                },
+                ScheduleEntry {
+                    betriebsstelle: "Mehle Hp".into(),
+                    driving_time: Duration::minutes(3) + Duration::seconds(20),
+                    stop_time: None,
+                    time_fix: None,
+                    km: None,
+                    laufweg: None,
+                },
+            ],
+        };
+
+        assert_eq!(generate_schedule_from_buchfahrplan(&fahrplan_zeilen(), false), expected);
+    }
+
+    #[test]
+    fn test_generate_schedule_from_buchfahrplan_equals_trn() {
+        let fahrplan_eintraege = vec![
+            FahrplanEintrag::builder().betriebsstelle("Elze".into()).ankunft(Some(datetime!(2024-06-20 08:39:00))).abfahrt(Some(datetime!(2024-06-20 08:41:40))).build(),
+            FahrplanEintrag::builder().betriebsstelle("Mehle Hp".into()).abfahrt(Some(datetime!(2024-06-20 08:45:00))).build(),
+            FahrplanEintrag::builder().betriebsstelle("Osterwald Hp".into()).ankunft(Some(datetime!(2024-06-20 08:48:00))).build(),
+        ];
+
+        assert_eq!(
+            generate_schedule_from_buchfahrplan(&fahrplan_zeilen(), false),
+            generate_schedule(&fahrplan_eintraege),
+        );
+    }
+
+    #[test]
+    fn test_generate_schedule_from_buchfahrplan_with_extra_data() {
+        let schedule = generate_schedule_from_buchfahrplan(&fahrplan_zeilen(), true);
+
+        assert_eq!(
+            schedule.entries.iter().map(|entry| (entry.km, entry.laufweg)).collect::<Vec<_>>(),
+            vec![
+                (Some(32.8757), Some(20092.018)),
+                (Some(4.5357), Some(24631.027)),
+            ],
+        );
+    }
 }
\ No newline at end of file
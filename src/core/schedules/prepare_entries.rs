@@ -67,24 +67,32 @@ mod tests {
                     driving_time: Duration::minutes(0),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "C".into(),
                     driving_time: Duration::minutes(3),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "D".into(),
                     driving_time: Duration::minutes(5),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "E".into(),
                     driving_time: Duration::minutes(7),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
             ],
         };
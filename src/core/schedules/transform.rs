@@ -0,0 +1,179 @@
+use crate::core::lib::time_rounding::{round_duration, RoundingDirection, RoundingGranularity};
+use crate::input::schedule::{Schedule, ScheduleEntry};
+use time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecoveryMargin {
+    /// Added to the driving time of every section
+    Fixed(Duration),
+
+    /// Percentage of the (scaled) driving time of every section
+    Percentage(f64),
+}
+
+/// Changes applied to every entry of a schedule, in the order of the fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleTransformation {
+    pub driving_time_factor: f64,
+    pub recovery_margin: Option<RecoveryMargin>,
+    pub min_stop_time: Option<Duration>,
+    pub round_to: Option<RoundingGranularity>,
+}
+
+impl Default for ScheduleTransformation {
+    fn default() -> Self {
+        Self {
+            driving_time_factor: 1.,
+            recovery_margin: None,
+            min_stop_time: None,
+            round_to: None,
+        }
+    }
+}
+
+/// Creates a new schedule with transformed driving and stop times.
+/// Sections without driving time, like the first entry, are left unchanged.
+pub fn transform_schedule(schedule: &Schedule, transformation: &ScheduleTransformation) -> Schedule {
+    Schedule {
+        entries: schedule.entries
+            .iter()
+            .map(|entry| transform_entry(entry, transformation))
+            .collect(),
+    }
+}
+
+fn transform_entry(entry: &ScheduleEntry, transformation: &ScheduleTransformation) -> ScheduleEntry {
+    let mut driving_time = entry.driving_time;
+    if !driving_time.is_zero() {
+        driving_time = driving_time * transformation.driving_time_factor;
+        driving_time += match transformation.recovery_margin {
+            None => Duration::ZERO,
+            Some(RecoveryMargin::Fixed(margin)) => margin,
+            Some(RecoveryMargin::Percentage(percentage)) => driving_time * (percentage / 100.),
+        };
+    }
+
+    let stop_time = entry.stop_time.map(|stop_time| match transformation.min_stop_time {
+        Some(min_stop_time) if stop_time < min_stop_time => min_stop_time,
+        _ => stop_time,
+    });
+
+    // sections with driving time keep at least one step, so consecutive Betriebsstellen never share the same time
+    let driving_time = match transformation.round_to {
+        Some(granularity) if !driving_time.is_zero() => round_duration(driving_time, granularity, RoundingDirection::Nearest).max(granularity.step()),
+        Some(_) => driving_time,
+        None => Duration::seconds(driving_time.whole_seconds()),
+    };
+
+    ScheduleEntry {
+        driving_time,
+        // rounding up ensures the minimum stop time is kept
+        stop_time: stop_time.map(|stop_time| match transformation.round_to {
+            Some(granularity) => round_duration(stop_time, granularity, RoundingDirection::Up),
+            None => stop_time,
+        }),
+        ..entry.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> Schedule {
+        Schedule {
+            entries: vec![
+                ScheduleEntry {
+                    betriebsstelle: "A".into(),
+                    driving_time: Duration::seconds(0),
+                    stop_time: Some(Duration::seconds(20)),
+                    time_fix: None,
+                    km: None,
+                    laufweg: None,
+                },
+                ScheduleEntry {
+                    betriebsstelle: "B".into(),
+                    driving_time: Duration::minutes(3),
+                    stop_time: None,
+                    time_fix: None,
+                    km: None,
+                    laufweg: None,
+                },
+                ScheduleEntry {
+                    betriebsstelle: "C".into(),
+                    driving_time: Duration::minutes(2) + Duration::seconds(10),
+                    stop_time: Some(Duration::seconds(40)),
+                    time_fix: None,
+                    km: None,
+                    laufweg: None,
+                },
+            ],
+        }
+    }
+
+    fn times(schedule: Schedule) -> Vec<(Duration, Option<Duration>)> {
+        schedule.entries.into_iter().map(|entry| (entry.driving_time, entry.stop_time)).collect()
+    }
+
+    #[test]
+    fn test_transform_schedule_without_changes() {
+        assert_eq!(transform_schedule(&schedule(), &ScheduleTransformation::default()), schedule());
+    }
+
+    #[test]
+    fn test_transform_schedule_with_percentage() {
+        let transformation = ScheduleTransformation {
+            driving_time_factor: 1.2,
+            recovery_margin: Some(RecoveryMargin::Percentage(5.)),
+            min_stop_time: Some(Duration::seconds(30)),
+            round_to: None,
+        };
+
+        assert_eq!(
+            times(transform_schedule(&schedule(), &transformation)),
+            vec![
+                (Duration::seconds(0), Some(Duration::seconds(30))),
+                (Duration::seconds(226), None),
+                (Duration::seconds(163), Some(Duration::seconds(40))),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_transform_schedule_with_fixed_margin_and_rounding() {
+        let transformation = ScheduleTransformation {
+            driving_time_factor: 1.1,
+            recovery_margin: Some(RecoveryMargin::Fixed(Duration::seconds(20))),
+            min_stop_time: Some(Duration::seconds(50)),
+            round_to: Some(RoundingGranularity::HalfMinute),
+        };
+
+        assert_eq!(
+            times(transform_schedule(&schedule(), &transformation)),
+            vec![
+                (Duration::seconds(0), Some(Duration::minutes(1))),
+                (Duration::minutes(3) + Duration::seconds(30), None),
+                (Duration::minutes(2) + Duration::seconds(30), Some(Duration::minutes(1))),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_transform_schedule_keeps_short_driving_times() {
+        let mut schedule = schedule();
+        schedule.entries[1].driving_time = Duration::seconds(10);
+        let transformation = ScheduleTransformation {
+            round_to: Some(RoundingGranularity::HalfMinute),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            times(transform_schedule(&schedule, &transformation)),
+            vec![
+                (Duration::seconds(0), Some(Duration::seconds(30))),
+                (Duration::seconds(30), None),
+                (Duration::minutes(2), Some(Duration::minutes(1))),
+            ],
+        );
+    }
+}
\ No newline at end of file
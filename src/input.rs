@@ -2,4 +2,9 @@ pub mod fahrplan_config;
 pub mod schedule;
 pub mod environment;
 pub mod rolling_stock_config;
-pub mod copy_delay_config;
\ No newline at end of file
+pub mod copy_delay_config;
+pub mod takt_config;
+pub mod stop_mapping;
+pub mod config_file;
+pub mod config_fragment;
+pub mod variables;
\ No newline at end of file
@@ -0,0 +1,391 @@
+use crate::core::lib::file_error::{FileError, FileErrorKind};
+use crate::input::variables::{substitute_variables, VariableConfig};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use serde_helpers::xml::{FromXML, ToXML};
+use serde_json::Value;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Format of a config file, TOML and YAML configs have the same structure as the XML configs:
+/// attributes are written as keys with scalar values, child elements as tables and repeated child elements as lists of tables.
+/// If differently named child elements are interleaved, like the RouteParts and Includes of a Route,
+/// all child elements are written in document order as a `$value` list of tables with a single key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Xml,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Chooses the format by the file extension, files with other extensions are treated as XML.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Xml,
+        }
+    }
+}
+
+pub fn read_config<T: DeserializeOwned + FromXML, P: AsRef<Path>>(path: P) -> Result<T, FileError> {
+    let path = path.as_ref();
+    match ConfigFormat::from_path(path) {
+        ConfigFormat::Xml => T::from_xml_file_by_path(path).map_err(|error| (path, error).into()),
+        format => {
+            let content = fs::read_to_string(path).map_err(|error| FileError::from((path, error)))?;
+            config_from_str(&content, format).map_err(|error| (path, error).into())
+        },
+    }
+}
+
+/// Reads the config like [read_config] after replacing all variable references in it.
+/// The given variables take precedence over the ones defined in the config.
+pub fn read_config_with_variables<T: DeserializeOwned, P: AsRef<Path>>(path: P, variables: &[VariableConfig]) -> Result<T, FileError> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|error| FileError::from((path, error)))?;
+    let xml = config_to_xml(content.trim_start_matches('\u{feff}'), ConfigFormat::from_path(path)).map_err(|error| FileError::from((path, error)))?;
+    let xml = substitute_variables(&xml, variables).map_err(|error| FileError::from((path, format_error(error))))?;
+    quick_xml::de::from_str(&xml).map_err(|error| (path, format_error(error)).into())
+}
+
+pub fn write_config<T: Serialize + ToXML, P: AsRef<Path>>(config: &T, path: P) -> Result<(), FileError> {
+    let path = path.as_ref();
+    match ConfigFormat::from_path(path) {
+        ConfigFormat::Xml => config.to_xml_file_by_path(path, true).map_err(|error| (path, error).into()),
+        format => {
+            let content = config_to_string(config, format).map_err(|error| FileError::from((path, error)))?;
+            fs::write(path, content).map_err(|error| (path, error).into())
+        },
+    }
+}
+
+pub fn config_from_str<T: DeserializeOwned>(content: &str, format: ConfigFormat) -> Result<T, FileErrorKind> {
+    quick_xml::de::from_str(&config_to_xml(content, format)?).map_err(format_error)
+}
+
+fn config_to_xml(content: &str, format: ConfigFormat) -> Result<String, FileErrorKind> {
+    match format {
+        ConfigFormat::Xml => Ok(content.to_owned()),
+        ConfigFormat::Toml => value_to_xml(toml::from_str(content).map_err(format_error)?),
+        ConfigFormat::Yaml => value_to_xml(serde_norway::from_str(content).map_err(format_error)?),
+    }
+}
+
+pub fn config_to_string<T: Serialize>(config: &T, format: ConfigFormat) -> Result<String, FileErrorKind> {
+    let xml = quick_xml::se::to_string(config).map_err(format_error)?;
+    match format {
+        ConfigFormat::Xml => Ok(xml),
+        ConfigFormat::Toml => toml::to_string(&xml_to_document(&xml)?).map_err(format_error),
+        ConfigFormat::Yaml => serde_norway::to_string(&xml_to_document(&xml)?).map_err(format_error),
+    }
+}
+
+fn format_error<E: ToString>(error: E) -> FileErrorKind {
+    FileErrorKind::FormatError { error: error.to_string() }
+}
+
+/// Key of the list containing all child elements in document order
+const CHILDREN_IN_ORDER_KEY: &str = "$value";
+
+/// XML element without text content, which is the common structure of all config formats
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct ConfigElement {
+    attributes: Vec<(String, String)>,
+    children: Vec<(String, ConfigElement)>,
+}
+
+impl Serialize for ConfigElement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for (name, value) in &self.attributes {
+            map.serialize_entry(name, value)?;
+        }
+        let mut names: Vec<&String> = vec![];
+        for (name, _) in &self.children {
+            if names.last() != Some(&name) {
+                names.push(name);
+            }
+        }
+        if (1..names.len()).any(|index| names[..index].contains(&names[index])) {
+            let children: Vec<NamedConfigElement> = self.children
+                .iter()
+                .map(|(name, child)| NamedConfigElement { name: name.clone(), element: child.clone() })
+                .collect();
+            map.serialize_entry(CHILDREN_IN_ORDER_KEY, &children)?;
+            return map.end();
+        }
+        for name in names {
+            let children: Vec<&ConfigElement> = self.children
+                .iter()
+                .filter(|(child_name, _)| child_name == name)
+                .map(|(_, child)| child)
+                .collect();
+            match children.as_slice() {
+                [child] => map.serialize_entry(name, child)?,
+                children => map.serialize_entry(name, children)?,
+            }
+        }
+        map.end()
+    }
+}
+
+/// Element together with its name, serialized as table with a single key like the root element of a config
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NamedConfigElement {
+    name: String,
+    element: ConfigElement,
+}
+
+impl Serialize for NamedConfigElement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&self.name, &self.element)?;
+        map.end()
+    }
+}
+
+fn xml_to_document(xml: &str) -> Result<NamedConfigElement, FileErrorKind> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut stack: Vec<(String, ConfigElement)> = vec![];
+    loop {
+        let (name, element) = match reader.read_event().map_err(format_error)? {
+            Event::Start(start) => {
+                stack.push(start_to_element(&start)?);
+                continue;
+            },
+            Event::Empty(start) => start_to_element(&start)?,
+            Event::End(_) => stack.pop().ok_or_else(|| format_error("Unexpected closing tag."))?,
+            Event::Text(_) | Event::CData(_) | Event::GeneralRef(_) => {
+                return Err(format_error("Text content can't be converted to TOML or YAML."));
+            },
+            Event::Eof => return Err(format_error("The XML document has no root element.")),
+            _ => continue,
+        };
+        match stack.last_mut() {
+            Some((_, parent)) => parent.children.push((name, element)),
+            None => return Ok(NamedConfigElement { name, element }),
+        }
+    }
+}
+
+fn start_to_element(start: &BytesStart) -> Result<(String, ConfigElement), FileErrorKind> {
+    let attributes = start
+        .attributes()
+        .map(|attribute| {
+            let attribute = attribute.map_err(format_error)?;
+            let name = String::from_utf8(attribute.key.as_ref().to_vec()).map_err(format_error)?;
+            let value = attribute.unescape_value().map_err(format_error)?.into_owned();
+            Ok((name, value))
+        })
+        .collect::<Result<Vec<_>, FileErrorKind>>()?;
+    let name = String::from_utf8(start.name().as_ref().to_vec()).map_err(format_error)?;
+    Ok((name, ConfigElement { attributes, children: vec![] }))
+}
+
+/// Converts a parsed TOML or YAML document with a single root table to XML.
+fn value_to_xml(value: Value) -> Result<String, FileErrorKind> {
+    let Value::Object(document) = value else {
+        return Err(format_error("The config must be a table."));
+    };
+    let mut entries = document.into_iter();
+    let (Some((name, root)), None) = (entries.next(), entries.next()) else {
+        return Err(format_error("The config must contain exactly one root table."));
+    };
+    let mut xml = String::new();
+    write_element(&name, &value_to_element(&name, root)?, &mut xml);
+    Ok(xml)
+}
+
+/// Scalars become attributes, booleans are written as 1 and 0 like in Zusi files.
+fn value_to_element(name: &str, value: Value) -> Result<ConfigElement, FileErrorKind> {
+    let Value::Object(entries) = value else {
+        return Err(format_error(format!("'{name}' must be a table.")));
+    };
+    let mut element = ConfigElement::default();
+    for (key, value) in entries {
+        match value {
+            Value::Null => {},
+            Value::Bool(value) => element.attributes.push((key, if value { "1" } else { "0" }.into())),
+            Value::Number(value) => element.attributes.push((key, value.to_string())),
+            Value::String(value) => element.attributes.push((key, value)),
+            Value::Array(values) if key == CHILDREN_IN_ORDER_KEY => for value in values {
+                let Value::Object(child) = value else {
+                    return Err(format_error(format!("'{key}' of '{name}' must be a list of tables.")));
+                };
+                let mut entries = child.into_iter();
+                let (Some((child_name, child)), None) = (entries.next(), entries.next()) else {
+                    return Err(format_error(format!("Every table in '{key}' of '{name}' must contain exactly one element.")));
+                };
+                let child = value_to_element(&child_name, child)?;
+                element.children.push((child_name, child));
+            },
+            Value::Array(values) => for value in values {
+                let child = value_to_element(&key, value)?;
+                element.children.push((key.clone(), child));
+            },
+            value @ Value::Object(_) => {
+                let child = value_to_element(&key, value)?;
+                element.children.push((key, child));
+            },
+        }
+    }
+    Ok(element)
+}
+
+fn write_element(name: &str, element: &ConfigElement, xml: &mut String) {
+    write!(xml, "<{name}").unwrap();
+    for (key, value) in &element.attributes {
+        write!(xml, r#" {key}="{}""#, escape(value.as_str())).unwrap();
+    }
+    if element.children.is_empty() {
+        xml.push_str("/>");
+    } else {
+        xml.push('>');
+        for (child_name, child) in &element.children {
+            write_element(child_name, child, xml);
+        }
+        write!(xml, "</{name}>").unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fahrplan_config::{RouteConfig, RouteInclude, RouteIncludeConfig, RoutePart, RoutePartSource};
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(rename = "Example")]
+    struct Example {
+        #[serde(rename = "@name")]
+        name: String,
+
+        #[serde(rename = "@count")]
+        count: u32,
+
+        #[serde(rename = "Part", default)]
+        parts: Vec<Part>,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Part {
+        #[serde(rename = "$value")]
+        source: Source,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Source {
+        ByPath {
+            #[serde(rename = "@path")]
+            path: String,
+        },
+        ByNummer {
+            #[serde(rename = "@nummer")]
+            nummer: String,
+        },
+    }
+
+    fn expected_deserialized() -> Example {
+        Example {
+            name: "A & B".into(),
+            count: 2,
+            parts: vec![
+                Part { source: Source::ByPath { path: "./path/to/a.trn".into() } },
+                Part { source: Source::ByNummer { nummer: "10000".into() } },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_config_format_from_path() {
+        assert_eq!(ConfigFormat::from_path("./config.xml"), ConfigFormat::Xml);
+        assert_eq!(ConfigFormat::from_path("./config.toml"), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path("./config.yml"), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path("./config"), ConfigFormat::Xml);
+    }
+
+    #[test]
+    fn test_config_from_toml() {
+        let toml = r#"
+            [Example]
+            name = "A & B"
+            count = 2
+
+            [[Example.Part]]
+            ByPath = { path = "./path/to/a.trn" }
+
+            [[Example.Part]]
+            ByNummer = { nummer = 10000 }
+        "#;
+
+        assert_eq!(config_from_str::<Example>(toml, ConfigFormat::Toml).unwrap(), expected_deserialized());
+    }
+
+    #[test]
+    fn test_config_from_yaml() {
+        let yaml = concat!(
+            "Example:\n",
+            "  name: A & B\n",
+            "  count: 2\n",
+            "  Part:\n",
+            "    - ByPath:\n",
+            "        path: ./path/to/a.trn\n",
+            "    - ByNummer:\n",
+            "        nummer: 10000\n",
+        );
+
+        assert_eq!(config_from_str::<Example>(yaml, ConfigFormat::Yaml).unwrap(), expected_deserialized());
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(rename = "Zug")]
+    struct RouteExample {
+        #[serde(rename = "Route")]
+        route: RouteConfig,
+    }
+
+    fn route_part(path: &str) -> RoutePart {
+        RoutePart {
+            source: RoutePartSource::TrainFileByPath { path: path.into() },
+            start_fahrzeug_verband_aktion: None,
+            time_fix: None,
+            apply_schedule: None,
+        }
+    }
+
+    #[test]
+    fn test_config_round_trip_keeps_order() {
+        let route_example = RouteExample {
+            route: RouteConfig {
+                parts: vec![route_part("./a.trn"), route_part("./b.trn")],
+                includes: vec![
+                    RouteInclude {
+                        index: 1,
+                        include: RouteIncludeConfig { path: "./fragment.xml".into(), name: "middle".into() },
+                    },
+                ],
+            },
+        };
+
+        for format in [ConfigFormat::Xml, ConfigFormat::Toml, ConfigFormat::Yaml] {
+            let serialized = config_to_string(&route_example, format).unwrap();
+            assert_eq!(config_from_str::<RouteExample>(&serialized, format).unwrap(), route_example);
+        }
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        for format in [ConfigFormat::Xml, ConfigFormat::Toml, ConfigFormat::Yaml] {
+            let serialized = config_to_string(&expected_deserialized(), format).unwrap();
+            assert_eq!(config_from_str::<Example>(&serialized, format).unwrap(), expected_deserialized());
+        }
+    }
+}
\ No newline at end of file
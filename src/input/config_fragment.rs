@@ -0,0 +1,135 @@
+use crate::input::fahrplan_config::{IncludeConfig, RoutePart, ZugConfig, ZugTemplate};
+use serde::{Deserialize, Serialize};
+
+/// Part of a config which can be included by other configs, relative paths are resolved relative to this file
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields, rename = "ConfigFragment")]
+pub struct ConfigFragment {
+    /// Further ConfigFragments whose Zuege and RoutePart lists are part of this fragment
+    #[serde(rename = "Include", default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<IncludeConfig>,
+
+    #[serde(rename = "RouteParts", default, skip_serializing_if = "Vec::is_empty")]
+    pub route_parts: Vec<NamedRouteParts>,
+
+    /// ZugTemplates which can be extended by all Zuege of the including config
+    #[serde(rename = "ZugTemplate", default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<ZugTemplate>,
+
+    #[serde(rename = "Zug", default, skip_serializing_if = "Vec::is_empty")]
+    pub zuege: Vec<ZugConfig>,
+}
+
+/// Reusable list of RouteParts which can be included into routes by its name
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NamedRouteParts {
+    #[serde(rename = "@name")]
+    pub name: String,
+
+    #[serde(rename = "RoutePart")]
+    pub parts: Vec<RoutePart>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::fahrplan_config::{RouteConfig, RouteInclude, RouteIncludeConfig, RoutePartSource};
+    use crate::input::rolling_stock_config::RollingStockConfig;
+    use quick_xml::{de, se};
+    use serde_helpers::xml::test_utils::cleanup_xml;
+
+    const EXPECTED_SERIALIZED: &'static str = r#"
+        <ConfigFragment>
+            <Include path="./other-fragment.xml"/>
+            <RouteParts name="main-line">
+                <RoutePart>
+                    <TrainFileByPath path="./path/to/route-part-1.trn"/>
+                </RoutePart>
+                <RoutePart>
+                    <TrainFileByPath path="./path/to/route-part-2.trn"/>
+                </RoutePart>
+            </RouteParts>
+            <ZugTemplate id="rb" gattung="RB">
+                <RollingStock path="./path/to/rolling-stock.trn"/>
+            </ZugTemplate>
+            <Zug nummer="20000" extends="rb">
+                <Route>
+                    <Include path="./other-fragment.xml" name="branch-line"/>
+                    <RoutePart>
+                        <TrainConfigByNummer nummer="10000"/>
+                    </RoutePart>
+                </Route>
+            </Zug>
+        </ConfigFragment>
+    "#;
+
+    fn route_part(source: RoutePartSource) -> RoutePart {
+        RoutePart { source, start_fahrzeug_verband_aktion: None, time_fix: None, apply_schedule: None }
+    }
+
+    fn expected_deserialized() -> ConfigFragment {
+        ConfigFragment {
+            includes: vec![
+                IncludeConfig { path: "./other-fragment.xml".into() },
+            ],
+            route_parts: vec![
+                NamedRouteParts {
+                    name: "main-line".into(),
+                    parts: vec![
+                        route_part(RoutePartSource::TrainFileByPath { path: "./path/to/route-part-1.trn".into() }),
+                        route_part(RoutePartSource::TrainFileByPath { path: "./path/to/route-part-2.trn".into() }),
+                    ],
+                },
+            ],
+            templates: vec![
+                ZugTemplate {
+                    id: "rb".into(),
+                    gattung: "RB".into(),
+                    meta_data: None,
+                    route: Default::default(),
+                    rolling_stock: RollingStockConfig { path: "./path/to/rolling-stock.trn".into() },
+                    copy_delay_config: None,
+                },
+            ],
+            zuege: vec![
+                ZugConfig {
+                    nummer: "20000".into(),
+                    gattung: "".into(),
+                    extends: Some("rb".into()),
+                    zuglauf: "".into(),
+                    fahrplan_gruppe: "".into(),
+                    meta_data: None,
+                    route: RouteConfig {
+                        parts: vec![
+                            route_part(RoutePartSource::TrainConfigByNummer { nummer: "10000".into() }),
+                        ],
+                        includes: vec![
+                            RouteInclude {
+                                index: 0,
+                                include: RouteIncludeConfig { path: "./other-fragment.xml".into(), name: "branch-line".into() },
+                            },
+                        ],
+                    },
+                    rolling_stock: Default::default(),
+                    copy_delay_config: None,
+                    takt: None,
+                    return_config: None,
+                    time_rounding: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_serialize() {
+        let serialized = se::to_string(&expected_deserialized()).unwrap();
+        assert_eq!(serialized, cleanup_xml(EXPECTED_SERIALIZED.into()));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let deserialized: ConfigFragment = de::from_str(EXPECTED_SERIALIZED).unwrap();
+        assert_eq!(deserialized, expected_deserialized());
+    }
+}
\ No newline at end of file
@@ -1,9 +1,13 @@
 pub mod non_default_fahrzeug_verband_aktion;
 
+use serde_helpers::with::duration::duration_format;
 use serde_helpers::with::duration::duration_option_format;
 use serde_helpers::with::bool_as_int::bool_as_int_format;
+use crate::core::lib::time_rounding::{RoundingDirection, RoundingGranularity};
 use crate::input::copy_delay_config::CopyDelayConfig;
 use crate::input::rolling_stock_config::RollingStockConfig;
+use crate::input::takt_config::TaktConfig;
+use crate::input::variables::VariablesConfig;
 use serde::{Deserialize, Serialize};
 use serde_helpers::default::IsDefault;
 use serde_helpers::with::date_time::date_time_format;
@@ -22,19 +26,109 @@ pub struct FahrplanConfig {
     #[serde(rename = "@generateFrom")]
     pub generate_from: PathBuf,
 
+    /// Generate all Zuege which are valid instead of stopping at the first invalid one
+    #[serde(rename = "@collectErrors", with = "bool_as_int_format", default, skip_serializing_if = "IsDefault::is_default")]
+    pub collect_errors: bool,
+
+    /// How to handle generated Zuege which would be written to the same file
+    #[serde(rename = "@zugCollisions", default, skip_serializing_if = "IsDefault::is_default")]
+    pub zug_collisions: ZugCollisionPolicy,
+
+    /// Variables which can be referenced by `${name}` in all attribute values, they are replaced when the config is read
+    #[serde(rename = "Variables", default, skip_serializing_if = "IsDefault::is_default")]
+    pub variables: VariablesConfig,
+
+    /// Default rounding of the generated times, can be overridden per Zug
+    #[serde(rename = "TimeRounding", default, skip_serializing_if = "Option::is_none")]
+    pub time_rounding: Option<TimeRounding>,
+
+    #[serde(rename = "Umlauf", default, skip_serializing_if = "Vec::is_empty")]
+    pub umlaeufe: Vec<UmlaufConfig>,
+
+    /// Check the generated Zuege for conflicts at shared Betriebsstellen and on single-track sections
+    #[serde(rename = "ConflictDetection", default, skip_serializing_if = "Option::is_none")]
+    pub conflict_detection: Option<ConflictDetectionConfig>,
+
+    /// Values of the generated Fahrplan which replace the ones of the template .fpn file
+    #[serde(rename = "Header", default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<FahrplanHeaderConfig>,
+
+    /// Streckenmodule which are added to the ones of the template .fpn file
+    #[serde(rename = "StrModule", default, skip_serializing_if = "Option::is_none")]
+    pub str_module: Option<StrModuleConfig>,
+
+    /// Shared values of Zuege, which are used by Zuege extending them
+    #[serde(rename = "ZugTemplate", default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<ZugTemplate>,
+
+    /// ConfigFragments whose Zuege are added to the Zuege of this config
+    #[serde(rename = "Include", default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<IncludeConfig>,
+
     #[serde(rename = "Zug", default)]
     pub zuege: Vec<ZugConfig>,
 }
 
+/// Reference to a ConfigFragment file, relative paths are resolved relative to the including file
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct IncludeConfig {
+    #[serde(rename = "@path")]
+    pub path: PathBuf,
+}
+
+/// Zuege which are run in sequence by the same consist
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct UmlaufConfig {
+    /// Minimum time between the Ankunft of a Zug and the Abfahrt of the next one
+    #[serde(rename = "@minTurnaroundTime", with = "duration_option_format", default, skip_serializing_if = "Option::is_none")]
+    pub min_turnaround_time: Option<Duration>,
+
+    #[serde(rename = "UmlaufZug")]
+    pub zuege: Vec<UmlaufZug>,
+
+    /// Vehicle turn at the end of every Zug except the last one
+    #[serde(rename = "StartFahrzeugVerbandAktion", default, skip_serializing_if = "Option::is_none")]
+    pub start_fahrzeug_verband_aktion: Option<StartFahrzeugVerbandAktion>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct UmlaufZug {
+    #[serde(rename = "@nummer")]
+    pub nummer: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+#[serde(deny_unknown_fields)]
+pub enum ZugCollisionPolicy {
+    /// Report collisions but write all Zuege anyway
+    #[default]
+    Warn,
+
+    /// Abort the generation
+    Fail,
+
+    /// Keep the first Zug and increment the Zugnummer of all others until it is unique.
+    /// Umlaeufe and route parts keep referencing the Zugnummer of the config.
+    Renumber,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ZugConfig {
     #[serde(rename = "@nummer")]
     pub nummer: String,
 
-    #[serde(rename = "@gattung")]
+    /// Can be omitted if it is set by the template
+    #[serde(rename = "@gattung", default, skip_serializing_if = "IsDefault::is_default")]
     pub gattung: String,
 
+    /// Id of the ZugTemplate whose values are used for all values which aren't set by the Zug itself
+    #[serde(rename = "@extends", default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+
     #[serde(rename = "@zuglauf", default, skip_serializing_if = "IsDefault::is_default")]
     pub zuglauf: String,
 
@@ -44,28 +138,249 @@ pub struct ZugConfig {
     #[serde(rename = "MetaData", default, skip_serializing_if = "Option::is_none")]
     pub meta_data: Option<MetaDataConfig>,
 
-    #[serde(rename = "Route")]
+    /// Can be omitted if it is set by the template
+    #[serde(rename = "Route", default, skip_serializing_if = "IsDefault::is_default")]
     pub route: RouteConfig,
 
-    #[serde(rename = "RollingStock")]
+    /// Can be omitted if it is set by the template
+    #[serde(rename = "RollingStock", default, skip_serializing_if = "IsDefault::is_default")]
     pub rolling_stock: RollingStockConfig,
 
     #[serde(rename = "CopyDelay", default, skip_serializing_if = "Option::is_none")]
     pub copy_delay_config: Option<CopyDelayConfig>,
+
+    #[serde(rename = "Takt", default, skip_serializing_if = "Option::is_none")]
+    pub takt: Option<TaktConfig>,
+
+    #[serde(rename = "Return", default, skip_serializing_if = "Option::is_none")]
+    pub return_config: Option<ReturnConfig>,
+
+    #[serde(rename = "TimeRounding", default, skip_serializing_if = "Option::is_none")]
+    pub time_rounding: Option<TimeRounding>,
 }
 
+/// Values shared by multiple Zuege, a Zug extending the template overrides the values it sets itself
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
-pub struct MetaDataConfig {
+pub struct ZugTemplate {
+    #[serde(rename = "@id")]
+    pub id: String,
+
+    #[serde(rename = "@gattung", default, skip_serializing_if = "IsDefault::is_default")]
+    pub gattung: String,
+
+    #[serde(rename = "MetaData", default, skip_serializing_if = "Option::is_none")]
+    pub meta_data: Option<MetaDataConfig>,
+
+    #[serde(rename = "Route", default, skip_serializing_if = "IsDefault::is_default")]
+    pub route: RouteConfig,
+
+    #[serde(rename = "RollingStock", default, skip_serializing_if = "IsDefault::is_default")]
+    pub rolling_stock: RollingStockConfig,
+
+    #[serde(rename = "CopyDelay", default, skip_serializing_if = "Option::is_none")]
+    pub copy_delay_config: Option<CopyDelayConfig>,
+}
+
+/// Zug in the opposite direction which departs after the Zug has arrived at its destination.
+/// It uses the same rolling stock, meta data and copies as the Zug itself.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ReturnConfig {
+    /// Minimum time between the Ankunft of the Zug and the Abfahrt of the return Zug
+    #[serde(rename = "@minTurnaroundTime", with = "duration_format")]
+    pub min_turnaround_time: Duration,
+
+    /// Added to the Zugnummer of the Zug to get the Zugnummer of the return Zug
+    #[serde(rename = "@increment")]
+    pub increment: i32,
+
+    /// Zuglauf of the return Zug, the one of the Zug is used if not set
+    #[serde(rename = "@zuglauf", default, skip_serializing_if = "IsDefault::is_default")]
+    pub zuglauf: String,
+
+    #[serde(rename = "Route")]
+    pub route: RouteConfig,
+
+    /// Vehicle turn at the end of the Zug
+    #[serde(rename = "StartFahrzeugVerbandAktion", default, skip_serializing_if = "Option::is_none")]
+    pub start_fahrzeug_verband_aktion: Option<StartFahrzeugVerbandAktion>,
+}
+
+/// Rounding of Ankunft and Abfahrt, applied to the .trn and the Buchfahrplan.
+/// Stops are never shortened by rounding.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TimeRounding {
+    #[serde(rename = "@granularity")]
+    pub granularity: RoundingGranularity,
+
+    #[serde(rename = "@direction", default, skip_serializing_if = "IsDefault::is_default")]
+    pub direction: RoundingDirection,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ConflictDetectionConfig {
+    /// Minimum time between two Zuege at the same Betriebsstelle or on the same single-track section
+    #[serde(rename = "@headway", with = "duration_format")]
+    pub headway: Duration,
+
+    /// Abort the generation if any conflicts are found
+    #[serde(rename = "@fail", with = "bool_as_int_format", default, skip_serializing_if = "IsDefault::is_default")]
+    pub fail: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FahrplanHeaderConfig {
+    #[serde(rename = "@chaosVorschlagen", with = "bool_as_int_option_format", default, skip_serializing_if = "Option::is_none")]
+    pub chaos_vorschlagen: Option<bool>,
+
+    #[serde(rename = "AnfangsZeit", default, skip_serializing_if = "Option::is_none")]
+    pub anfangs_zeit: Option<AnfangsZeitConfig>,
+
+    #[serde(rename = "BefehlsKonfiguration", default, skip_serializing_if = "Option::is_none")]
+    pub befehls_konfiguration: Option<HeaderFileConfig>,
+
+    #[serde(rename = "Begruessungsdatei", default, skip_serializing_if = "Option::is_none")]
+    pub begruessungsdatei: Option<HeaderFileConfig>,
+
+    #[serde(rename = "LaPDF", default, skip_serializing_if = "Option::is_none")]
+    pub la_pdf: Option<HeaderFileConfig>,
+
+    #[serde(rename = "StrebuPDF", default, skip_serializing_if = "Option::is_none")]
+    pub strebu_pdf: Option<HeaderFileConfig>,
+
+    #[serde(rename = "ErsatzfahrplaenePDF", default, skip_serializing_if = "Option::is_none")]
+    pub ersatzfahrplaene_pdf: Option<HeaderFileConfig>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AnfangsZeitConfig {
+    #[serde(rename = "$value")]
+    pub source: AnfangsZeitSource,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub enum AnfangsZeitSource {
+    /// Start time independent of the generated Zuege
+    Fixed {
+        #[serde(rename = "@value", with = "date_time_format")]
+        value: PrimitiveDateTime,
+    },
+
+    /// Earliest Abfahrt of all generated Zuege minus the lead time
+    BeforeFirstAbfahrt {
+        #[serde(rename = "@leadTime", with = "duration_format")]
+        lead_time: Duration,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StrModuleConfig {
+    /// Add the Streckenmodule of the Fahrplaene the route templates were created in
+    #[serde(rename = "@fromRoutes", with = "bool_as_int_format", default, skip_serializing_if = "IsDefault::is_default")]
+    pub from_routes: bool,
+
+    #[serde(rename = "StrModul", default, skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<StrModulConfig>,
+}
+
+/// Path to a .st3 file, absolute paths are relative to the data dir
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StrModulConfig {
+    #[serde(rename = "@path")]
+    pub path: PathBuf,
+}
+
+/// File referenced by the Fahrplan, absolute paths are relative to the data dir
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HeaderFileConfig {
     #[serde(rename = "@path")]
     pub path: PathBuf,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
+pub struct MetaDataConfig {
+    #[serde(rename = "@path")]
+    pub path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+#[serde(from = "RouteConfigElements", into = "RouteConfigElements")]
 pub struct RouteConfig {
-    #[serde(rename = "$value")]
     pub parts: Vec<RoutePart>,
+
+    /// Named RoutePart lists of ConfigFragments, which are inserted into the parts when the includes are resolved
+    pub includes: Vec<RouteInclude>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct RouteInclude {
+    /// Number of RouteParts before the include
+    pub index: usize,
+
+    pub include: RouteIncludeConfig,
+}
+
+/// Reference to a named RoutePart list of a ConfigFragment file, relative paths are resolved relative to the including file
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RouteIncludeConfig {
+    #[serde(rename = "@path")]
+    pub path: PathBuf,
+
+    #[serde(rename = "@name")]
+    pub name: String,
+}
+
+/// RouteParts and includes in the order they are written in the config
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct RouteConfigElements {
+    #[serde(rename = "$value")]
+    elements: Vec<RouteConfigElement>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+enum RouteConfigElement {
+    RoutePart(RoutePart),
+    Include(RouteIncludeConfig),
+}
+
+impl From<RouteConfigElements> for RouteConfig {
+    fn from(value: RouteConfigElements) -> Self {
+        let mut config = RouteConfig { parts: vec![], includes: vec![] };
+        for element in value.elements {
+            match element {
+                RouteConfigElement::RoutePart(part) => config.parts.push(part),
+                RouteConfigElement::Include(include) => config.includes.push(RouteInclude { index: config.parts.len(), include }),
+            }
+        }
+        config
+    }
+}
+
+impl From<RouteConfig> for RouteConfigElements {
+    fn from(value: RouteConfig) -> Self {
+        let mut includes = value.includes.into_iter().peekable();
+        let mut elements = vec![];
+        for (index, part) in value.parts.into_iter().enumerate() {
+            while let Some(include) = includes.next_if(|include| include.index <= index) {
+                elements.push(RouteConfigElement::Include(include.include));
+            }
+            elements.push(RouteConfigElement::RoutePart(part));
+        }
+        elements.extend(includes.map(|include| RouteConfigElement::Include(include.include)));
+        RouteConfigElements { elements }
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -140,11 +455,32 @@ pub struct ApplySchedule {
     pub last_stop_time: Option<Duration>,
 }
 
+/// Like `bool_as_int_format`, for optional values
+mod bool_as_int_option_format {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde_helpers::with::bool_as_int::bool_as_int_format;
+
+    #[derive(Deserialize)]
+    struct BoolAsInt(#[serde(with = "bool_as_int_format")] bool);
+
+    pub fn serialize<S: Serializer>(value: &Option<bool>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => bool_as_int_format::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<bool>, D::Error> {
+        Ok(Option::<BoolAsInt>::deserialize(deserializer)?.map(|BoolAsInt(value)| value))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::input::copy_delay_config::CopyDelayTask;
     use crate::input::environment::zusi_environment_config::ZusiEnvironmentConfig;
+    use crate::input::variables::VariableConfig;
     use quick_xml::{de, se};
     use serde_helpers::xml::test_utils::cleanup_xml;
     use time::macros::datetime;
@@ -196,10 +532,21 @@ mod tests {
             value: FahrplanConfig {
                 generate_at: "./path/to/destination.fpn".into(),
                 generate_from: "./path/to/template.fpn".into(),
+                collect_errors: false,
+                zug_collisions: Default::default(),
+                variables: Default::default(),
+                time_rounding: None,
+                umlaeufe: vec![],
+                conflict_detection: None,
+                header: None,
+                str_module: None,
+                templates: vec![],
+                includes: vec![],
                 zuege: vec![
                     ZugConfig {
                         nummer: "20000".into(),
                         gattung: "RB".into(),
+                        extends: None,
                         zuglauf: "ADorf - BDorf".into(),
                         fahrplan_gruppe: "A - B".into(),
                         meta_data: Some(MetaDataConfig {
@@ -238,6 +585,7 @@ mod tests {
                                     apply_schedule: None,
                                 },
                             ],
+                            includes: vec![],
                         },
                         rolling_stock: RollingStockConfig { path: "./path/to/rolling-stock.trn".into() },
                         copy_delay_config: Some(CopyDelayConfig {
@@ -260,10 +608,14 @@ mod tests {
                                 },
                             ],
                         }),
+                        takt: None,
+                        return_config: None,
+                        time_rounding: None,
                     },
                     ZugConfig {
                         nummer: "30000".into(),
                         gattung: "RE".into(),
+                        extends: None,
                         zuglauf: "".into(),
                         fahrplan_gruppe: "".into(),
                         meta_data: None,
@@ -276,9 +628,13 @@ mod tests {
                                     apply_schedule: None,
                                 },
                             ],
+                            includes: vec![],
                         },
                         rolling_stock: RollingStockConfig { path: "./path/to/rolling-stock.trn".into() },
                         copy_delay_config: None,
+                        takt: None,
+                        return_config: None,
+                        time_rounding: None,
                     },
                 ],
             },
@@ -296,4 +652,487 @@ mod tests {
         let deserialized: ZusiEnvironmentConfig<FahrplanConfig> = de::from_str(EXPECTED_SERIALIZED).unwrap();
         assert_eq!(deserialized, expected_deserialized());
     }
+
+    fn fahrplan_config() -> FahrplanConfig {
+        FahrplanConfig {
+            generate_at: "./path/to/destination.fpn".into(),
+            generate_from: "./path/to/template.fpn".into(),
+            collect_errors: false,
+            zug_collisions: Default::default(),
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![],
+            includes: vec![],
+            zuege: vec![],
+        }
+    }
+
+    fn route_part(path: &str) -> RoutePart {
+        RoutePart {
+            source: RoutePartSource::TrainFileByPath { path: path.into() },
+            start_fahrzeug_verband_aktion: None,
+            time_fix: None,
+            apply_schedule: None,
+        }
+    }
+
+    fn zug_config() -> ZugConfig {
+        ZugConfig {
+            nummer: "20000".into(),
+            gattung: "RB".into(),
+            extends: None,
+            zuglauf: "".into(),
+            fahrplan_gruppe: "".into(),
+            meta_data: None,
+            route: RouteConfig { parts: vec![route_part("./path/to/route-part.trn")], includes: vec![] },
+            rolling_stock: RollingStockConfig { path: "./path/to/rolling-stock.trn".into() },
+            copy_delay_config: None,
+            takt: None,
+            return_config: None,
+            time_rounding: None,
+        }
+    }
+
+    fn assert_serialized(config: FahrplanConfig, expected: &str) {
+        let serialized = se::to_string(&config).unwrap();
+        assert_eq!(serialized, cleanup_xml(expected.into()));
+    }
+
+    fn assert_deserialized(serialized: &str, expected: FahrplanConfig) {
+        let deserialized: FahrplanConfig = de::from_str(serialized).unwrap();
+        assert_eq!(deserialized, expected);
+    }
+
+    const EXPECTED_SERIALIZED_ERROR_HANDLING: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn" collectErrors="1" zugCollisions="Renumber"/>
+    "#;
+
+    fn expected_deserialized_error_handling() -> FahrplanConfig {
+        FahrplanConfig {
+            collect_errors: true,
+            zug_collisions: ZugCollisionPolicy::Renumber,
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_error_handling() {
+        assert_serialized(expected_deserialized_error_handling(), EXPECTED_SERIALIZED_ERROR_HANDLING);
+    }
+
+    #[test]
+    fn test_deserialize_error_handling() {
+        assert_deserialized(EXPECTED_SERIALIZED_ERROR_HANDLING, expected_deserialized_error_handling());
+    }
+
+    const EXPECTED_SERIALIZED_VARIABLES: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <Variables>
+                <Variable name="day" value="2023-02-01"/>
+            </Variables>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_variables() -> FahrplanConfig {
+        FahrplanConfig {
+            variables: VariablesConfig {
+                variables: vec![
+                    VariableConfig { name: "day".into(), value: "2023-02-01".into() },
+                ],
+            },
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_variables() {
+        assert_serialized(expected_deserialized_variables(), EXPECTED_SERIALIZED_VARIABLES);
+    }
+
+    #[test]
+    fn test_deserialize_variables() {
+        assert_deserialized(EXPECTED_SERIALIZED_VARIABLES, expected_deserialized_variables());
+    }
+
+    const EXPECTED_SERIALIZED_TIME_ROUNDING: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <TimeRounding granularity="HalfMinute"/>
+            <Zug nummer="20000" gattung="RB">
+                <Route>
+                    <RoutePart>
+                        <TrainFileByPath path="./path/to/route-part.trn"/>
+                    </RoutePart>
+                </Route>
+                <RollingStock path="./path/to/rolling-stock.trn"/>
+                <TimeRounding granularity="Minute" direction="Up"/>
+            </Zug>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_time_rounding() -> FahrplanConfig {
+        FahrplanConfig {
+            time_rounding: Some(TimeRounding { granularity: RoundingGranularity::HalfMinute, direction: RoundingDirection::Nearest }),
+            zuege: vec![
+                ZugConfig {
+                    time_rounding: Some(TimeRounding { granularity: RoundingGranularity::Minute, direction: RoundingDirection::Up }),
+                    ..zug_config()
+                },
+            ],
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_time_rounding() {
+        assert_serialized(expected_deserialized_time_rounding(), EXPECTED_SERIALIZED_TIME_ROUNDING);
+    }
+
+    #[test]
+    fn test_deserialize_time_rounding() {
+        assert_deserialized(EXPECTED_SERIALIZED_TIME_ROUNDING, expected_deserialized_time_rounding());
+    }
+
+    const EXPECTED_SERIALIZED_UMLAUF: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <Umlauf minTurnaroundTime="00:05:00">
+                <UmlaufZug nummer="20000"/>
+                <UmlaufZug nummer="30000"/>
+                <StartFahrzeugVerbandAktion aktion="1"/>
+            </Umlauf>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_umlauf() -> FahrplanConfig {
+        FahrplanConfig {
+            umlaeufe: vec![
+                UmlaufConfig {
+                    min_turnaround_time: Some(Duration::minutes(5)),
+                    zuege: vec![
+                        UmlaufZug { nummer: "20000".into() },
+                        UmlaufZug { nummer: "30000".into() },
+                    ],
+                    start_fahrzeug_verband_aktion: Some(StartFahrzeugVerbandAktion {
+                        aktion: NonDefaultFahrzeugVerbandAktion::ZugDrehen,
+                        wende_signal: false,
+                        wende_signal_abstand: 0.,
+                    }),
+                },
+            ],
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_umlauf() {
+        assert_serialized(expected_deserialized_umlauf(), EXPECTED_SERIALIZED_UMLAUF);
+    }
+
+    #[test]
+    fn test_deserialize_umlauf() {
+        assert_deserialized(EXPECTED_SERIALIZED_UMLAUF, expected_deserialized_umlauf());
+    }
+
+    const EXPECTED_SERIALIZED_CONFLICT_DETECTION: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <ConflictDetection headway="00:02:00" fail="1"/>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_conflict_detection() -> FahrplanConfig {
+        FahrplanConfig {
+            conflict_detection: Some(ConflictDetectionConfig { headway: Duration::minutes(2), fail: true }),
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_conflict_detection() {
+        assert_serialized(expected_deserialized_conflict_detection(), EXPECTED_SERIALIZED_CONFLICT_DETECTION);
+    }
+
+    #[test]
+    fn test_deserialize_conflict_detection() {
+        assert_deserialized(EXPECTED_SERIALIZED_CONFLICT_DETECTION, expected_deserialized_conflict_detection());
+    }
+
+    const EXPECTED_SERIALIZED_HEADER: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <Header chaosVorschlagen="1">
+                <AnfangsZeit>
+                    <BeforeFirstAbfahrt leadTime="00:15:00"/>
+                </AnfangsZeit>
+                <BefehlsKonfiguration path="/Signals/Deutschland/Befehle/408_2015.authority.xml"/>
+                <LaPDF path="./path/to/la.pdf"/>
+            </Header>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_header() -> FahrplanConfig {
+        FahrplanConfig {
+            header: Some(FahrplanHeaderConfig {
+                chaos_vorschlagen: Some(true),
+                anfangs_zeit: Some(AnfangsZeitConfig {
+                    source: AnfangsZeitSource::BeforeFirstAbfahrt { lead_time: Duration::minutes(15) },
+                }),
+                befehls_konfiguration: Some(HeaderFileConfig { path: "/Signals/Deutschland/Befehle/408_2015.authority.xml".into() }),
+                begruessungsdatei: None,
+                la_pdf: Some(HeaderFileConfig { path: "./path/to/la.pdf".into() }),
+                strebu_pdf: None,
+                ersatzfahrplaene_pdf: None,
+            }),
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_header() {
+        assert_serialized(expected_deserialized_header(), EXPECTED_SERIALIZED_HEADER);
+    }
+
+    #[test]
+    fn test_deserialize_header() {
+        assert_deserialized(EXPECTED_SERIALIZED_HEADER, expected_deserialized_header());
+    }
+
+    const EXPECTED_SERIALIZED_STR_MODULE: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <StrModule fromRoutes="1">
+                <StrModul path="/Routes/Deutschland/32U_0006_0058/000551_005775_Elze/Elze_2003.st3"/>
+            </StrModule>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_str_module() -> FahrplanConfig {
+        FahrplanConfig {
+            str_module: Some(StrModuleConfig {
+                from_routes: true,
+                modules: vec![
+                    StrModulConfig { path: "/Routes/Deutschland/32U_0006_0058/000551_005775_Elze/Elze_2003.st3".into() },
+                ],
+            }),
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_str_module() {
+        assert_serialized(expected_deserialized_str_module(), EXPECTED_SERIALIZED_STR_MODULE);
+    }
+
+    #[test]
+    fn test_deserialize_str_module() {
+        assert_deserialized(EXPECTED_SERIALIZED_STR_MODULE, expected_deserialized_str_module());
+    }
+
+    const EXPECTED_SERIALIZED_TEMPLATE: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <ZugTemplate id="rb" gattung="RB">
+                <MetaData path="./path/to/meta-data.trn"/>
+                <RollingStock path="./path/to/rolling-stock.trn"/>
+                <CopyDelay>
+                    <CopyDelayTask delay="01:00:00" increment="2" count="4"/>
+                </CopyDelay>
+            </ZugTemplate>
+            <Zug nummer="20100" extends="rb">
+                <Route>
+                    <RoutePart>
+                        <TrainFileByPath path="./path/to/route-part.trn"/>
+                    </RoutePart>
+                </Route>
+            </Zug>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_template() -> FahrplanConfig {
+        FahrplanConfig {
+            templates: vec![
+                ZugTemplate {
+                    id: "rb".into(),
+                    gattung: "RB".into(),
+                    meta_data: Some(MetaDataConfig { path: "./path/to/meta-data.trn".into() }),
+                    route: Default::default(),
+                    rolling_stock: RollingStockConfig { path: "./path/to/rolling-stock.trn".into() },
+                    copy_delay_config: Some(CopyDelayConfig {
+                        tasks: vec![
+                            CopyDelayTask {
+                                delay: Duration::hours(1),
+                                first_delay: None,
+                                increment: 2,
+                                first_increment: None,
+                                count: 4,
+                                custom_rolling_stock: None,
+                            },
+                        ],
+                    }),
+                },
+            ],
+            zuege: vec![
+                ZugConfig {
+                    nummer: "20100".into(),
+                    gattung: "".into(),
+                    extends: Some("rb".into()),
+                    rolling_stock: Default::default(),
+                    ..zug_config()
+                },
+            ],
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_template() {
+        assert_serialized(expected_deserialized_template(), EXPECTED_SERIALIZED_TEMPLATE);
+    }
+
+    #[test]
+    fn test_deserialize_template() {
+        assert_deserialized(EXPECTED_SERIALIZED_TEMPLATE, expected_deserialized_template());
+    }
+
+    const EXPECTED_SERIALIZED_INCLUDE: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <Include path="./path/to/line-a.xml"/>
+            <Zug nummer="20000" gattung="RB">
+                <Route>
+                    <RoutePart>
+                        <TrainFileByPath path="./path/to/route-part.trn"/>
+                    </RoutePart>
+                    <Include path="./path/to/shared.xml" name="main-line"/>
+                </Route>
+                <RollingStock path="./path/to/rolling-stock.trn"/>
+            </Zug>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_include() -> FahrplanConfig {
+        FahrplanConfig {
+            includes: vec![
+                IncludeConfig { path: "./path/to/line-a.xml".into() },
+            ],
+            zuege: vec![
+                ZugConfig {
+                    route: RouteConfig {
+                        parts: vec![route_part("./path/to/route-part.trn")],
+                        includes: vec![
+                            RouteInclude {
+                                index: 1,
+                                include: RouteIncludeConfig { path: "./path/to/shared.xml".into(), name: "main-line".into() },
+                            },
+                        ],
+                    },
+                    ..zug_config()
+                },
+            ],
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_include() {
+        assert_serialized(expected_deserialized_include(), EXPECTED_SERIALIZED_INCLUDE);
+    }
+
+    #[test]
+    fn test_deserialize_include() {
+        assert_deserialized(EXPECTED_SERIALIZED_INCLUDE, expected_deserialized_include());
+    }
+
+    const EXPECTED_SERIALIZED_TAKT: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <Zug nummer="20000" gattung="RB">
+                <Route>
+                    <RoutePart>
+                        <TrainFileByPath path="./path/to/route-part.trn"/>
+                    </RoutePart>
+                </Route>
+                <RollingStock path="./path/to/rolling-stock.trn"/>
+                <Takt start="05:00:00" end="23:00:00" interval="01:00:00" increment="2"/>
+            </Zug>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_takt() -> FahrplanConfig {
+        FahrplanConfig {
+            zuege: vec![
+                ZugConfig {
+                    takt: Some(TaktConfig {
+                        start: Duration::hours(5),
+                        end: Duration::hours(23),
+                        interval: Duration::hours(1),
+                        increment: 2,
+                        windows: vec![],
+                    }),
+                    ..zug_config()
+                },
+            ],
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_takt() {
+        assert_serialized(expected_deserialized_takt(), EXPECTED_SERIALIZED_TAKT);
+    }
+
+    #[test]
+    fn test_deserialize_takt() {
+        assert_deserialized(EXPECTED_SERIALIZED_TAKT, expected_deserialized_takt());
+    }
+
+    const EXPECTED_SERIALIZED_RETURN: &'static str = r#"
+        <Fahrplan generateAt="./path/to/destination.fpn" generateFrom="./path/to/template.fpn">
+            <Zug nummer="20000" gattung="RB">
+                <Route>
+                    <RoutePart>
+                        <TrainFileByPath path="./path/to/route-part.trn"/>
+                    </RoutePart>
+                </Route>
+                <RollingStock path="./path/to/rolling-stock.trn"/>
+                <Return minTurnaroundTime="00:12:00" increment="1" zuglauf="BDorf - ADorf">
+                    <Route>
+                        <RoutePart>
+                            <TrainFileByPath path="./path/to/return-route-part.trn"/>
+                        </RoutePart>
+                    </Route>
+                    <StartFahrzeugVerbandAktion aktion="2"/>
+                </Return>
+            </Zug>
+        </Fahrplan>
+    "#;
+
+    fn expected_deserialized_return() -> FahrplanConfig {
+        FahrplanConfig {
+            zuege: vec![
+                ZugConfig {
+                    return_config: Some(ReturnConfig {
+                        min_turnaround_time: Duration::minutes(12),
+                        increment: 1,
+                        zuglauf: "BDorf - ADorf".into(),
+                        route: RouteConfig { parts: vec![route_part("./path/to/return-route-part.trn")], includes: vec![] },
+                        start_fahrzeug_verband_aktion: Some(StartFahrzeugVerbandAktion {
+                            aktion: NonDefaultFahrzeugVerbandAktion::Fueherstandswechsel,
+                            wende_signal: false,
+                            wende_signal_abstand: 0.,
+                        }),
+                    }),
+                    ..zug_config()
+                },
+            ],
+            ..fahrplan_config()
+        }
+    }
+
+    #[test]
+    fn test_serialize_return() {
+        assert_serialized(expected_deserialized_return(), EXPECTED_SERIALIZED_RETURN);
+    }
+
+    #[test]
+    fn test_deserialize_return() {
+        assert_deserialized(EXPECTED_SERIALIZED_RETURN, expected_deserialized_return());
+    }
 }
\ No newline at end of file
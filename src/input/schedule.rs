@@ -3,14 +3,14 @@ use serde_helpers::with::duration::duration_format;
 use serde_helpers::with::duration::duration_option_format;
 use time::Duration;
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct Schedule {
     #[serde(rename = "ScheduleEntry")]
     pub entries: Vec<ScheduleEntry>,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ScheduleEntry {
     #[serde(rename = "@betriebsstelle")]
@@ -24,6 +24,14 @@ pub struct ScheduleEntry {
 
     #[serde(rename = "@timeFix", default, skip_serializing_if = "Option::is_none")]
     pub time_fix: Option<TimeFix>,
+
+    /// Kilometre position of the Betriebsstelle, only informational
+    #[serde(rename = "@km", default, skip_serializing_if = "Option::is_none")]
+    pub km: Option<f32>,
+
+    /// Distance from the start of the route (FplLaufweg), only informational
+    #[serde(rename = "@laufweg", default, skip_serializing_if = "Option::is_none")]
+    pub laufweg: Option<f32>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
@@ -60,30 +68,40 @@ mod tests {
                     driving_time: Duration::seconds(0),
                     stop_time: Some(Duration::seconds(50)),
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "b".into(),
                     driving_time: Duration::minutes(2) + Duration::seconds(40),
                     stop_time: Some(Duration::seconds(50)),
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "b".into(),
                     driving_time: Duration::seconds(0),
                     stop_time: None,
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "c".into(),
                     driving_time: Duration::minutes(3) + Duration::seconds(10),
                     stop_time: Some(Duration::seconds(20)),
                     time_fix: Some(TimeFix::Abfahrt),
+                    km: None,
+                    laufweg: None,
                 },
                 ScheduleEntry {
                     betriebsstelle: "d".into(),
                     driving_time: Duration::minutes(2) + Duration::seconds(30),
                     stop_time: Some(Duration::seconds(0)),
                     time_fix: None,
+                    km: None,
+                    laufweg: None,
                 },
             ],
         }
@@ -100,4 +118,45 @@ mod tests {
         let deserialized: Schedule = de::from_str(SERIALIZED_SCHEDULE).unwrap();
         assert_eq!(deserialized, deserialized_schedule());
     }
+    const SERIALIZED_SCHEDULE_WITH_POSITIONS: &'static str = r#"
+        <Schedule>
+            <ScheduleEntry betriebsstelle="a" drivingTime="00:00:00" stopTime="00:00:50" km="32.8757" laufweg="20092.018"/>
+            <ScheduleEntry betriebsstelle="d" drivingTime="00:02:30" stopTime="00:00:00" km="9.0405"/>
+        </Schedule>
+    "#;
+
+    fn deserialized_schedule_with_positions() -> Schedule {
+        Schedule {
+            entries: vec![
+                ScheduleEntry {
+                    betriebsstelle: "a".into(),
+                    driving_time: Duration::seconds(0),
+                    stop_time: Some(Duration::seconds(50)),
+                    time_fix: None,
+                    km: Some(32.8757),
+                    laufweg: Some(20092.018),
+                },
+                ScheduleEntry {
+                    betriebsstelle: "d".into(),
+                    driving_time: Duration::minutes(2) + Duration::seconds(30),
+                    stop_time: Some(Duration::seconds(0)),
+                    time_fix: None,
+                    km: Some(9.0405),
+                    laufweg: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_serialize_with_positions() {
+        let serialized = se::to_string(&deserialized_schedule_with_positions()).unwrap();
+        assert_eq!(serialized, cleanup_xml(SERIALIZED_SCHEDULE_WITH_POSITIONS.into()));
+    }
+
+    #[test]
+    fn test_deserialize_with_positions() {
+        let deserialized: Schedule = de::from_str(SERIALIZED_SCHEDULE_WITH_POSITIONS).unwrap();
+        assert_eq!(deserialized, deserialized_schedule_with_positions());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// Maps the stops of a GTFS feed to Zusi Betriebsstellen
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StopMapping {
+    #[serde(rename = "Stop", default)]
+    pub stops: Vec<StopMappingEntry>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StopMappingEntry {
+    /// stop_id of a stop or its parent station
+    #[serde(rename = "@stopId")]
+    pub stop_id: String,
+
+    #[serde(rename = "@betriebsstelle")]
+    pub betriebsstelle: String,
+}
+
+impl StopMapping {
+    pub fn betriebsstelle(&self, stop_id: &str) -> Option<&str> {
+        self.stops
+            .iter()
+            .find(|entry| entry.stop_id == stop_id)
+            .map(|entry| entry.betriebsstelle.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::{de, se};
+    use serde_helpers::xml::test_utils::cleanup_xml;
+
+    const SERIALIZED_STOP_MAPPING: &'static str = r#"
+        <StopMapping>
+            <Stop stopId="de:03254:1001" betriebsstelle="Elze"/>
+            <Stop stopId="de:03254:1002:1:1" betriebsstelle="Mehle Hp"/>
+        </StopMapping>
+    "#;
+
+    fn deserialized_stop_mapping() -> StopMapping {
+        StopMapping {
+            stops: vec![
+                StopMappingEntry { stop_id: "de:03254:1001".into(), betriebsstelle: "Elze".into() },
+                StopMappingEntry { stop_id: "de:03254:1002:1:1".into(), betriebsstelle: "Mehle Hp".into() },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_serialize() {
+        let serialized = se::to_string(&deserialized_stop_mapping()).unwrap();
+        assert_eq!(serialized, cleanup_xml(SERIALIZED_STOP_MAPPING.into()));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let deserialized: StopMapping = de::from_str(SERIALIZED_STOP_MAPPING).unwrap();
+        assert_eq!(deserialized, deserialized_stop_mapping());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use serde_helpers::with::duration::duration_format;
+use serde_helpers::with::duration::duration_option_format;
+use time::Duration;
+
+/// Clock-face copies of a Zug. All times are relative to midnight of the day of the Zug's first Abfahrt.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields, rename = "Takt")]
+pub struct TaktConfig {
+    /// Abfahrt of the first Zug
+    #[serde(rename = "@start", with = "duration_format")]
+    pub start: Duration,
+
+    /// Latest Abfahrt of the last Zug
+    #[serde(rename = "@end", with = "duration_format")]
+    pub end: Duration,
+
+    #[serde(rename = "@interval", with = "duration_format")]
+    pub interval: Duration,
+
+    /// Added to the Zugnummer for every copy
+    #[serde(rename = "@increment")]
+    pub increment: i32,
+
+    #[serde(rename = "TaktWindow", default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<TaktWindow>,
+}
+
+/// Time range with a different interval. Without an interval no Zuege depart within the range.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TaktWindow {
+    #[serde(rename = "@from", with = "duration_format")]
+    pub from: Duration,
+
+    #[serde(rename = "@to", with = "duration_format")]
+    pub to: Duration,
+
+    #[serde(rename = "@interval", with = "duration_option_format", default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<Duration>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::{de, se};
+    use serde_helpers::xml::test_utils::cleanup_xml;
+    use time::Duration;
+
+    const EXPECTED_SERIALIZED: &'static str = r#"
+        <Takt start="05:00:00" end="23:00:00" interval="00:30:00" increment="2">
+            <TaktWindow from="06:00:00" to="08:00:00" interval="00:15:00"/>
+            <TaktWindow from="12:00:00" to="13:00:00"/>
+        </Takt>
+    "#;
+
+    fn expected_deserialized() -> TaktConfig {
+        TaktConfig {
+            start: Duration::hours(5),
+            end: Duration::hours(23),
+            interval: Duration::minutes(30),
+            increment: 2,
+            windows: vec![
+                TaktWindow {
+                    from: Duration::hours(6),
+                    to: Duration::hours(8),
+                    interval: Some(Duration::minutes(15)),
+                },
+                TaktWindow {
+                    from: Duration::hours(12),
+                    to: Duration::hours(13),
+                    interval: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_serialize() {
+        let serialized = se::to_string(&expected_deserialized()).unwrap();
+        assert_eq!(serialized, cleanup_xml(EXPECTED_SERIALIZED.into()));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let deserialized: TaktConfig = de::from_str(EXPECTED_SERIALIZED).unwrap();
+        assert_eq!(deserialized, expected_deserialized());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,200 @@
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum VariableError {
+    #[error("The variable '{name}' is not defined.")]
+    UnknownVariable {
+        name: String,
+    },
+
+    #[error("A variable reference starting with '${{' is never closed.")]
+    UnclosedReference,
+
+    #[error("'{value}' is no variable assignment in the format 'name=value'.")]
+    InvalidAssignment {
+        value: String,
+    },
+
+    #[error("The variables couldn't be read: {error}")]
+    ReadError {
+        error: String,
+    },
+}
+
+/// Values which can be referenced by `${name}` in all attribute values of the config
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct VariablesConfig {
+    #[serde(rename = "Variable", default)]
+    pub variables: Vec<VariableConfig>,
+}
+
+impl VariablesConfig {
+    /// Replaces the values of already defined variables and adds all others.
+    pub fn set(&mut self, variables: &[VariableConfig]) {
+        for variable in variables {
+            match self.variables.iter_mut().find(|defined| defined.name == variable.name) {
+                Some(defined) => defined.value = variable.value.clone(),
+                None => self.variables.push(variable.clone()),
+            }
+        }
+    }
+}
+
+/// The value may reference variables defined before
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct VariableConfig {
+    #[serde(rename = "@name")]
+    pub name: String,
+
+    #[serde(rename = "@value")]
+    pub value: String,
+}
+
+impl FromStr for VariableConfig {
+    type Err = VariableError;
+
+    /// Parses an assignment in the format `name=value`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value
+            .split_once('=')
+            .filter(|(name, _)| !name.trim().is_empty())
+            .map(|(name, value)| VariableConfig { name: name.trim().into(), value: value.into() })
+            .ok_or_else(|| VariableError::InvalidAssignment { value: value.into() })
+    }
+}
+
+/// Replaces all variable references in the XML by the values of the variables.
+/// The given variables take precedence over the ones defined by `Variable` elements of the XML.
+pub fn substitute_variables(xml: &str, variables: &[VariableConfig]) -> Result<String, VariableError> {
+    let mut values: HashMap<String, String> = variables
+        .iter()
+        .map(|variable| (variable.name.clone(), variable.value.clone()))
+        .collect();
+    for variable in defined_variables(xml)? {
+        if !values.contains_key(&variable.name) {
+            let value = substitute(&variable.value, &values, false)?;
+            values.insert(variable.name, value);
+        }
+    }
+    substitute(xml, &values, true)
+}
+
+/// Variables defined by `Variable` elements in the order they are defined
+fn defined_variables(xml: &str) -> Result<Vec<VariableConfig>, VariableError> {
+    let read_error = |error: &dyn ToString| VariableError::ReadError { error: error.to_string() };
+    let mut reader = Reader::from_str(xml);
+    let mut variables = vec![];
+    loop {
+        match reader.read_event().map_err(|error| read_error(&error))? {
+            Event::Start(start) | Event::Empty(start) if start.name().as_ref() == b"Variable" => {
+                let mut name = None;
+                let mut value = None;
+                for attribute in start.attributes() {
+                    let attribute = attribute.map_err(|error| read_error(&error))?;
+                    let attribute_value = attribute.unescape_value().map_err(|error| read_error(&error))?.into_owned();
+                    match attribute.key.as_ref() {
+                        b"name" => name = Some(attribute_value),
+                        b"value" => value = Some(attribute_value),
+                        _ => {},
+                    }
+                }
+                // incomplete definitions are reported when the config is deserialized
+                if let (Some(name), Some(value)) = (name, value) {
+                    variables.push(VariableConfig { name, value });
+                }
+            },
+            Event::Eof => return Ok(variables),
+            _ => {},
+        }
+    }
+}
+
+fn substitute(text: &str, values: &HashMap<String, String>, escape_values: bool) -> Result<String, VariableError> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let length = rest[start..].find('}').ok_or(VariableError::UnclosedReference)?;
+        let name = rest[start + 2..start + length].trim();
+        let value = values.get(name).ok_or_else(|| VariableError::UnknownVariable { name: name.into() })?;
+        if escape_values {
+            result.push_str(&escape(value.as_str()));
+        } else {
+            result.push_str(value);
+        }
+        rest = &rest[start + length + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"
+        <Fahrplan generateAt="./${variant}.fpn">
+            <Variables>
+                <Variable name="day" value="2024-06-20"/>
+                <Variable name="variant" value="weekday-${day}"/>
+            </Variables>
+            <TimeFix type="StartAbf" value="${day} 08:00:00"/>
+        </Fahrplan>
+    "#;
+
+    #[test]
+    fn test_substitute_variables() {
+        assert_eq!(
+            substitute_variables(XML, &[]).unwrap(),
+            r#"
+        <Fahrplan generateAt="./weekday-2024-06-20.fpn">
+            <Variables>
+                <Variable name="day" value="2024-06-20"/>
+                <Variable name="variant" value="weekday-2024-06-20"/>
+            </Variables>
+            <TimeFix type="StartAbf" value="2024-06-20 08:00:00"/>
+        </Fahrplan>
+    "#,
+        );
+    }
+
+    #[test]
+    fn test_substitute_variables_with_overrides() {
+        let overrides = vec![
+            "day=2024-06-22".parse().unwrap(),
+            VariableConfig { name: "extra".into(), value: "A & B".into() },
+        ];
+        let substituted = substitute_variables(&XML.replace("./${variant}", "${extra}/${variant}"), &overrides).unwrap();
+
+        assert!(substituted.contains(r#"generateAt="A &amp; B/weekday-2024-06-22.fpn""#));
+        assert!(substituted.contains(r#"value="2024-06-22 08:00:00""#));
+    }
+
+    #[test]
+    fn test_substitute_unknown_variable() {
+        assert_eq!(
+            substitute_variables(r#"<Fahrplan generateAt="${unknown}.fpn"/>"#, &[]),
+            Err(VariableError::UnknownVariable { name: "unknown".into() }),
+        );
+    }
+
+    #[test]
+    fn test_parse_variable() {
+        assert_eq!(
+            "day=2024-06-20".parse(),
+            Ok(VariableConfig { name: "day".into(), value: "2024-06-20".into() }),
+        );
+        assert_eq!(
+            "=value".parse::<VariableConfig>(),
+            Err(VariableError::InvalidAssignment { value: "=value".into() }),
+        );
+    }
+}
\ No newline at end of file
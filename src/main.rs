@@ -1,14 +1,33 @@
 use clap::Parser;
 use serde_helpers::xml::{FromXML, ToXML};
+use std::fs;
+use std::fs::File;
 use std::path::Path;
-use zusi_fahrplan_generator::cli::{Cli, CliCommand, CliScheduleCommand};
-use zusi_fahrplan_generator::core::generate_fahrplan::generate_fahrplan;
-use zusi_fahrplan_generator::core::lib::helpers::read_zug;
+use time::Duration;
+use zusi_fahrplan_generator::cli::{Cli, CliCommand, CliExportCommand, CliImportCommand, CliScheduleCommand, ImportConfigArgs};
+use zusi_fahrplan_generator::core::export::bildfahrplan::render_bildfahrplan;
+use zusi_fahrplan_generator::core::export::read_fahrplan_zuege::read_fahrplan_zuege;
+use zusi_fahrplan_generator::core::export::timetable_csv::{zuege_to_csv, CsvLayout};
+use zusi_fahrplan_generator::core::generate_fahrplan::validate_fahrplan::validate_fahrplan;
+use zusi_fahrplan_generator::core::import::gtfs::{read_gtfs_feed, read_gtfs_zuege};
+use zusi_fahrplan_generator::core::import::import_zuege::{import_zuege, ImportedSchedule, ImportedZug, RouteTemplate};
+use zusi_fahrplan_generator::core::import::timetable_csv::read_timetable_csv;
+use zusi_fahrplan_generator::core::generate_fahrplan::{prepare_fahrplan, write_fahrplan, GenerateFahrplanError};
+use zusi_fahrplan_generator::core::lib::generated_fahrplan::ReportFormat;
+use zusi_fahrplan_generator::core::lib::generated_zug::RawGeneratedZug;
+use zusi_fahrplan_generator::core::lib::helpers::{read_buchfahrplan, read_zug};
 use zusi_fahrplan_generator::core::schedules::apply::apply_schedule;
-use zusi_fahrplan_generator::core::schedules::generate::generate_schedule;
+use zusi_fahrplan_generator::core::schedules::combine::combine_schedules;
+use zusi_fahrplan_generator::core::schedules::generate::{generate_schedule, generate_schedule_from_buchfahrplan};
+use zusi_fahrplan_generator::core::schedules::transform::{transform_schedule, RecoveryMargin, ScheduleTransformation};
+use zusi_fahrplan_generator::core::schedules::update_buchfahrplan::update_buchfahrplan;
+use zusi_fahrplan_generator::input::config_file::{read_config, read_config_with_variables, write_config};
 use zusi_fahrplan_generator::input::environment::zusi_environment_config::ZusiEnvironmentConfig;
 use zusi_fahrplan_generator::input::fahrplan_config::FahrplanConfig;
 use zusi_fahrplan_generator::input::schedule::Schedule;
+use zusi_fahrplan_generator::input::stop_mapping::StopMapping;
+use zusi_xml_lib::xml::zusi::lib::datei::Datei;
+use zusi_xml_lib::xml::zusi::lib::path::prejoined_zusi_path::PrejoinedZusiPath;
 use zusi_xml_lib::xml::zusi::Zusi;
 
 fn main() -> Result<(), String> {
@@ -18,46 +37,256 @@ fn main() -> Result<(), String> {
         CliCommand::GenerateFahrplan(args) => {
             let config_path = args.config;
 
-            println!(r#"Generate Fahrplan using config file at "{}""#, config_path.display());
-            let config = ZusiEnvironmentConfig::<FahrplanConfig>::from_xml_file_by_path(&config_path)
+            if args.report.is_none() {
+                println!(r#"Generate Fahrplan using config file at "{}""#, config_path.display());
+            }
+            let config = read_config_with_variables::<ZusiEnvironmentConfig<FahrplanConfig>, _>(&config_path, &args.variables)
                 .map_err(|error| format!("Couldn't read the config file: {error}"))?;
-            let (environment, fahrplan_config) = config.into_zusi_environment(config_path)
+            let (environment, mut fahrplan_config) = config.into_zusi_environment(config_path)
                 .map_err(|error| format!("Couldn't create the ZusiEnvironment: {error}"))?;
+            if args.report.is_none() {
+                println!("{environment}");
+            }
+            fahrplan_config.collect_errors |= args.collect_errors;
+            fahrplan_config.variables.set(&args.variables);
+            let generated_fahrplan = prepare_fahrplan(&environment, fahrplan_config).map_err(|error| format!("{error}"))?;
+            generated_fahrplan.zug_collisions
+                .iter()
+                .for_each(|collision| eprintln!("Warning: {collision}"));
+            generated_fahrplan.conflicts
+                .iter()
+                .for_each(|conflict| eprintln!("Warning: conflict between {conflict}"));
+            if let Some(conflict_report_path) = args.conflict_report.as_ref().filter(|_| !args.dry_run) {
+                let report = serde_json::to_string_pretty(&generated_fahrplan.conflicts)
+                    .map_err(|error| format!("Couldn't create the conflict report: {error}"))?;
+                fs::write(conflict_report_path, report)
+                    .map_err(|error| format!(r#"Couldn't write the conflict report to "{}": {error}"#, conflict_report_path.display()))?;
+            }
+            if !args.dry_run && (args.bildfahrplan.is_some() || args.csv.is_some()) {
+                let zuege: Vec<RawGeneratedZug> = generated_fahrplan.zuege.iter().map(RawGeneratedZug::from).collect();
+                if let Some(bildfahrplan_path) = &args.bildfahrplan {
+                    write_bildfahrplan(&zuege, bildfahrplan_path)?;
+                }
+                if let Some(csv_path) = &args.csv {
+                    write_csv(&zuege, args.csv_layout, ',', csv_path)?;
+                }
+            }
+            if args.dry_run {
+                let mut dry_run_report = generated_fahrplan.dry_run_report();
+                if let Some(conflict_report_path) = args.conflict_report {
+                    dry_run_report.add_file(conflict_report_path);
+                }
+                if let Some(bildfahrplan_path) = args.bildfahrplan {
+                    dry_run_report.add_file(bildfahrplan_path);
+                }
+                if let Some(csv_path) = args.csv {
+                    dry_run_report.add_file(csv_path);
+                }
+                print!("{dry_run_report}");
+                return Ok(());
+            }
+            let report = args.report
+                .map(|format| match format {
+                    ReportFormat::Json => serde_json::to_string_pretty(&generated_fahrplan.report()),
+                })
+                .transpose()
+                .map_err(|error| format!("Couldn't create the report: {error}"))?;
+            let result = write_fahrplan(generated_fahrplan);
+            // the report lists the Zuege which couldn't be generated, so it is printed before their error is passed on
+            let zuege_written = matches!(result, Ok(()) | Err(GenerateFahrplanError::GenerateZuegeErrors { .. }));
+            if let Some(report) = report.filter(|_| zuege_written) {
+                println!("{report}");
+            }
+            result.map_err(|error| format!("{error}"))
+        },
+        CliCommand::Validate(args) => {
+            let config_path = args.config;
+
+            println!(r#"Validate config file at "{}""#, config_path.display());
+            let config = read_config_with_variables::<ZusiEnvironmentConfig<FahrplanConfig>, _>(&config_path, &args.variables)
+                .map_err(|error| format!("Couldn't read the config file: {error}"))?;
+            let (environment, mut fahrplan_config) = config.into_zusi_environment(config_path)
+                .map_err(|error| format!("Couldn't create the ZusiEnvironment: {error}"))?;
+            fahrplan_config.variables.set(&args.variables);
             println!("{environment}");
-            generate_fahrplan(&environment, fahrplan_config).map_err(|error| format!("{error}"))
+            let errors = validate_fahrplan(&environment, fahrplan_config);
+            if errors.is_empty() {
+                println!("No problems found.");
+                Ok(())
+            } else {
+                errors.iter().for_each(|error| eprintln!("- {error}"));
+                Err(format!("Found {} problem(s) in the config.", errors.len()))
+            }
+        },
+        CliCommand::ConvertConfig(args) => {
+            let config = read_config::<ZusiEnvironmentConfig<FahrplanConfig>, _>(&args.input)
+                .map_err(|error| format!("Couldn't read the config file: {error}"))?;
+            write_config(&config, &args.output)
+                .map_err(|error| format!("Couldn't write the config file: {error}"))
         },
         CliCommand::Schedule(CliScheduleCommand::Apply(args)) => {
             let schedule_path = args.schedule;
             let trn_file_paths = args.trn_files;
+            let data_dir = args.data_dir;
 
             let schedule = Schedule::from_xml_file_by_path(schedule_path)
                 .map_err(|error| format!("Couldn't read the schedule file: {error}"))?;
             trn_file_paths
                 .into_iter()
                 .for_each(|trn_file_path| {
-                    apply_schedule_to_file(&schedule, &trn_file_path) // TODO: update_buchfahrplan if BuchfahrplanDatei is present? (currently impossible due to missing data_dir)
+                    apply_schedule_to_file(&schedule, &trn_file_path, data_dir.as_deref())
                         .unwrap_or_else(|error| eprintln!(r#"Error occoured for "{}": {error}"#, trn_file_path.display()));
                 });
             Ok(())
         },
         CliCommand::Schedule(CliScheduleCommand::Generate(args)) => {
             let schedule_path = args.schedule;
-            let trn_file_path = args.trn;
 
-            let zug = read_zug(trn_file_path)
-                .map_err(|error| format!(r"Couldn't read the trn file: {error}"))?;
-            let schedule = generate_schedule(&zug.value.fahrplan_eintraege);
+            let schedule = if let Some(timetable_file_path) = args.timetable {
+                let buchfahrplan = read_buchfahrplan(timetable_file_path)
+                    .map_err(|error| format!(r"Couldn't read the Buchfahrplan file: {error}"))?;
+                generate_schedule_from_buchfahrplan(&buchfahrplan.value.fahrplan_zeilen, args.extra_data)
+            } else {
+                let schedules = args.trn
+                    .into_iter()
+                    .map(|trn_file_path| {
+                        let zug = read_zug(&trn_file_path)
+                            .map_err(|error| format!(r#"Couldn't read the trn file "{trn_file_path}": {error}"#))?;
+                        Ok(generate_schedule(&zug.value.fahrplan_eintraege))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                combine_schedules(&schedules, args.combine)
+                    .map_err(|error| format!(r"Couldn't combine the schedules: {error}"))?
+            };
             schedule.to_xml_file_by_path(schedule_path, true).map_err(|error| format!(r"Couldn't write the schedule file: {error}"))
-        }
+        },
+        CliCommand::Schedule(CliScheduleCommand::Transform(args)) => {
+            let schedule = Schedule::from_xml_file_by_path(args.schedule)
+                .map_err(|error| format!("Couldn't read the schedule file: {error}"))?;
+            let transformation = ScheduleTransformation {
+                driving_time_factor: args.scale,
+                recovery_margin: match (args.recovery_seconds, args.recovery_percent) {
+                    (Some(seconds), _) => Some(RecoveryMargin::Fixed(Duration::seconds(seconds))),
+                    (None, Some(percentage)) => Some(RecoveryMargin::Percentage(percentage)),
+                    (None, None) => None,
+                },
+                min_stop_time: args.min_stop_time.map(Duration::seconds),
+                round_to: args.round,
+            };
+            transform_schedule(&schedule, &transformation)
+                .to_xml_file_by_path(args.output, true)
+                .map_err(|error| format!(r"Couldn't write the schedule file: {error}"))
+        },
+        CliCommand::Export(CliExportCommand::Bildfahrplan(args)) => {
+            let zuege = read_fahrplan_zuege(args.fahrplan, &args.data_dir)
+                .map_err(|error| format!("Couldn't read the Fahrplan: {error}"))?;
+            write_bildfahrplan(&zuege, &args.output)
+        },
+        CliCommand::Export(CliExportCommand::Csv(args)) => {
+            let zuege = read_fahrplan_zuege(args.fahrplan, &args.data_dir)
+                .map_err(|error| format!("Couldn't read the Fahrplan: {error}"))?;
+            write_csv(&zuege, args.layout, args.separator, &args.output)
+        },
+        CliCommand::Import(CliImportCommand::Csv(args)) => {
+            let csv = fs::read_to_string(&args.input)
+                .map_err(|error| format!(r#"Couldn't read the CSV file "{}": {error}"#, args.input.display()))?;
+            let zuege = read_timetable_csv(&csv, args.separator, args.date)
+                .map_err(|error| format!("Couldn't read the CSV timetable: {error}"))?;
+            write_imported_fahrplan(&zuege, args.config)
+        },
+        CliCommand::Import(CliImportCommand::Gtfs(args)) => {
+            let file = File::open(&args.input)
+                .map_err(|error| format!(r#"Couldn't open the GTFS feed "{}": {error}"#, args.input.display()))?;
+            let feed = read_gtfs_feed(file)
+                .map_err(|error| format!("{error}"))?;
+            let mapping = StopMapping::from_xml_file_by_path(&args.mapping)
+                .map_err(|error| format!("Couldn't read the stop mapping file: {error}"))?;
+            let zuege = read_gtfs_zuege(&feed, &mapping, args.date)
+                .map_err(|error| format!("Couldn't import the GTFS trips: {error}"))?;
+            write_imported_fahrplan(&zuege, args.config)
+        },
     }
 }
 
-fn apply_schedule_to_file<P: AsRef<Path>>(schedule: &Schedule, trn_file_path: P) -> Result<(), String> {
+fn write_bildfahrplan(zuege: &[RawGeneratedZug], path: &Path) -> Result<(), String> {
+    let svg = render_bildfahrplan(zuege)
+        .map_err(|error| format!("Couldn't render the Bildfahrplan: {error}"))?;
+    fs::write(path, svg)
+        .map_err(|error| format!(r#"Couldn't write the Bildfahrplan to "{}": {error}"#, path.display()))
+}
+
+fn write_csv(zuege: &[RawGeneratedZug], layout: CsvLayout, separator: char, path: &Path) -> Result<(), String> {
+    fs::write(path, zuege_to_csv(zuege, layout, separator))
+        .map_err(|error| format!(r#"Couldn't write the CSV file to "{}": {error}"#, path.display()))
+}
+
+fn write_imported_fahrplan(zuege: &[ImportedZug], args: ImportConfigArgs) -> Result<(), String> {
+    let config_dir = args.config.parent().unwrap_or(Path::new(""));
+    let templates = args.templates
+        .into_iter()
+        .map(|path| {
+            let zug = read_zug(config_dir.join(&path))
+                .map_err(|error| format!("Couldn't read the route template: {error}"))?;
+            Ok(RouteTemplate { path, fahrplan_eintraege: zug.value.fahrplan_eintraege })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let imported = import_zuege(zuege, &templates, &args.rolling_stock)
+        .map_err(|error| format!("Couldn't import the Zuege: {error}"))?;
+    imported.warnings
+        .iter()
+        .for_each(|warning| eprintln!("Warning: {warning}"));
+
+    for ImportedSchedule { path, schedule } in &imported.schedules {
+        schedule.to_xml_file_by_path(config_dir.join(path), true)
+            .map_err(|error| format!(r"Couldn't write the schedule file: {error}"))?;
+    }
+    let config = ZusiEnvironmentConfig {
+        data_dir: args.data_dir,
+        value: FahrplanConfig {
+            generate_at: args.generate_at,
+            generate_from: args.generate_from,
+            collect_errors: false,
+            zug_collisions: Default::default(),
+            variables: Default::default(),
+            time_rounding: None,
+            umlaeufe: vec![],
+            conflict_detection: None,
+            header: None,
+            str_module: None,
+            templates: vec![],
+            includes: vec![],
+            zuege: imported.zug_configs,
+        },
+    };
+    write_config(&config, &args.config)
+        .map_err(|error| format!(r"Couldn't write the config file: {error}"))
+}
+
+fn apply_schedule_to_file<P: AsRef<Path>>(schedule: &Schedule, trn_file_path: P, data_dir: Option<&Path>) -> Result<(), String> {
     let trn_file_path = trn_file_path.as_ref();
     let mut zug = read_zug(trn_file_path)
         .map_err(|error| format!(r"Couldn't read the trn file: {error}"))?;
     apply_schedule(&mut zug.value.fahrplan_eintraege, schedule)
         .map_err(|error| format!(r"Couldn't apply the schedule: {error}"))?;
+
+    let buchfahrplan = match (data_dir, &zug.value.buchfahrplan_roh_datei) {
+        (Some(data_dir), Some(Datei { dateiname, .. })) => {
+            let buchfahrplan_path = PrejoinedZusiPath::new(data_dir, dateiname.clone());
+            let mut buchfahrplan = read_buchfahrplan(buchfahrplan_path.full_path())
+                .map_err(|error| format!(r"Couldn't read the Buchfahrplan file: {error}"))?;
+            update_buchfahrplan(&zug.value.fahrplan_eintraege, &mut buchfahrplan.value.fahrplan_zeilen)
+                .map_err(|error| format!(r"Couldn't update the Buchfahrplan: {error}"))?;
+            Some((buchfahrplan_path, buchfahrplan))
+        },
+        _ => None,
+    };
+
+    if let Some((buchfahrplan_path, buchfahrplan)) = buchfahrplan {
+        let buchfahrplan: Zusi = buchfahrplan.into();
+        buchfahrplan.to_xml_file_by_path(buchfahrplan_path.full_path(), true)
+            .map_err(|error| format!(r"Couldn't write the Buchfahrplan file: {error}"))?;
+    }
+
     let zug: Zusi = zug.into();
     zug.to_xml_file_by_path(trn_file_path, true)
         .map_err(|error| format!(r"Couldn't write the trn file: {error}"))?;
@@ -0,0 +1,27 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use serde_helpers::xml::test_utils::read_xml_file;
+use std::process::Command;
+
+#[test]
+fn test_apply_schedule_with_buchfahrplan() {
+    let tmp_dir = TmpDirHelper::from("./tests/apply_schedule_with_buchfahrplan/input");
+
+    let data_dir = tmp_dir.path().join("data_dir");
+    let schedule_path = tmp_dir.path().join("data_dir/path/to/schedule.xml");
+    let trn_path = tmp_dir.path().join("data_dir/some/where/RB1.trn");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("schedule").arg("apply").arg("-s").arg(&schedule_path).arg("-t").arg(&trn_path).arg("-d").arg(&data_dir)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/apply_schedule_with_buchfahrplan/expected", true, |actual, expected| {
+        assert_eq!(read_xml_file(actual), read_xml_file(expected));
+    });
+}
\ No newline at end of file
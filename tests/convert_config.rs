@@ -0,0 +1,35 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use serde_helpers::xml::test_utils::read_xml_file;
+use std::process::Command;
+
+#[test]
+fn test_convert_config() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan/input");
+
+    let config_dir = tmp_dir.path().join("data_dir/dev");
+
+    for (input, output) in [("config.xml", "config.yaml"), ("config.yaml", "config.toml"), ("config.toml", "converted-config.xml")] {
+        let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+        cmd.arg("convert-config").arg("-i").arg(config_dir.join(input)).arg("-o").arg(config_dir.join(output))
+            .assert()
+            .stdout("")
+            .stderr("")
+            .success();
+    }
+
+    assert_eq!(read_xml_file(config_dir.join("converted-config.xml")), read_xml_file(config_dir.join("config.xml")));
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("generate-fahrplan").arg("-c").arg(config_dir.join("config.toml"))
+        .assert()
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/generate_fahrplan/expected", true, |actual, expected| {
+        assert_eq!(read_xml_file(actual), read_xml_file(expected));
+    });
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_export_bildfahrplan() {
+    let tmp_dir = TmpDirHelper::from("./tests/export_bildfahrplan/input");
+
+    let data_dir = tmp_dir.path().join("data_dir");
+    let fahrplan_path = data_dir.join("out/my-fahrplan.fpn");
+    let output_path = tmp_dir.path().join("bildfahrplan.svg");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("export").arg("bildfahrplan").arg("-f").arg(&fahrplan_path).arg("-d").arg(&data_dir).arg("-o").arg(&output_path)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    let svg = fs::read_to_string(output_path).unwrap();
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg""#));
+    assert!(svg.contains(r#"rotate(-60)">Elze (32.9)</text>"#));
+    assert!(svg.contains(r#"rotate(-60)">Voldagsen (12.8)</text>"#));
+    assert!(svg.contains("<title>RB 20000</title>"));
+    assert!(svg.contains("<title>RB 20002</title>"));
+    assert!(svg.contains(">08:30</text>"));
+    assert!(svg.contains(">11:00</text>"));
+}
\ No newline at end of file
@@ -0,0 +1,61 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_export_csv_per_zug() {
+    let tmp_dir = TmpDirHelper::from("./tests/export_csv/input");
+
+    let data_dir = tmp_dir.path().join("data_dir");
+    let fahrplan_path = data_dir.join("out/my-fahrplan.fpn");
+    let output_path = tmp_dir.path().join("timetable.csv");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("export").arg("csv").arg("-f").arg(&fahrplan_path).arg("-d").arg(&data_dir).arg("-o").arg(&output_path)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    let csv = fs::read_to_string(output_path).unwrap();
+    assert!(csv.starts_with("Gattung,Nummer,Betriebsstelle,Ankunft,Abfahrt,Signal,FahrzeugVerbandAktion\n"));
+    assert!(csv.contains("RB,20000,Elze,2023-06-20 08:38:00,2023-06-20 08:41:40,N1,\n"));
+    assert!(csv.contains("RB,20000,Voldagsen,,,A,\n"));
+    assert!(csv.contains("RB,20002,Mehle Hp,,2023-06-20 10:45:00,,\n"));
+    assert_eq!(csv.lines().count(), 11);
+}
+
+#[test]
+fn test_export_csv_matrix() {
+    let tmp_dir = TmpDirHelper::from("./tests/export_csv/input");
+
+    let data_dir = tmp_dir.path().join("data_dir");
+    let fahrplan_path = data_dir.join("out/my-fahrplan.fpn");
+    let output_path = tmp_dir.path().join("timetable.csv");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("export").arg("csv").arg("-f").arg(&fahrplan_path).arg("-d").arg(&data_dir).arg("-o").arg(&output_path)
+        .arg("--layout").arg("matrix").arg("--separator").arg(";")
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(output_path).unwrap(),
+        concat!(
+            "Betriebsstelle;;RB 20000;RB 20002\n",
+            "Elze;an;08:38:00;10:38:00\n",
+            "Elze;ab;08:41:40;10:41:40\n",
+            "Mehle Hp;;08:45:00;10:45:00\n",
+            "Osterwald Hp;an;08:48:00;10:48:00\n",
+            "Osterwald Hp;ab;08:48:50;10:48:50\n",
+            "Voldagsen;an;08:52:20;10:52:20\n",
+            "Voldagsen;ab;08:53:00;10:53:00\n",
+        ),
+    );
+}
\ No newline at end of file
@@ -0,0 +1,45 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_generate_fahrplan_conflicts() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan_conflicts/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+    let report_path = tmp_dir.path().join("conflicts.json");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("generate-fahrplan").arg("-c").arg(&config_path).arg("--conflict-report").arg(&report_path)
+        .assert()
+        .stderr(predicates::str::is_match(concat!(
+            r#"^Warning: conflict between RB20000 \([0-9:\.]+ - [0-9:\.]+\) and RB20002 \([0-9:\.]+ - [0-9:\.]+\) at signal 'N1' of 'Elze'\r?\n"#,
+            r#"Warning: conflict between RB20000 \([0-9:\.]+ - [0-9:\.]+\) and RB20002 \([0-9:\.]+ - [0-9:\.]+\) at 'Mehle Hp'\r?\n"#,
+        )).unwrap())
+        .success();
+
+    let report = fs::read_to_string(report_path).unwrap();
+    assert!(report.contains(r#""type": "Signal""#));
+    assert!(report.contains(r#""betriebsstelle": "Osterwald Hp""#));
+    assert!(report.contains(r#""nummer": "20002""#));
+}
+
+#[test]
+fn test_generate_fahrplan_conflicts_dry_run() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan_conflicts/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+    let report_path = tmp_dir.path().join("conflicts.json");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("generate-fahrplan").arg("-c").arg(&config_path).arg("--conflict-report").arg(&report_path).arg("--dry-run")
+        .assert()
+        .stdout(predicates::str::is_match(r#"  \[create\] "/[a-zA-Z0-9\./-_]+/conflicts\.json"\r?\n"#).unwrap())
+        .success();
+
+    assert!(!report_path.exists());
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_generate_fahrplan_dry_run() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("generate-fahrplan").arg("-c").arg(&config_path).arg("--dry-run")
+        .assert()
+        .stdout(predicates::str::is_match(concat!(
+            r#"(?s)^Generate Fahrplan using config file at ".+"\r?\n.*"#,
+            r#"Dry run: no files were written\.\r?\n"#,
+            r#"Files to write:\r?\n"#,
+            r#"  \[create\] "/[a-zA-Z0-9\./-_]+/data_dir/out/my-fahrplan/RB20000\.trn"\r?\n.*"#,
+            r#"  \[create\] "/[a-zA-Z0-9\./-_]+/data_dir/out/my-fahrplan\.fpn"\r?\n"#,
+            r#"ZugDateiEintraege:\r?\n"#,
+            r#"  "out/my-fahrplan/RB20000\.trn"\r?\n.*"#,
+            r#"Existing files which would be replaced: none(\r\n|\n)*$"#,
+        )).unwrap())
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/generate_fahrplan/input", false, |actual, expected| {
+        assert_eq!(fs::read_to_string(actual).unwrap(), fs::read_to_string(expected).unwrap());
+    });
+}
+
+
+#[test]
+fn test_generate_fahrplan_dry_run_with_exports() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+    let bildfahrplan_path = tmp_dir.path().join("bildfahrplan.svg");
+    let csv_path = tmp_dir.path().join("timetable.csv");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("generate-fahrplan").arg("-c").arg(&config_path).arg("--dry-run")
+        .arg("--bildfahrplan").arg(&bildfahrplan_path).arg("--csv").arg(&csv_path)
+        .assert()
+        .stdout(predicates::str::is_match(concat!(
+            r#"(?s)  \[create\] "/[a-zA-Z0-9\./-_]+/data_dir/out/my-fahrplan\.fpn"\r?\n"#,
+            r#"  \[create\] "/[a-zA-Z0-9\./-_]+/bildfahrplan\.svg"\r?\n"#,
+            r#"  \[create\] "/[a-zA-Z0-9\./-_]+/timetable\.csv"\r?\n"#,
+        )).unwrap())
+        .success();
+
+    assert!(!bildfahrplan_path.exists());
+    assert!(!csv_path.exists());
+}
\ No newline at end of file
@@ -0,0 +1,94 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use serde_json::{json, Value};
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_generate_fahrplan_report() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    let output = cmd.arg("generate-fahrplan").arg("-c").arg(&config_path).arg("--report").arg("json")
+        .assert()
+        .stderr("")
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let report: Value = serde_json::from_slice(&output).unwrap();
+    assert!(report["fahrplan"].as_str().unwrap().ends_with("data_dir/out/my-fahrplan.fpn"));
+    assert_eq!(report["failed_zuege"], json!([]));
+
+    let zuege = report["zuege"].as_array().unwrap();
+    assert_eq!(zuege.len(), 4);
+    assert!(tmp_dir.path().join("data_dir/out/my-fahrplan/RB20000.trn").exists());
+
+    let zug = zuege.iter().find(|zug| zug["nummer"] == "20000").unwrap();
+    assert_eq!(zug["gattung"], "RB");
+    assert!(zug["path"].as_str().unwrap().ends_with("data_dir/out/my-fahrplan/RB20000.trn"));
+    assert_eq!(zug["buchfahrplan"], Value::Null);
+    assert_eq!(zug["start"], json!({ "betriebsstelle": "Elze", "time": "2023-06-20 08:41:40" }));
+    assert_eq!(zug["end"], json!({ "betriebsstelle": "Voldagsen", "time": "2023-06-20 08:52:20" }));
+    assert_eq!(zug["origin"]["config_nummer"], "20000");
+    assert_eq!(zug["origin"]["copy_delay"], Value::Null);
+    assert_eq!(zug["origin"]["rolling_stock"], "./rolling-stock-a.trn");
+    assert_eq!(zug["warnings"], json!([]));
+
+    let copy = zuege.iter().find(|zug| zug["nummer"] == "20004").unwrap();
+    assert_eq!(copy["origin"]["config_nummer"], "20000");
+    assert_eq!(copy["origin"]["copy_delay"]["task"], 2);
+    assert_eq!(copy["origin"]["copy_delay"]["copy"], 2);
+    assert_eq!(copy["origin"]["rolling_stock"], "./rolling-stock-b.trn");
+}
+
+#[test]
+fn test_generate_fahrplan_report_with_failed_zuege() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+    let config = fs::read_to_string(&config_path).unwrap().replace("    </Fahrplan>", r#"        <Zug nummer="30000" gattung="RE">
+            <Route>
+                <RoutePart>
+                    <TrainFileByPath path="./missing-route-part.trn"/>
+                </RoutePart>
+            </Route>
+            <RollingStock path="./rolling-stock-a.trn"/>
+        </Zug>
+    </Fahrplan>"#);
+    fs::write(&config_path, config).unwrap();
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    let output = cmd.arg("generate-fahrplan").arg("-c").arg(&config_path).arg("--collect-errors").arg("--report").arg("json")
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+
+    let report: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["zuege"].as_array().unwrap().len(), 4);
+    assert_eq!(report["failed_zuege"].as_array().unwrap().len(), 1);
+    assert!(tmp_dir.path().join("data_dir/out/my-fahrplan/RB20000.trn").exists());
+}
+
+#[test]
+fn test_generate_fahrplan_report_conflicts_with_dry_run() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("generate-fahrplan").arg("-c").arg(&config_path).arg("--report").arg("json").arg("--dry-run")
+        .assert()
+        .stdout("")
+        .failure();
+
+    assert!(!tmp_dir.path().join("data_dir/out").exists());
+}
\ No newline at end of file
@@ -0,0 +1,24 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use serde_helpers::xml::test_utils::read_xml_file;
+use std::process::Command;
+
+#[test]
+fn test_generate_fahrplan_templates() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan_templates/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("generate-fahrplan").arg("-c").arg(&config_path)
+        .assert()
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/generate_fahrplan/expected", false, |actual, expected| {
+        assert_eq!(read_xml_file(actual), read_xml_file(expected));
+    });
+}
\ No newline at end of file
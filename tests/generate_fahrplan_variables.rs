@@ -0,0 +1,40 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use serde_helpers::xml::test_utils::read_xml_file;
+use std::process::Command;
+
+#[test]
+fn test_generate_fahrplan_variables() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan_variables/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("generate-fahrplan").arg("-c").arg(&config_path)
+        .arg("--set").arg("nummer=20000")
+        .arg("--set").arg("delay=06:00:00")
+        .arg("--set").arg("day=2023-06-20")
+        .assert()
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/generate_fahrplan/expected", false, |actual, expected| {
+        assert_eq!(read_xml_file(actual), read_xml_file(expected));
+    });
+}
+
+#[test]
+fn test_generate_fahrplan_unknown_variable() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan_variables/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("generate-fahrplan").arg("-c").arg(&config_path)
+        .arg("--set").arg("nummer=20000")
+        .assert()
+        .failure();
+}
\ No newline at end of file
@@ -0,0 +1,27 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use serde_helpers::xml::test_utils::read_xml_file;
+use std::process::Command;
+
+#[test]
+fn test_generate_schedule_combined() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_schedule_combined/input");
+
+    let trn_path_1 = tmp_dir.path().join("some/where/RB1.trn");
+    let trn_path_2 = tmp_dir.path().join("some/where/RB3.trn");
+    let schedule_path = tmp_dir.path().join("put/schedule/here/schedule.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("schedule").arg("generate").arg("-t").arg(&trn_path_1).arg(&trn_path_2).arg("-c").arg("max").arg("-s").arg(&schedule_path)
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/generate_schedule_combined/expected", true, |actual, expected| {
+        assert_eq!(read_xml_file(actual), read_xml_file(expected));
+    });
+}
\ No newline at end of file
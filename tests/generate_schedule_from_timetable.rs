@@ -0,0 +1,26 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use serde_helpers::xml::test_utils::read_xml_file;
+use std::process::Command;
+
+#[test]
+fn test_generate_schedule_from_timetable() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_schedule_from_timetable/input");
+
+    let timetable_path = tmp_dir.path().join("some/where/RB1.timetable.xml");
+    let schedule_path = tmp_dir.path().join("put/schedule/here/schedule.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("schedule").arg("generate").arg("-b").arg(&timetable_path).arg("-s").arg(&schedule_path).arg("--extra-data")
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/generate_schedule_from_timetable/expected", true, |actual, expected| {
+        assert_eq!(read_xml_file(actual), read_xml_file(expected));
+    });
+}
\ No newline at end of file
@@ -0,0 +1,38 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use serde_helpers::xml::test_utils::read_xml_file;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_import_csv() {
+    let tmp_dir = TmpDirHelper::from("./tests/import_csv/input");
+
+    let dev_dir = tmp_dir.path().join("data_dir/dev");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("import").arg("csv")
+        .arg("-i").arg(dev_dir.join("timetable.csv"))
+        .arg("--date").arg("2023-06-20")
+        .arg("-t").arg("./route.trn")
+        .arg("-r").arg("./rolling-stock.trn")
+        .arg("-c").arg(dev_dir.join("config.xml"))
+        .arg("-d").arg("../")
+        .arg("--generate-from").arg("./my-fahrplan.fpn")
+        .arg("--generate-at").arg("../out/imported.fpn")
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/import_csv/expected", true, |actual, expected| {
+        if actual.extension().is_some_and(|extension| extension == "csv") {
+            assert_eq!(fs::read_to_string(actual).unwrap(), fs::read_to_string(expected).unwrap());
+        } else {
+            assert_eq!(read_xml_file(actual), read_xml_file(expected));
+        }
+    });
+}
\ No newline at end of file
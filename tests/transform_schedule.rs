@@ -0,0 +1,48 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use serde_helpers::xml::test_utils::read_xml_file;
+use std::process::Command;
+
+#[test]
+fn test_transform_schedule() {
+    let tmp_dir = TmpDirHelper::from("./tests/transform_schedule/input");
+
+    let schedule_path = tmp_dir.path().join("schedules/fast.xml");
+    let output_path = tmp_dir.path().join("schedules/slow.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("schedule").arg("transform").arg("-s").arg(&schedule_path).arg("-o").arg(&output_path)
+        .arg("--scale").arg("1.1")
+        .arg("--recovery-seconds").arg("20")
+        .arg("--min-stop-time").arg("50")
+        .arg("--round").arg("half-minute")
+        .assert()
+        .stdout("")
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/transform_schedule/expected", true, |actual, expected| {
+        assert_eq!(read_xml_file(actual), read_xml_file(expected));
+    });
+}
+
+#[test]
+fn test_transform_schedule_with_invalid_factors() {
+    let tmp_dir = TmpDirHelper::from("./tests/transform_schedule/input");
+
+    let schedule_path = tmp_dir.path().join("schedules/fast.xml");
+    let output_path = tmp_dir.path().join("schedules/slow.xml");
+
+    for (arg, value) in [("--scale", "0"), ("--scale", "-1.1"), ("--scale", "NaN"), ("--scale", "inf"), ("--recovery-percent", "-101")] {
+        let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+        cmd.arg("schedule").arg("transform").arg("-s").arg(&schedule_path).arg("-o").arg(&output_path)
+            .arg(format!("{arg}={value}"))
+            .assert()
+            .stdout("")
+            .failure();
+        assert!(!output_path.exists());
+    }
+}
\ No newline at end of file
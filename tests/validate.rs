@@ -0,0 +1,48 @@
+mod utils;
+
+use crate::utils::tmp_dir_helper::TmpDirHelper;
+use assert_cmd::assert::OutputAssertExt;
+use assert_cmd::cargo::CommandCargoExt;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_validate_valid_config() {
+    let tmp_dir = TmpDirHelper::from("./tests/generate_fahrplan/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("validate").arg("-c").arg(&config_path)
+        .assert()
+        .stdout(predicates::str::ends_with("No problems found.\n"))
+        .stderr("")
+        .success();
+
+    tmp_dir.assert_with("./tests/generate_fahrplan/input", false, |actual, expected| {
+        assert_eq!(fs::read_to_string(actual).unwrap(), fs::read_to_string(expected).unwrap());
+    });
+}
+
+#[test]
+fn test_validate_reports_all_problems() {
+    let tmp_dir = TmpDirHelper::from("./tests/validate/input");
+
+    let config_path = tmp_dir.path().join("data_dir/dev/config.xml");
+
+    let mut cmd = Command::cargo_bin("zusi-fahrplan-generator").unwrap();
+    cmd.arg("validate").arg("-c").arg(&config_path)
+        .assert()
+        .stderr(predicates::str::is_match(concat!(
+            r#"(?s)^- Zug with Zugnummer '20000': The route is invalid: Route part 1: .*missing\.schedule\.xml.*\r?\n"#,
+            r#"- Zug with Zugnummer '20000': The route is invalid: Route part 2: .*missing-route-part-2\.trn.*\r?\n"#,
+            r#"- Zug with Zugnummer '20000': Couldn't read the meta data template file: .*missing-meta-data\.trn.*\r?\n"#,
+            r#"- Zug with Zugnummer '20000': The copy delay config is invalid: .*missing-rolling-stock-b\.trn.*\r?\n"#,
+            r#"Error: "Found 4 problem\(s\) in the config\."\r?\n$"#,
+        )).unwrap())
+        .failure();
+
+    tmp_dir.assert_with("./tests/validate/input", false, |actual, expected| {
+        assert_eq!(fs::read_to_string(actual).unwrap(), fs::read_to_string(expected).unwrap());
+    });
+}